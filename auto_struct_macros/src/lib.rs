@@ -222,6 +222,7 @@ struct DeriveOptions
     readable: bool,
     writable: bool,
     fixed_size: bool,
+    into_static: bool,
 }
 
 impl Parse for DeriveOptions
@@ -232,6 +233,7 @@ impl Parse for DeriveOptions
             readable: false,
             writable: false,
             fixed_size: false,
+            into_static: false,
         };
         let idents = Punctuated::<Ident, Token![,]>::parse_terminated(input)?;
         for ident in idents {
@@ -250,6 +252,11 @@ impl Parse for DeriveOptions
                     err(ident.span(), format!("Duplicate '{}'", ident))?;
                 }
                 options.fixed_size = true;
+            } else if ident == "IntoStatic" {
+                if options.into_static {
+                    err(ident.span(), format!("Duplicate '{}'", ident))?;
+                }
+                options.into_static = true;
             } else {
                 err(ident.span(), format!("Unknown option '{}'", ident))?;
             }
@@ -494,6 +501,53 @@ impl AutoStructDecl
 
     }
 
+    fn static_conversion_impl_tokens(&self) -> Result<proc_macro2::TokenStream>
+    {
+        let name = &self.struct_.ident;
+        let generics = &self.struct_.generics;
+        let has_reader_lifetime = generics.lifetimes().any(|ld| ld.lifetime.ident == "r");
+        let static_ty = if generics.params.is_empty() {
+            quote!(#name)
+        } else if has_reader_lifetime && generics.params.len() == 1 {
+            quote!(#name<'static>)
+        } else {
+            err(generics.span(), "IntoStatic requires 'r to be the only generic parameter")?
+        };
+        let (impl_gens, type_gens, where_clause) = generics.split_for_impl();
+
+        // Only the fields that borrow from the reader need converting, the rest are moved
+        let idents = self.fields.iter()
+            .filter(|field| field.has_storage())
+            .map(|field| &field.ident)
+            .collect::<Vec<_>>();
+        let exprs = self.fields.iter()
+            .filter(|field| field.has_storage())
+            .map(|field| {
+                let ident = &field.ident;
+                if mentions_reader_lifetime(field.ty.to_token_stream()) {
+                    quote!(reader_writer::IntoStatic::into_static(#ident))
+                } else {
+                    quote!(#ident)
+                }
+            });
+
+        Ok(quote! {
+            #[automatically_derived]
+            impl #impl_gens reader_writer::IntoStatic for #name #type_gens
+                #where_clause
+            {
+                type Static = #static_ty;
+                fn into_static(self) -> Self::Static
+                {
+                    let #name { #(#idents,)* } = self;
+                    #name {
+                        #(#idents: #exprs,)*
+                    }
+                }
+            }
+        })
+    }
+
     fn struct_and_impl_tokens(self, options: DeriveOptions) -> proc_macro2::TokenStream
     {
 
@@ -509,16 +563,45 @@ impl AutoStructDecl
             proc_macro2::TokenStream::new()
         };
 
+        let into_static_tokens = if options.into_static {
+            self.static_conversion_impl_tokens().unwrap_or_else(|e| e.to_compile_error())
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
         let struct_tokens = self.struct_.into_token_stream();
         quote! {
             #struct_tokens
             #readable_tokens
             #writable_tokens
+            #into_static_tokens
         }
     }
 }
 
 
+// Whether a type refers to the 'r lifetime anywhere, e.g. `Option<CStr<'r>>`
+fn mentions_reader_lifetime(tokens: proc_macro2::TokenStream) -> bool
+{
+    let mut prev_was_quote = false;
+    for tt in tokens {
+        let found = match &tt {
+            proc_macro2::TokenTree::Ident(ident) => prev_was_quote && ident == "r",
+            proc_macro2::TokenTree::Group(group) => mentions_reader_lifetime(group.stream()),
+            _ => false,
+        };
+        if found {
+            return true
+        }
+        prev_was_quote = match &tt {
+            proc_macro2::TokenTree::Punct(punct) => punct.as_char() == '\'',
+            _ => false,
+        };
+    }
+    false
+}
+
+
 #[proc_macro_attribute]
 pub fn auto_struct(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     if attr.is_empty() {
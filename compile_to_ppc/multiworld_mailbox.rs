@@ -0,0 +1,149 @@
+
+mod _multiworld_mailbox {
+    #![allow(dead_code)]
+
+    // The host finds the mailbox by searching the game's memory for this
+    pub const MULTIWORLD_MAILBOX_MAGIC: [u8; 8] = *b"RPMWMBX1";
+    pub const MULTIWORLD_MAILBOX_SENT_SLOTS: usize = 16;
+    pub const MULTIWORLD_MAILBOX_RECEIVED_SLOTS: usize = 16;
+    // One for every pickup location
+    pub const MULTIWORLD_SEND_QUEUE_LEN: usize = 100;
+
+    // CPlayerState::EItemType values the game doesn't use. The amount of the first counts the
+    // received items that have been given to the player, so it's kept in the save file along
+    // with the items themselves. Pickups of the second are sent to another player; their amount
+    // is one more than the pickup's location index.
+    pub const MULTIWORLD_RECEIVED_COUNTER_ITEM: i32 = 25;
+    pub const MULTIWORLD_SEND_ITEM: i32 = 27;
+
+    /// Every field is a big-endian u32 in the game's memory. The counts only ever grow; the slot
+    /// for the nth entry of either list is n modulo the list's length.
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    pub struct MultiworldMailbox
+    {
+        pub magic: [u8; 8],
+        // Set by the game once it has started using the mailbox
+        pub active: u32,
+
+        // Written by the game: location indices of the pickups sent to other players
+        pub sent_count: u32,
+        pub sent: [u32; MULTIWORLD_MAILBOX_SENT_SLOTS],
+        // Written by the host once it has forwarded the sent pickups
+        pub sent_acked: u32,
+
+        // Written by the host: the items the game should give to the player
+        pub received_count: u32,
+        pub received: [MultiworldReceivedItem; MULTIWORLD_MAILBOX_RECEIVED_SLOTS],
+        // Written by the game: how many received items the player already has
+        pub received_given: u32,
+    }
+
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    pub struct MultiworldReceivedItem
+    {
+        // CPlayerState::EItemType
+        pub item_type: u32,
+        pub amount: u32,
+        pub capacity: u32,
+    }
+
+    impl MultiworldMailbox
+    {
+        pub const fn new() -> Self
+        {
+            MultiworldMailbox {
+                magic: MULTIWORLD_MAILBOX_MAGIC,
+                active: 0,
+                sent_count: 0,
+                sent: [0; MULTIWORLD_MAILBOX_SENT_SLOTS],
+                sent_acked: 0,
+                received_count: 0,
+                received: [MultiworldReceivedItem {
+                    item_type: 0,
+                    amount: 0,
+                    capacity: 0,
+                }; MULTIWORLD_MAILBOX_RECEIVED_SLOTS],
+                received_given: 0,
+            }
+        }
+    }
+
+    impl Default for MultiworldMailbox
+    {
+        fn default() -> Self
+        {
+            Self::new()
+        }
+    }
+
+    /// The locations of pickups collected for other players that haven't been put in the
+    /// mailbox yet. The game takes a sent pickup's item away again as soon as it has been
+    /// collected and queues its location here, so that pickups collected while the mailbox is
+    /// full don't add their locations together.
+    #[derive(Copy, Clone)]
+    pub struct MultiworldSendQueue
+    {
+        locations: [u32; MULTIWORLD_SEND_QUEUE_LEN],
+        len: usize,
+    }
+
+    impl MultiworldSendQueue
+    {
+        pub const fn new() -> Self
+        {
+            MultiworldSendQueue {
+                locations: [0; MULTIWORLD_SEND_QUEUE_LEN],
+                len: 0,
+            }
+        }
+
+        pub fn is_empty(&self) -> bool
+        {
+            self.len == 0
+        }
+
+        /// Queues a location, unless it's already queued because the pickup was collected
+        /// again after the game was reloaded
+        pub fn push(&mut self, location_idx: u32)
+        {
+            if self.len < MULTIWORLD_SEND_QUEUE_LEN
+                && !self.locations[..self.len].contains(&location_idx) {
+                self.locations[self.len] = location_idx;
+                self.len += 1;
+            }
+        }
+
+        /// Moves as many queued locations into the mailbox's sent list as it has free slots
+        /// for, passing each slot and location to `write_slot`. Returns the new sent count.
+        pub fn flush(
+            &mut self,
+            sent_count: u32,
+            sent_acked: u32,
+            mut write_slot: impl FnMut(usize, u32),
+        ) -> u32
+        {
+            let in_use = sent_count.wrapping_sub(sent_acked) as usize;
+            let count = MULTIWORLD_MAILBOX_SENT_SLOTS.saturating_sub(in_use).min(self.len);
+            for (i, location_idx) in self.locations[..count].iter().enumerate() {
+                write_slot(
+                    (sent_count as usize + i) % MULTIWORLD_MAILBOX_SENT_SLOTS,
+                    *location_idx,
+                );
+            }
+            self.locations.copy_within(count..self.len, 0);
+            self.len -= count;
+            sent_count.wrapping_add(count as u32)
+        }
+    }
+
+    impl Default for MultiworldSendQueue
+    {
+        fn default() -> Self
+        {
+            Self::new()
+        }
+    }
+}
+pub use self::_multiworld_mailbox::*;
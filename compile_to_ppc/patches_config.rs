@@ -6,6 +6,9 @@ mod _rel_config {
     {
         pub quickplay_mlvl: u32,
         pub quickplay_mrea: u32,
+        pub item_tracker: bool,
+        pub multiworld: bool,
+        pub practice_mode: bool,
     }
 }
 pub(crate) use self::_rel_config::RelConfig;
@@ -3,6 +3,7 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
 use syn::{
+    ext::IdentExt,
     parenthesized,
     parse_macro_input,
     parse::Parser,
@@ -81,7 +82,8 @@ impl syn::parse::Parse for Flags
                 if kind.is_some() {
                     Err(syn::Error::new(pair.ident.span(), "Duplicate `kind` flag"))?;
                 }
-                let ident = <syn::Ident as syn::parse::Parse>::parse.parse2(pair.tokens)?;
+                // `return` is a keyword, so it has to be parsed as any identifier
+                let ident = syn::Ident::parse_any.parse2(pair.tokens)?;
 
                 kind = if ident == "call" {
                     Some(PatchKind::Call)
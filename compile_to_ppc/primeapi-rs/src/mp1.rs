@@ -57,20 +57,51 @@ impl CStringTable
 //     capacity: u32,
 // }
 
+/// One of the logbook's scans and how far through scanning it the player is, where 1.0 means
+/// it has been scanned
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct ScanTime
+{
+    pub scan: u32,
+    pub time: f32,
+}
+
 pub enum CPlayerState { }
 impl CPlayerState
 {
+    pub const MAX_SCAN_TIMES: usize = 846;
+
+    // x170_scanTimes in metaforce, a reserved_vector of every scan in the logbook
+    cpp_field!(scan_time_count: u32; ro_val @ 0x170);
+    cpp_field!(scan_times: [ScanTime; CPlayerState::MAX_SCAN_TIMES]; ptr @ 0x174);
+
     #[cpp_method(CPlayerState::GetItemCapacity(CPlayerState::EItemType) const)]
     pub unsafe fn get_item_capacity(this: *const CPlayerState, type_: i32) -> u32
     { }
 
-    #[cpp_method(CPlayerState::IncrPickup(CPlayerState::EItemType, u32))]
-    pub unsafe fn incr_pickup(this: *const CPlayerState, type_: i32, amount: u32)
+    #[cpp_method(CPlayerState::GetItemAmount(CPlayerState::EItemType) const)]
+    pub unsafe fn get_item_amount(this: *const CPlayerState, type_: i32) -> u32
+    { }
+
+    #[cpp_method(CPlayerState::IncrPickUp(CPlayerState::EItemType, int))]
+    pub unsafe fn incr_pickup(this: *mut CPlayerState, type_: i32, amount: i32)
     { }
 
-    #[cpp_method(CPlayerState::DecrPickup(CPlayerState::EItemType, u32) const)]
-    pub unsafe fn decr_pickup(this: *const CPlayerState, type_: i32, amount: u32)
+    #[cpp_method(CPlayerState::DecrPickUp(CPlayerState::EItemType, int))]
+    pub unsafe fn decr_pickup(this: *mut CPlayerState, type_: i32, amount: i32)
     { }
+
+    #[cpp_method(CPlayerState::InitializePowerUp(CPlayerState::EItemType, int))]
+    pub unsafe fn initialize_power_up(this: *mut CPlayerState, type_: i32, capacity: i32)
+    { }
+}
+
+pub enum CPauseScreenBase { }
+impl CPauseScreenBase
+{
+    // After the vtable and the CStateManager reference, as in metaforce's CPauseScreenBase
+    cpp_field!(frame: *const CGuiFrame; ro_val @ 0x8);
 }
 
 pub enum CWorldState { }
@@ -110,9 +141,61 @@ impl CGameState
 pub enum CStateManager { }
 impl CStateManager
 {
+    cpp_field!(player: *mut CPlayer; ro_val @ 0x84c);
     cpp_field!(player_state: *mut CPlayerState; ptr @ 0x8b8);
+    cpp_field!(next_area_id: TAreaId; ro_val @ 0x8cc);
+    // TODO This is actually a bit field
+    cpp_field!(flags_f94: u8; val @ 0xf94);
+
+    // Inlined in the game, so there's nothing to link against
+    pub unsafe fn set_should_quit_game(this: *mut CStateManager)
+    {
+        Self::set_flags_f94(this, Self::flags_f94(this) | 0x40);
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct CVector3f
+{
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
 }
 
+pub enum CActor { }
+impl CActor
+{
+    // The translation column of the actor's CTransform4f
+    cpp_field!(translation_x: f32; ro_val @ 0x40);
+    cpp_field!(translation_y: f32; ro_val @ 0x50);
+    cpp_field!(translation_z: f32; ro_val @ 0x60);
+
+    pub unsafe fn translation(this: *const CActor) -> CVector3f
+    {
+        CVector3f {
+            x: Self::translation_x(this),
+            y: Self::translation_y(this),
+            z: Self::translation_z(this),
+        }
+    }
+
+    #[cpp_method(CActor::SetTranslation(const CVector3f &))]
+    pub unsafe fn set_translation(this: *mut CActor, translation: *const CVector3f)
+    { }
+}
+
+pub enum CPhysicsActor { }
+impl CPhysicsActor
+{
+    #[cpp_method(CPhysicsActor::Stop(void))]
+    pub unsafe fn stop(this: *mut CPhysicsActor)
+    { }
+}
+
+// Derives from CPhysicsActor, which derives from CActor
+pub enum CPlayer { }
+
 #[repr(C)]
 pub struct CHudMemoParams
 {
@@ -0,0 +1,82 @@
+use linkme::distributed_slice;
+
+use primeapi::patch_fn;
+use primeapi::mp1::{
+    CGameState, CGuiFrame, CGuiTextSupport, CGuiTextPane, CPauseScreenBase, CPlayerState,
+};
+use primeapi::rstl::WString;
+
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::REL_CONFIG;
+
+// CPlayerState::EItemType values of the upgrades shown by the tracker, and how they're shown
+const TRACKED_UPGRADES: &[(i32, &str)] = &[
+    (10, "Ch"), (1, "Ic"), (2, "Wa"), (3, "Pl"),
+    (11, "Sm"), (28, "Wb"), (14, "Is"), (8, "Fl"),
+    (16, "Mb"), (6, "Bo"), (18, "Bb"), (19, "Sb"),
+    (15, "Sj"), (12, "Gr"), (9, "Th"), (13, "Xr"),
+    (22, "Va"), (21, "Gv"), (23, "Ph"),
+];
+
+fn item_tracker_text(player_state: *const CPlayerState) -> String
+{
+    const MISSILES: i32 = 4;
+    const POWER_BOMBS: i32 = 7;
+    const ENERGY_TANKS: i32 = 24;
+    const FIRST_ARTIFACT: i32 = 29;
+
+    let mut s = String::new();
+    unsafe {
+        let artifacts = (FIRST_ARTIFACT..FIRST_ARTIFACT + 12)
+            .filter(|item| CPlayerState::get_item_amount(player_state, *item) > 0)
+            .count();
+        let _ = write!(
+            s,
+            "M {} PB {} ET {} A {}/12",
+            CPlayerState::get_item_capacity(player_state, MISSILES),
+            CPlayerState::get_item_capacity(player_state, POWER_BOMBS),
+            CPlayerState::get_item_capacity(player_state, ENERGY_TANKS),
+            artifacts,
+        );
+        for (item, name) in TRACKED_UPGRADES {
+            if CPlayerState::get_item_capacity(player_state, *item) > 0 {
+                let _ = write!(s, " {}", name);
+            }
+        }
+    }
+    s
+}
+
+// Replaces the final `blr` of CPauseScreenBase's constructor, so `this` is still in r3 and the
+// original caller is returned to. The pause screen's title is replaced with a compact list of
+// the collected items. The constructor is 0x18c bytes long in both the 1.00 and 1.02 symbol
+// tables, so its last instruction is at +0x188.
+#[patch_fn(kind = return,
+           target = "__ct__16CPauseScreenBaseFRC13CStateManagerRC9CGuiFrameRC12CStringTable" + 0x188,
+           version = Ntsc0_00)]
+#[patch_fn(kind = return,
+           target = "__ct__16CPauseScreenBaseFRC13CStateManagerRC9CGuiFrameRC12CStringTable" + 0x188,
+           version = Ntsc0_02)]
+unsafe extern "C" fn update_pause_screen_item_tracker(screen: *mut CPauseScreenBase)
+    -> *mut CPauseScreenBase
+{
+    if !REL_CONFIG.item_tracker {
+        return screen;
+    }
+
+    let player_state = *CGameState::player_state(CGameState::global_instance());
+    let title = CGuiFrame::find_widget(
+        CPauseScreenBase::frame(screen),
+        b"textpane_title\0".as_ptr(),
+    );
+    if title.is_null() {
+        return screen;
+    }
+
+    let s = WString::from_ascii(item_tracker_text(player_state).as_bytes());
+    let text_support = CGuiTextPane::text_support_mut(title as *mut CGuiTextPane);
+    CGuiTextSupport::set_text(text_support, &s);
+    screen
+}
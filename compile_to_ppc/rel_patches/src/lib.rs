@@ -12,36 +12,66 @@ use primeapi::mp1::{
     CArchitectureQueue, CGameState, CGuiFrame, CGuiTextSupport, CGuiTextPane, CGuiWidget,
     CMainFlow, CStringTable, CWorldState,
 };
+#[cfg(feature = "ntsc_0_00")]
+use primeapi::mp1::CStateManager;
 use primeapi::rstl::WString;
 
+use alloc::vec::Vec;
 use core::mem::MaybeUninit;
 
+// Neither the pause screen's constructor nor the CPlayerState functions the item tracker and
+// multiworld need are in the PAL symbol table. The starting scans are applied from the
+// per-frame hook, which isn't either.
+#[cfg(not(feature = "pal"))]
+mod item_tracker;
+#[cfg(not(feature = "pal"))]
+mod multiworld;
+mod practice;
+#[cfg(not(feature = "pal"))]
+mod starting_scans;
+
 include!("../../patches_config.rs");
 static mut REL_CONFIG: RelConfig = RelConfig {
     quickplay_mlvl: 0xFFFFFFFF,
     quickplay_mrea: 0xFFFFFFFF,
+    item_tracker: false,
+    multiworld: false,
+    practice_mode: false,
 };
 
+unsafe fn read_file(name: &[u8]) -> Option<Vec<u8>>
+{
+    let mut fi = DVDFileInfo::new(name)?;
+    let size = fi.file_length() as usize;
+    let mut recv_buf = alloc::vec![MaybeUninit::<u8>::uninit(); size + 63];
+    let recv_buf = Aligned32::split_unaligned_prefix_mut(&mut recv_buf[..]).1;
+    let recv_buf = &mut recv_buf[..(size + 31) & !31];
+    {
+        let _ = fi.read_async(recv_buf, 0, 0);
+    }
+    Some(recv_buf[..size].assume_init().to_vec())
+}
+
 #[prolog_fn]
 unsafe extern "C" fn setup_global_state()
 {
-    {
-        let mut fi = if let Some(fi) = DVDFileInfo::new(b"rel_config.bin\0") {
-            fi
-        } else {
-            return;
-        };
-        let config_size = fi.file_length() as usize;
-        let mut recv_buf = alloc::vec![MaybeUninit::<u8>::uninit(); config_size + 63];
-        let recv_buf = Aligned32::split_unaligned_prefix_mut(&mut recv_buf[..]).1;
-        let recv_buf = &mut recv_buf[..(config_size + 31) & !31];
-        {
-            let _ = fi.read_async(recv_buf, 0, 0);
+    let config = if let Some(config) = read_file(b"rel_config.bin\0") {
+        config
+    } else {
+        return;
+    };
+    REL_CONFIG = ssmarshal::deserialize(&config).unwrap().0;
+
+    if REL_CONFIG.practice_mode {
+        if let Some(warps) = read_file(b"practice_warps.bin\0") {
+            practice::load_warps(&warps);
         }
-        REL_CONFIG = ssmarshal::deserialize(&recv_buf[..config_size].assume_init())
-            .unwrap().0;
     }
 
+    #[cfg(not(feature = "pal"))]
+    if let Some(scans) = read_file(b"starting_scans.bin\0") {
+        starting_scans::load_scans(&scans);
+    }
 }
 
 
@@ -87,6 +117,18 @@ unsafe extern "C" fn quickplay_hook_advance_game_state(
 )
 {
     static mut INIT: bool = false;
+    if CMainFlow::game_state(flow) == CMainFlow::CLIENT_FLOW_STATE_GAME {
+        // The practice warp menu quits the game; start it again in the chosen room rather
+        // than going back to the title screen
+        if let Some((mlvl, mrea)) = practice::take_pending_warp() {
+            let game_state = CGameState::global_instance();
+            CGameState::set_current_world_id(game_state, mlvl);
+            let world_state = CGameState::get_current_world_state(game_state);
+            CWorldState::set_desired_area_asset_id(world_state, mrea);
+            CMainFlow::set_game_state(flow, CMainFlow::CLIENT_FLOW_STATE_GAME, q);
+            return;
+        }
+    }
     if CMainFlow::game_state(flow) == CMainFlow::CLIENT_FLOW_STATE_PRE_FRONT_END  && !INIT {
         INIT = true;
         if REL_CONFIG.quickplay_mlvl != 0xFFFFFFFF {
@@ -100,3 +142,40 @@ unsafe extern "C" fn quickplay_hook_advance_game_state(
     }
     CMainFlow::advance_game_state(flow, q)
 }
+
+// Replaces the final `blr` of CStateManager's constructor. Nothing else gives the REL a way to
+// find the CStateManager, which the practice mode needs for the player. The constructor is
+// only in the 1.00 symbol table, so the practice mode is limited to that version.
+#[cfg(feature = "ntsc_0_00")]
+#[patch_fn(kind = return,
+           target = "__ct__13CStateManagerFRCQ24rstl26ncrc_ptr<14CScriptMailbox>RCQ24rstl25ncrc_ptr<13CMapWorldInfo>RCQ24rstl24ncrc_ptr<12CPlayerState>RCQ24rstl30ncrc_ptr<18CWorldTransManager>" + 0x12c8,
+           version = Ntsc0_00)]
+unsafe extern "C" fn capture_state_manager(state_manager: *mut CStateManager)
+    -> *mut CStateManager
+{
+    if REL_CONFIG.practice_mode {
+        practice::set_state_manager(state_manager);
+    }
+    state_manager
+}
+
+// Replaces the final `blr` of CSamusHud::Update, which runs once a frame while the player is in
+// game and it isn't paused. It isn't in the PAL symbol table.
+#[cfg(not(feature = "pal"))]
+#[patch_fn(kind = return,
+           target = "Update__9CSamusHudFfRC13CStateManagerUibb" + 0xd40,
+           version = Ntsc0_00)]
+#[patch_fn(kind = return,
+           target = "Update__9CSamusHudFfRC13CStateManagerUibb" + 0xd40,
+           version = Ntsc0_02)]
+unsafe extern "C" fn update_every_frame()
+{
+    let player_state = *CGameState::player_state(CGameState::global_instance());
+    starting_scans::update(player_state);
+    if REL_CONFIG.multiworld {
+        multiworld::update(player_state);
+    }
+    if REL_CONFIG.practice_mode {
+        practice::update(player_state);
+    }
+}
@@ -0,0 +1,68 @@
+use primeapi::mp1::CPlayerState;
+
+use core::ptr;
+
+include!("../../multiworld_mailbox.rs");
+
+// The host side of the multiworld bridge finds this by its magic and reads and writes it
+// while the game is running, so every access after the magic goes through a volatile pointer.
+#[no_mangle]
+static mut MULTIWORLD_MAILBOX: MultiworldMailbox = MultiworldMailbox::new();
+static mut SEND_QUEUE: MultiworldSendQueue = MultiworldSendQueue::new();
+
+unsafe fn mailbox() -> *mut MultiworldMailbox
+{
+    ptr::addr_of_mut!(MULTIWORLD_MAILBOX)
+}
+
+/// Passes collected pickups meant for other players to the host and gives the player the items
+/// the host has received for them
+pub(crate) unsafe fn update(player_state: *mut CPlayerState)
+{
+    let mailbox = mailbox();
+    ptr::write_volatile(ptr::addr_of_mut!((*mailbox).active), 1);
+
+    send_collected_pickup(mailbox, player_state);
+    give_received_items(mailbox, player_state);
+}
+
+unsafe fn send_collected_pickup(mailbox: *mut MultiworldMailbox, player_state: *mut CPlayerState)
+{
+    let queue = &mut *ptr::addr_of_mut!(SEND_QUEUE);
+
+    // Taken away straight away, so that the next sent pickup starts from nothing again
+    let amount = CPlayerState::get_item_amount(player_state, MULTIWORLD_SEND_ITEM);
+    if amount != 0 {
+        queue.push(amount - 1);
+        CPlayerState::decr_pickup(player_state, MULTIWORLD_SEND_ITEM, amount as i32);
+    }
+    if queue.is_empty() {
+        return;
+    }
+
+    let sent_count = ptr::read_volatile(ptr::addr_of!((*mailbox).sent_count));
+    let sent_acked = ptr::read_volatile(ptr::addr_of!((*mailbox).sent_acked));
+    let sent_count = queue.flush(sent_count, sent_acked, |slot, location_idx| {
+        ptr::write_volatile(ptr::addr_of_mut!((*mailbox).sent[slot]), location_idx);
+    });
+    ptr::write_volatile(ptr::addr_of_mut!((*mailbox).sent_count), sent_count);
+}
+
+unsafe fn give_received_items(mailbox: *mut MultiworldMailbox, player_state: *mut CPlayerState)
+{
+    let mut given = CPlayerState::get_item_amount(player_state, MULTIWORLD_RECEIVED_COUNTER_ITEM);
+    let received_count = ptr::read_volatile(ptr::addr_of!((*mailbox).received_count));
+    while given < received_count {
+        let slot = given as usize % MULTIWORLD_MAILBOX_RECEIVED_SLOTS;
+        let item = ptr::read_volatile(ptr::addr_of!((*mailbox).received[slot]));
+
+        // The same as what CScriptPickup does when it's touched
+        CPlayerState::initialize_power_up(player_state, item.item_type as i32, item.capacity as i32);
+        CPlayerState::incr_pickup(player_state, item.item_type as i32, item.amount as i32);
+
+        CPlayerState::initialize_power_up(player_state, MULTIWORLD_RECEIVED_COUNTER_ITEM, 1);
+        CPlayerState::incr_pickup(player_state, MULTIWORLD_RECEIVED_COUNTER_ITEM, 1);
+        given += 1;
+    }
+    ptr::write_volatile(ptr::addr_of_mut!((*mailbox).received_given), given);
+}
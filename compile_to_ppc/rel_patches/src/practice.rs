@@ -0,0 +1,250 @@
+use primeapi::mp1::{
+    CActor, CGameState, CHudMemoParams, CPhysicsActor, CPlayerState, CSamusHud, CStateManager,
+    CVector3f, TAreaId,
+};
+use primeapi::rstl::WString;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ptr;
+
+// The buttons of the first controller are the top half of its serial interface input buffer,
+// which is refreshed every time the game polls the controllers
+const SI_CHANNEL_0_INPUT: *const u32 = 0xCC006404 as *const u32;
+
+const PAD_LEFT: u16 = 0x0001;
+const PAD_RIGHT: u16 = 0x0002;
+const PAD_DOWN: u16 = 0x0004;
+const PAD_UP: u16 = 0x0008;
+const PAD_R: u16 = 0x0020;
+const PAD_L: u16 = 0x0040;
+const PAD_Y: u16 = 0x0800;
+
+// Held down (fully pressed) along with a direction on the D-Pad to use the practice features
+const PAD_COMBO: u16 = PAD_L | PAD_R;
+
+const MISSILES: i32 = 4;
+const POWER_BOMBS: i32 = 7;
+const HEALTH_REFILL: i32 = 26;
+
+struct Warp
+{
+    mlvl: u32,
+    mrea: u32,
+    name: String,
+}
+
+static mut STATE_MANAGER: *mut CStateManager = ptr::null_mut();
+static mut PREVIOUS_BUTTONS: u16 = 0;
+static mut SAVED_POSITION: Option<(TAreaId, CVector3f)> = None;
+// The area the player is in and the play time when they entered it
+static mut CURRENT_ROOM: Option<(TAreaId, f64)> = None;
+
+static mut WARPS: Vec<Warp> = Vec::new();
+static mut WARP_MENU_OPEN: bool = false;
+static mut SELECTED_WARP: usize = 0;
+static mut PENDING_WARP: Option<(u32, u32)> = None;
+
+unsafe fn warps() -> &'static Vec<Warp>
+{
+    &*ptr::addr_of!(WARPS)
+}
+
+/// Parses the list of rooms in the warp menu. Every entry is the room's MLVL and MREA as
+/// big-endian u32s, followed by the length of its name as a u8 and then the name in ASCII.
+pub(crate) unsafe fn load_warps(mut data: &[u8])
+{
+    let warps = &mut *ptr::addr_of_mut!(WARPS);
+    while data.len() >= 9 {
+        let mlvl = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let mrea = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        let name_len = data[8] as usize;
+        let name = if let Some(name) = data.get(9..9 + name_len) {
+            name
+        } else {
+            break;
+        };
+        warps.push(Warp {
+            mlvl,
+            mrea,
+            name: name.iter().map(|b| *b as char).collect(),
+        });
+        data = &data[9 + name_len..];
+    }
+}
+
+#[cfg_attr(not(feature = "ntsc_0_00"), allow(dead_code))]
+pub(crate) unsafe fn set_state_manager(state_manager: *mut CStateManager)
+{
+    STATE_MANAGER = state_manager;
+    CURRENT_ROOM = None;
+    WARP_MENU_OPEN = false;
+}
+
+/// The world and room the player asked to warp to, once their game has been quit
+pub(crate) unsafe fn take_pending_warp() -> Option<(u32, u32)>
+{
+    ptr::replace(ptr::addr_of_mut!(PENDING_WARP), None)
+}
+
+fn show_message(s: &str)
+{
+    let s = WString::from_ascii(s.as_bytes());
+    let params = CHudMemoParams {
+        display_time: 3.0,
+        clear_memo_window: 1,
+        fadeout_only: 0,
+        hint_memo: 0,
+    };
+    unsafe {
+        CSamusHud::display_hud_memo(&s, &params);
+    }
+}
+
+fn format_time(seconds: f64) -> String
+{
+    let centiseconds = (seconds * 100.0) as u32;
+    let mut s = String::new();
+    let _ = write!(
+        s,
+        "{}:{:02}.{:02}",
+        centiseconds / 6000,
+        centiseconds / 100 % 60,
+        centiseconds % 100,
+    );
+    s
+}
+
+/// Handles the practice button combos and times the rooms the player goes through. Called once
+/// a frame while the player is in game.
+#[cfg_attr(feature = "pal", allow(dead_code))]
+pub(crate) unsafe fn update(player_state: *mut CPlayerState)
+{
+    if STATE_MANAGER.is_null() {
+        return;
+    }
+
+    update_room_timer();
+
+    let buttons = (ptr::read_volatile(SI_CHANNEL_0_INPUT) >> 16) as u16;
+    let pressed = buttons & !PREVIOUS_BUTTONS;
+    PREVIOUS_BUTTONS = buttons;
+    if buttons & PAD_COMBO != PAD_COMBO {
+        return;
+    }
+
+    if WARP_MENU_OPEN {
+        update_warp_menu(buttons, pressed);
+    } else if pressed & PAD_UP != 0 {
+        refill(player_state);
+    } else if pressed & PAD_LEFT != 0 {
+        save_position();
+    } else if pressed & PAD_RIGHT != 0 {
+        load_position();
+    } else if pressed & PAD_DOWN != 0 {
+        if warps().is_empty() {
+            show_message("There are no rooms to warp to");
+        } else {
+            WARP_MENU_OPEN = true;
+            show_selected_warp();
+        }
+    }
+}
+
+unsafe fn update_room_timer()
+{
+    let area = CStateManager::next_area_id(STATE_MANAGER);
+    let play_time = CGameState::play_time(CGameState::global_instance());
+    match CURRENT_ROOM {
+        Some((current_area, _)) if current_area == area => (),
+        Some((_, entered_at)) => {
+            let mut s = String::from("Room time ");
+            s.push_str(&format_time(play_time - entered_at));
+            show_message(&s);
+            CURRENT_ROOM = Some((area, play_time));
+        },
+        None => CURRENT_ROOM = Some((area, play_time)),
+    }
+}
+
+unsafe fn refill(player_state: *mut CPlayerState)
+{
+    // The amounts are capped at what the player can carry
+    CPlayerState::incr_pickup(player_state, HEALTH_REFILL, 9999);
+    CPlayerState::incr_pickup(player_state, MISSILES, 999);
+    CPlayerState::incr_pickup(player_state, POWER_BOMBS, 99);
+    show_message("Refilled");
+}
+
+unsafe fn save_position()
+{
+    let player = CStateManager::player(STATE_MANAGER) as *mut CActor;
+    SAVED_POSITION = Some((CStateManager::next_area_id(STATE_MANAGER), CActor::translation(player)));
+    show_message("Position saved");
+}
+
+unsafe fn load_position()
+{
+    let player = CStateManager::player(STATE_MANAGER);
+    match SAVED_POSITION {
+        Some((area, translation)) if area == CStateManager::next_area_id(STATE_MANAGER) => {
+            CActor::set_translation(player as *mut CActor, &translation);
+            CPhysicsActor::stop(player as *mut CPhysicsActor);
+            show_message("Position loaded");
+        },
+        Some(_) => show_message("The saved position is in another room"),
+        None => show_message("No position has been saved"),
+    }
+}
+
+unsafe fn update_warp_menu(buttons: u16, pressed: u16)
+{
+    let warps = warps();
+    // Holding Y moves between worlds rather than rooms
+    let whole_world = buttons & PAD_Y != 0;
+    if pressed & PAD_LEFT != 0 {
+        let previous = if whole_world {
+            first_warp_of_world(SELECTED_WARP)
+        } else {
+            SELECTED_WARP
+        };
+        SELECTED_WARP = (previous + warps.len() - 1) % warps.len();
+        if whole_world {
+            SELECTED_WARP = first_warp_of_world(SELECTED_WARP);
+        }
+        show_selected_warp();
+    } else if pressed & PAD_RIGHT != 0 {
+        let mlvl = warps[SELECTED_WARP].mlvl;
+        SELECTED_WARP = (SELECTED_WARP + 1) % warps.len();
+        while whole_world && SELECTED_WARP != 0 && warps[SELECTED_WARP].mlvl == mlvl {
+            SELECTED_WARP = (SELECTED_WARP + 1) % warps.len();
+        }
+        show_selected_warp();
+    } else if pressed & PAD_UP != 0 {
+        let warp = &warps[SELECTED_WARP];
+        PENDING_WARP = Some((warp.mlvl, warp.mrea));
+        WARP_MENU_OPEN = false;
+        // The game is restarted in the chosen room once CMainFlow sees it has been quit
+        CStateManager::set_should_quit_game(STATE_MANAGER);
+    } else if pressed & PAD_DOWN != 0 {
+        WARP_MENU_OPEN = false;
+        show_message("Warp cancelled");
+    }
+}
+
+unsafe fn first_warp_of_world(mut i: usize) -> usize
+{
+    let warps = warps();
+    while i > 0 && warps[i - 1].mlvl == warps[i].mlvl {
+        i -= 1;
+    }
+    i
+}
+
+unsafe fn show_selected_warp()
+{
+    let mut s = String::from("Warp to ");
+    s.push_str(&warps()[SELECTED_WARP].name);
+    show_message(&s);
+}
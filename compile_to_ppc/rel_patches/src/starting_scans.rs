@@ -0,0 +1,33 @@
+use primeapi::mp1::CPlayerState;
+
+use alloc::vec::Vec;
+use core::ptr;
+
+// The SCAN asset ids of the scans the player starts with, sorted
+static mut STARTING_SCANS: Vec<u32> = Vec::new();
+
+/// Parses the list of scans the player starts with, which are big-endian SCAN asset ids
+pub(crate) unsafe fn load_scans(data: &[u8])
+{
+    let scans = &mut *ptr::addr_of_mut!(STARTING_SCANS);
+    scans.extend(data.chunks_exact(4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]])));
+    scans.sort_unstable();
+}
+
+/// Marks the starting scans as scanned. This is cheap enough to do every frame, which covers
+/// new games and loaded saves alike without having to know when the player state is replaced.
+pub(crate) unsafe fn update(player_state: *mut CPlayerState)
+{
+    let scans = &*ptr::addr_of!(STARTING_SCANS);
+    if scans.is_empty() {
+        return;
+    }
+    let count = (CPlayerState::scan_time_count(player_state) as usize)
+        .min(CPlayerState::MAX_SCAN_TIMES);
+    let scan_times = &mut (&mut *CPlayerState::scan_times_mut(player_state))[..count];
+    for scan_time in scan_times {
+        if scan_time.time < 1.0 && scans.binary_search(&scan_time.scan).is_ok() {
+            scan_time.time = 1.0;
+        }
+    }
+}
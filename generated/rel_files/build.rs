@@ -9,7 +9,7 @@ use dol_linker::{read_symbol_table, link_obj_files_to_bin, link_obj_files_to_rel
 use walkdir::WalkDir;
 
 
-fn invoke_cargo(ppc_manifest: &Path, package: &str)
+fn invoke_cargo(ppc_manifest: &Path, package: &str, features: &str)
 {
     let output = Command::new("rustup")
         .arg("run")
@@ -20,6 +20,8 @@ fn invoke_cargo(ppc_manifest: &Path, package: &str)
         .arg(ppc_manifest)
         .arg("-p")
         .arg(package)
+        .arg("--features")
+        .arg(features)
         .arg("--target")
         .arg("powerpc-unknown-linux-gnu")
         .arg("--release")
@@ -58,10 +60,13 @@ fn main()
         .join("..")
         .join("dol_symbol_table");
 
-    invoke_cargo(&ppc_manifest, "rel_loader");
-    invoke_cargo(&ppc_manifest, "rel_patches");
+    invoke_cargo(&ppc_manifest, "rel_loader", "");
+
+    // The patches are built once per version, so that hooks can be left out of the versions
+    // whose symbol tables don't have their targets
+    for (version, feature) in &[("1.00", "ntsc_0_00"), ("1.02", "ntsc_0_02"), ("pal", "pal")] {
+        invoke_cargo(&ppc_manifest, "rel_patches", feature);
 
-    for version in &["1.00", "1.02", "pal"] {
         let sym_table_path = symbol_table_dir.join(format!("{}.txt", version));
         eprintln!("{:?}", root_dir.join("..").join(&sym_table_path));
         let mut symbol_table = read_symbol_table(root_dir.join(sym_table_path)).unwrap();
@@ -0,0 +1,44 @@
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+};
+
+use crate::primitive_types::CStr;
+
+/// Data that can be detached from the buffer it was read from.
+///
+/// Used to keep a typed copy of data that was read from a temporary buffer. Can be derived
+/// by `auto_struct` with the `IntoStatic` option.
+pub trait IntoStatic
+{
+    type Static: 'static;
+    fn into_static(self) -> Self::Static;
+}
+
+impl<'r> IntoStatic for CStr<'r>
+{
+    type Static = CStr<'static>;
+    fn into_static(self) -> CStr<'static>
+    {
+        Cow::Owned(self.into_owned())
+    }
+}
+
+impl<T> IntoStatic for Option<T>
+    where T: IntoStatic
+{
+    type Static = Option<T::Static>;
+    fn into_static(self) -> Self::Static
+    {
+        self.map(T::into_static)
+    }
+}
+
+impl IntoStatic for PhantomData<&()>
+{
+    type Static = PhantomData<&'static ()>;
+    fn into_static(self) -> Self::Static
+    {
+        PhantomData
+    }
+}
@@ -12,6 +12,7 @@ pub mod read_only_array;
 pub mod iterator_array;
 
 pub mod lcow;
+pub mod into_static;
 pub mod derivable_array_proxy;
 pub mod uncached;
 pub mod with_read;
@@ -37,6 +38,7 @@ pub use crate::{
     with_read::WithRead,
 
     lcow::LCow,
+    into_static::IntoStatic,
 
     // XXX There are > 5 items in these modules. Do I want to use * imports everywhere for
     //     consistency?
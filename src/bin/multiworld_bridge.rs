@@ -0,0 +1,97 @@
+use randomprime::multiworld::{self, MultiworldEvent};
+
+use clap::{
+    Arg,
+    App,
+    crate_version,
+    Format,
+};
+
+use std::fs;
+
+fn print_event(event: MultiworldEvent)
+{
+    match event {
+        MultiworldEvent::PlayerConnected(player) => println!("Player {} connected", player),
+        MultiworldEvent::PlayerDisconnected(player) => println!("Player {} disconnected", player),
+        MultiworldEvent::PickupSent { from_player, location, to_player, pickup } =>
+            println!("Player {} sent {} to player {} (location {})",
+                     from_player, pickup.name(), to_player, location),
+        MultiworldEvent::PickupReceived { from_player, pickup } =>
+            println!("Received {} from player {}", pickup.name(), from_player),
+        MultiworldEvent::MailboxFound(addr) =>
+            println!("Found the game's mailbox at 0x{:08x}", addr),
+        MultiworldEvent::Error(e) => eprintln!("{}", e),
+    }
+}
+
+fn main_inner() -> Result<(), String>
+{
+    let matches = App::new("randomprime multiworld bridge")
+        .version(crate_version!())
+        .arg(Arg::with_name("serve")
+            .long("serve")
+            .takes_value(true)
+            .help("Run the relay the players' bridges connect to, listening on this address"))
+        .arg(Arg::with_name("relay")
+            .long("relay")
+            .takes_value(true)
+            .help("The address of the relay to connect to"))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .takes_value(true)
+            .help("The config the player's ISO was patched with"))
+        .arg(Arg::with_name("memory file")
+            .long("memory-file")
+            .takes_value(true)
+            .help("Use a file standing in for the game's memory instead of Dolphin's"))
+        .get_matches();
+
+    if let Some(addr) = matches.value_of("serve") {
+        println!("Relaying pickups on {}", addr);
+        return multiworld::run_relay(addr, print_event);
+    }
+
+    let relay_addr = matches.value_of("relay")
+        .ok_or("Either --serve or --relay is needed")?;
+    let config_path = matches.value_of("config").ok_or("--config is needed")?;
+    let config_json = fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read {}: {}", config_path, e))?;
+    let config = multiworld::multiworld_config_from_json(&config_json)?;
+
+    if let Some(path) = matches.value_of("memory file") {
+        multiworld::run_bridge(
+            &config,
+            relay_addr,
+            || multiworld::FileMemory::open(path),
+            print_event,
+        )
+    } else {
+        run_dolphin_bridge(&config, relay_addr)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn run_dolphin_bridge(
+    config: &randomprime::patch_config::MultiworldConfig,
+    relay_addr: &str,
+) -> Result<(), String>
+{
+    multiworld::run_bridge(config, relay_addr, multiworld::DolphinMemory::attach, print_event)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run_dolphin_bridge(
+    _config: &randomprime::patch_config::MultiworldConfig,
+    _relay_addr: &str,
+) -> Result<(), String>
+{
+    Err("Reading Dolphin's memory is only supported on Linux; use --memory-file".to_string())
+}
+
+fn main()
+{
+    if let Err(s) = main_inner() {
+        eprintln!("{} {}", Format::Error("error:"), s);
+    }
+}
@@ -103,9 +103,16 @@ pub mod custom_asset_ids {
         // has to be at the end //
         SKIP_HUDMEMO_STRG_START: STRG,
         SKIP_HUDMEMO_STRG_END: STRG = SKIP_HUDMEMO_STRG_START.to_u32() + 38,
+        // followed by one per hudmemo generated from the config //
+        GENERATED_HUDMEMO_STRG_START: STRG = SKIP_HUDMEMO_STRG_END.to_u32(),
     }
 }
 
+pub fn generated_hudmemo_strg(idx: usize) -> ResId<res_id::STRG>
+{
+    ResId::new(custom_asset_ids::GENERATED_HUDMEMO_STRG_START.to_u32() + idx as u32)
+}
+
 pub fn build_resource<'r, K>(file_id: ResId<K>, kind: ResourceKind<'r>) -> Resource<'r>
     where K: res_id::ResIdKind,
 {
@@ -163,6 +170,7 @@ pub fn custom_assets<'r>(
     resources: &HashMap<(u32, FourCC),
     structs::Resource<'r>>,
     starting_memo: Option<&str>,
+    generated_hudmemos: &[String],
 ) -> Vec<Resource<'r>>
 {
     // External assets
@@ -253,6 +261,15 @@ pub fn custom_assets<'r>(
         ));
     }
 
+    for (i, text) in generated_hudmemos.iter().enumerate() {
+        assets.push(build_resource(
+            generated_hudmemo_strg(i),
+            structs::ResourceKind::Strg(structs::Strg::from_strings(vec![
+                format!("&just=center;{}\0", text),
+            ])),
+        ));
+    }
+
     // Custom door assets
     for door_type in DoorType::iter() {
         if door_type.shield_cmdl().to_u32() >= 0xDEAF0000 { // only if it doesn't exist in-game already
@@ -268,6 +285,7 @@ pub fn custom_assets<'r>(
 pub fn collect_game_resources<'r>(
     gc_disc: &structs::GcDisc<'r>,
     starting_memo: Option<&str>,
+    generated_hudmemos: &[String],
 )
     -> HashMap<(u32, FourCC), structs::Resource<'r>>
 {
@@ -303,7 +321,7 @@ pub fn collect_game_resources<'r>(
     // Remove extra assets from dependency search since they won't appear     //
     // in any pak. Instead add them to the output resource pool. These assets //
     // are provided as external files checked into the repository.            //
-    for res in custom_assets(&found, starting_memo) {
+    for res in custom_assets(&found, starting_memo, generated_hudmemos) {
         let key = (res.file_id, res.fourcc());
         looking_for.remove(&key);
         assert!(found.insert(key, res).is_none());
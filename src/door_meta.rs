@@ -7,7 +7,8 @@ use structs::{res_id, ResId};
 use reader_writer::{FourCC};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-enum TypeVulnerability {
+pub enum TypeVulnerability {
+    Weak = 0x0,
     Normal = 0x1,
     Reflect = 0x2,
     Immune = 0x3,
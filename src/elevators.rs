@@ -1,17 +1,24 @@
 #![allow(unused)]
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use enum_map::{Enum, EnumMap};
 use crate::{pickup_meta::{self, PickupType}};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum World {
+    #[serde(rename = "frigate")]
     FrigateOrpheon,
+    #[serde(rename = "tallon")]
     TallonOverworld,
+    #[serde(rename = "chozo")]
     ChozoRuins,
+    #[serde(rename = "magmoor")]
     MagmoorCaverns,
+    #[serde(rename = "phendrana")]
     PhendranaDrifts,
+    #[serde(rename = "mines")]
     PhazonMines,
+    #[serde(rename = "impact")]
     ImpactCrater,
 }
 
@@ -54,6 +61,18 @@ impl World {
         }
     }
 
+    pub fn savw(&self) -> u32 {
+        match self {
+            World::FrigateOrpheon  => 0x8970ba7c,
+            World::ChozoRuins      => 0x1f08bb04,
+            World::PhendranaDrifts => 0x344026fa,
+            World::TallonOverworld => 0xa50c9a43,
+            World::PhazonMines     => 0x2d52090e,
+            World::MagmoorCaverns  => 0xa2066717,
+            World::ImpactCrater    => 0x5dc54dba,
+        }
+    }
+
     pub fn to_str(&self) -> &'static str {
         match self {
             World::FrigateOrpheon  => "Frigate Orpheon",
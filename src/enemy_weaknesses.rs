@@ -0,0 +1,292 @@
+use rand::{
+    rngs::StdRng,
+    seq::SliceRandom,
+    SeedableRng,
+};
+
+use crate::{
+    door_meta::TypeVulnerability,
+    patch_config::EnemyVulnerabilityConfig,
+    pickup_meta::PickupType,
+    starting_items::StartingItems,
+    structs::{
+        scly_props::structs::DamageVulnerability,
+        scly_schema,
+        SclyProperty,
+    },
+};
+
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Weapon
+{
+    PowerBeam,
+    IceBeam,
+    WaveBeam,
+    PlasmaBeam,
+    Bomb,
+    PowerBomb,
+    Missile,
+    BoostBall,
+    ChargedPowerBeam,
+    ChargedIceBeam,
+    ChargedWaveBeam,
+    ChargedPlasmaBeam,
+    SuperMissile,
+    IceSpreader,
+    Wavebuster,
+    Flamethrower,
+}
+
+impl Weapon
+{
+    pub const ALL: [Weapon; 16] = [
+        Weapon::PowerBeam,
+        Weapon::IceBeam,
+        Weapon::WaveBeam,
+        Weapon::PlasmaBeam,
+        Weapon::Bomb,
+        Weapon::PowerBomb,
+        Weapon::Missile,
+        Weapon::BoostBall,
+        Weapon::ChargedPowerBeam,
+        Weapon::ChargedIceBeam,
+        Weapon::ChargedWaveBeam,
+        Weapon::ChargedPlasmaBeam,
+        Weapon::SuperMissile,
+        Weapon::IceSpreader,
+        Weapon::Wavebuster,
+        Weapon::Flamethrower,
+    ];
+
+    pub fn name(&self) -> &'static str
+    {
+        match self {
+            Weapon::PowerBeam => "Power Beam",
+            Weapon::IceBeam => "Ice Beam",
+            Weapon::WaveBeam => "Wave Beam",
+            Weapon::PlasmaBeam => "Plasma Beam",
+            Weapon::Bomb => "Morph Ball Bomb",
+            Weapon::PowerBomb => "Power Bomb",
+            Weapon::Missile => "Missile",
+            Weapon::BoostBall => "Boost Ball",
+            Weapon::ChargedPowerBeam => "Charged Power Beam",
+            Weapon::ChargedIceBeam => "Charged Ice Beam",
+            Weapon::ChargedWaveBeam => "Charged Wave Beam",
+            Weapon::ChargedPlasmaBeam => "Charged Plasma Beam",
+            Weapon::SuperMissile => "Super Missile",
+            Weapon::IceSpreader => "Ice Spreader",
+            Weapon::Wavebuster => "Wavebuster",
+            Weapon::Flamethrower => "Flamethrower",
+        }
+    }
+
+    /// The items needed to fire this weapon. The Power Beam is always available.
+    pub fn required_items(&self) -> &'static [PickupType]
+    {
+        match self {
+            Weapon::PowerBeam => &[],
+            Weapon::IceBeam => &[PickupType::IceBeam],
+            Weapon::WaveBeam => &[PickupType::WaveBeam],
+            Weapon::PlasmaBeam => &[PickupType::PlasmaBeam],
+            Weapon::Bomb => &[PickupType::MorphBall, PickupType::MorphBallBomb],
+            Weapon::PowerBomb => &[PickupType::MorphBall, PickupType::PowerBomb],
+            Weapon::Missile => &[PickupType::Missile],
+            Weapon::BoostBall => &[PickupType::MorphBall, PickupType::BoostBall],
+            Weapon::ChargedPowerBeam => &[PickupType::ChargeBeam],
+            Weapon::ChargedIceBeam => &[PickupType::ChargeBeam, PickupType::IceBeam],
+            Weapon::ChargedWaveBeam => &[PickupType::ChargeBeam, PickupType::WaveBeam],
+            Weapon::ChargedPlasmaBeam => &[PickupType::ChargeBeam, PickupType::PlasmaBeam],
+            Weapon::SuperMissile =>
+                &[PickupType::ChargeBeam, PickupType::Missile, PickupType::SuperMissile],
+            Weapon::IceSpreader => &[
+                PickupType::ChargeBeam, PickupType::Missile, PickupType::IceBeam,
+                PickupType::IceSpreader,
+            ],
+            Weapon::Wavebuster => &[
+                PickupType::ChargeBeam, PickupType::Missile, PickupType::WaveBeam,
+                PickupType::Wavebuster,
+            ],
+            Weapon::Flamethrower => &[
+                PickupType::ChargeBeam, PickupType::Missile, PickupType::PlasmaBeam,
+                PickupType::Flamethrower,
+            ],
+        }
+    }
+
+    /// Whether a boss can realistically be fought with this weapon, as opposed to weapons
+    /// that need the player to be in morph ball next to it.
+    fn is_ranged(&self) -> bool
+    {
+        !matches!(self, Weapon::Bomb | Weapon::PowerBomb | Weapon::BoostBall)
+    }
+
+    fn vulnerability_mut<'a>(&self, dv: &'a mut DamageVulnerability) -> &'a mut u32
+    {
+        match self {
+            Weapon::PowerBeam => &mut dv.power,
+            Weapon::IceBeam => &mut dv.ice,
+            Weapon::WaveBeam => &mut dv.wave,
+            Weapon::PlasmaBeam => &mut dv.plasma,
+            Weapon::Bomb => &mut dv.bomb,
+            Weapon::PowerBomb => &mut dv.power_bomb,
+            Weapon::Missile => &mut dv.missile,
+            Weapon::BoostBall => &mut dv.boost_ball,
+            Weapon::ChargedPowerBeam => &mut dv.charged_beams.power,
+            Weapon::ChargedIceBeam => &mut dv.charged_beams.ice,
+            Weapon::ChargedWaveBeam => &mut dv.charged_beams.wave,
+            Weapon::ChargedPlasmaBeam => &mut dv.charged_beams.plasma,
+            Weapon::SuperMissile => &mut dv.beam_combos.power,
+            Weapon::IceSpreader => &mut dv.beam_combos.ice,
+            Weapon::Wavebuster => &mut dv.beam_combos.wave,
+            Weapon::Flamethrower => &mut dv.beam_combos.plasma,
+        }
+    }
+}
+
+// The part of the boss fight that is scripted around a specific weapon, which must never
+// be randomized away.
+const ESSENTIAL_WEAPONS: &[(&str, Weapon)] = &[
+    ("Flaahgra", Weapon::Bomb),
+];
+
+const VULNERABILITY_WEIGHTS: &[(TypeVulnerability, u32)] = &[
+    (TypeVulnerability::Weak, 15),
+    (TypeVulnerability::Normal, 45),
+    (TypeVulnerability::Reflect, 15),
+    (TypeVulnerability::Immune, 25),
+];
+
+#[derive(Clone, Debug)]
+pub struct EnemyWeaknesses
+{
+    pub object_type: u8,
+    pub vulnerabilities: [TypeVulnerability; 16],
+}
+
+impl EnemyWeaknesses
+{
+    pub fn name(&self) -> &'static str
+    {
+        scly_schema().object_name(self.object_type).unwrap_or("Unknown")
+    }
+
+    pub fn apply(&self, dv: &mut DamageVulnerability)
+    {
+        for (weapon, vulnerability) in Weapon::ALL.iter().zip(self.vulnerabilities.iter()) {
+            *weapon.vulnerability_mut(dv) = *vulnerability as u32;
+        }
+    }
+
+    fn can_be_damaged_by(&self, weapon: Weapon) -> bool
+    {
+        matches!(
+            self.vulnerabilities[weapon as usize],
+            TypeVulnerability::Weak | TypeVulnerability::Normal
+        )
+    }
+}
+
+impl fmt::Display for EnemyWeaknesses
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{}:", self.name())?;
+        let categories = [
+            ("weak to", TypeVulnerability::Weak),
+            ("reflects", TypeVulnerability::Reflect),
+            ("immune to", TypeVulnerability::Immune),
+        ];
+        for (label, kind) in categories.iter() {
+            let weapons: Vec<_> = Weapon::ALL.iter()
+                .zip(self.vulnerabilities.iter())
+                .filter(|(_, v)| *v == kind)
+                .map(|(w, _)| w.name())
+                .collect();
+            if !weapons.is_empty() {
+                write!(f, " {} {};", label, weapons.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Assigns new damage vulnerabilities to every kind of enemy. Ordinary enemies can be anywhere,
+/// including in front of the items the player needs, so they always stay damageable by the
+/// Power Beam or, if the player starts with them, Missiles. The required bosses stay damageable
+/// by at least one weapon that can be used from range and obtained in this seed (from the
+/// starting items or the layout).
+pub fn randomize_enemy_weaknesses(
+    seed: u64,
+    config: &EnemyVulnerabilityConfig,
+    starting_items: &StartingItems,
+    pickups: &[PickupType],
+) -> Vec<EnemyWeaknesses>
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let obtainable: Vec<Weapon> = Weapon::ALL.iter()
+        .filter(|weapon| {
+            weapon.required_items().iter()
+                .all(|item| starting_items.contains(*item) || pickups.contains(item))
+        })
+        .cloned()
+        .collect();
+    let ranged: Vec<Weapon> = obtainable.iter().filter(|w| w.is_ranged()).cloned().collect();
+    let mut always_available = vec![Weapon::PowerBeam];
+    if starting_items.contains(PickupType::Missile) {
+        always_available.push(Weapon::Missile);
+    }
+
+    (0..=255u8)
+        .filter(|object_type| SclyProperty::is_patterned_ai_type(*object_type))
+        .map(|object_type| {
+            let mut weaknesses = EnemyWeaknesses {
+                object_type,
+                vulnerabilities: [TypeVulnerability::Normal; 16],
+            };
+            for vulnerability in weaknesses.vulnerabilities.iter_mut() {
+                *vulnerability = VULNERABILITY_WEIGHTS.choose_weighted(&mut rng, |(_, w)| *w)
+                    .unwrap().0;
+            }
+
+            let name = weaknesses.name();
+            let usable = if config.required_bosses.iter().any(|boss| boss == name) {
+                &ranged
+            } else {
+                &always_available
+            };
+            if !usable.iter().any(|weapon| weaknesses.can_be_damaged_by(*weapon)) {
+                // The Power Beam is always available, so there's always something to pick
+                let weapon = usable.choose(&mut rng).unwrap();
+                weaknesses.vulnerabilities[*weapon as usize] = TypeVulnerability::Normal;
+            }
+
+            for (_, weapon) in ESSENTIAL_WEAPONS.iter().filter(|(boss, _)| *boss == name) {
+                weaknesses.vulnerabilities[*weapon as usize] = TypeVulnerability::Normal;
+            }
+            weaknesses
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn ordinary_enemies_take_power_beam_damage()
+    {
+        let config = EnemyVulnerabilityConfig { required_bosses: vec!["Ridley".to_string()] };
+        let starting_items = StartingItems::from_u64(1);
+        let pickups = [PickupType::PlasmaBeam, PickupType::MorphBall, PickupType::PowerBomb];
+        for seed in 0..20 {
+            for weaknesses in randomize_enemy_weaknesses(seed, &config, &starting_items, &pickups) {
+                if weaknesses.name() != "Ridley" {
+                    assert!(weaknesses.can_be_damaged_by(Weapon::PowerBeam), "{}", weaknesses);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use enum_map::EnumMap;
+use rand::{
+    rngs::StdRng,
+    seq::SliceRandom,
+    Rng,
+    SeedableRng,
+};
+use serde::Deserialize;
+
+use crate::{
+    elevators::{Elevator, SpawnRoom},
+    logic::{self, Difficulty, ItemCounts},
+    patch_config::Layout,
+    pickup_meta::PickupType,
+    starting_items::StartingItems,
+};
+
+use std::collections::BTreeSet;
+
+/// How many of the items that are never needed to finish the game are placed
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemPool
+{
+    All,
+    None,
+    Some,
+}
+
+impl Default for ItemPool
+{
+    fn default() -> Self
+    {
+        ItemPool::All
+    }
+}
+
+/// The number of each item to place for the given pool, before any Nothings are added
+pub fn item_pool_counts(seed: u64, difficulty: &Difficulty, pool: ItemPool) -> ItemCounts
+{
+    let mut counts = logic::vanilla_item_counts();
+    match pool {
+        ItemPool::All => (),
+        ItemPool::None => {
+            for (pt, count) in &difficulty.optional_items {
+                *counts.entry(*pt).or_insert(0) -= count;
+            }
+        },
+        ItemPool::Some => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut readd_list = vec![];
+            let mut total_kept = 0;
+            for (pt, count) in &difficulty.optional_items {
+                // Remove 2/3rds (rounding up) of each type of item
+                let count_to_keep = count / 3;
+                let count_to_remove = count - count_to_keep;
+                total_kept += count_to_keep;
+                *counts.entry(*pt).or_insert(0) -= count_to_remove;
+                readd_list.extend((0..count_to_remove).map(|_| *pt));
+            }
+
+            // Add back approximately enough for the amount removed and the amount kept to be
+            // equal, with a slight variance (+/- 15%)
+            let count_to_add_back = (readd_list.len() as f64 - total_kept as f64) / 2.0;
+            let count_to_add_back = (count_to_add_back * rng.gen_range(0.85, 1.15)).round();
+            for _ in 0..(count_to_add_back as usize).min(readd_list.len()) {
+                let i = rng.gen_range(0, readd_list.len());
+                *counts.get_mut(&readd_list.remove(i)).unwrap() += 1;
+            }
+        },
+    }
+    counts.retain(|_, count| *count > 0);
+    counts
+}
+
+fn count_missing(
+    obtained: &ItemCounts,
+    conjunct: &[(PickupType, u32)],
+    missing: &mut Vec<PickupType>,
+) -> u32
+{
+    let mut missing_count = 0;
+    for (pt, count) in conjunct {
+        let current = obtained.get(pt).copied().unwrap_or(0);
+        if current < *count {
+            if !missing.contains(pt) {
+                missing.push(*pt);
+            }
+            missing_count += count - current;
+        }
+    }
+    missing_count
+}
+
+/// Places `item_counts` across the pickup locations of `difficulty` such that every location
+/// is reachable. Locations that can't be reached even with every item are given a Nothing,
+/// as are any locations left over once every item has been placed.
+///
+/// The requirements are written for the vanilla elevators and starting room. Other
+/// connections are allowed, but the items are still placed as if the world were vanilla, so
+/// the layout may not be completable.
+pub fn generate_layout(
+    seed: u64,
+    difficulty: &Difficulty,
+    item_counts: &ItemCounts,
+    starting_items: &StartingItems,
+    starting_location: SpawnRoom,
+    elevators: EnumMap<Elevator, SpawnRoom>,
+) -> Result<Layout, String>
+{
+    if let Err(e) = logic::check_vanilla_connections(starting_location, &elevators) {
+        eprintln!("Warning: the layout may not be completable. {}", e);
+    }
+    let location_count = difficulty.locations.len();
+
+    let nothing_count = item_counts.iter()
+        .filter(|(pt, _)| **pt != PickupType::Nothing)
+        .map(|(_, count)| *count as usize)
+        .sum::<usize>();
+    let nothing_count = location_count.checked_sub(nothing_count)
+        .ok_or_else(|| format!("Cannot place more than {} items", location_count))?;
+    let mut item_counts = item_counts.clone();
+    item_counts.insert(PickupType::Nothing, nothing_count as u32);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let starting_counts = logic::starting_item_counts(starting_items);
+    let mut max_items = starting_counts.clone();
+    for (pt, count) in &item_counts {
+        *max_items.entry(*pt).or_insert(0) += count;
+    }
+
+    // Items placed so far, which along with the starting items are the items obtained
+    let mut placed_counts = ItemCounts::new();
+    let mut obtained = starting_counts;
+    let mut placed: Vec<Option<PickupType>> = vec![None; location_count];
+
+    // Locations we cannot yet reach (and therefore cannot yet place an item in)
+    let mut unreachable_locations = BTreeSet::new();
+    // Locations we can reach, but haven't placed an item in yet
+    let mut reachable_unplaced_locations = vec![];
+
+    for (i, loc) in difficulty.locations.iter().enumerate() {
+        if loc.is_satisfied(&max_items) {
+            unreachable_locations.insert(i);
+        } else {
+            placed[i] = Some(PickupType::Nothing);
+            *placed_counts.entry(PickupType::Nothing).or_insert(0) += 1;
+        }
+    }
+
+    // Loop until every location can be reached
+    loop {
+        let newly_reachable: Vec<_> = unreachable_locations.iter()
+            .cloned()
+            .filter(|i| difficulty.locations[*i].is_satisfied(&obtained))
+            .collect();
+        for i in newly_reachable {
+            unreachable_locations.remove(&i);
+            reachable_unplaced_locations.push(i);
+        }
+
+        if unreachable_locations.is_empty() {
+            break;
+        }
+
+        // Find the smallest sets of items needed to reach one or more additional locations.
+        // Combinations that need more of an item than can be placed are ignored.
+        let mut smallest_needed_count = u32::MAX;
+        let mut smallest_needed_items = vec![];
+        for i in &unreachable_locations {
+            let loc = &difficulty.locations[*i];
+            for required in &loc.required.conjuncts {
+                for escape in &loc.escape.conjuncts {
+                    let feasible = required.iter().chain(escape.iter())
+                        .all(|(pt, count)| max_items.get(pt).copied().unwrap_or(0) >= *count);
+                    if !feasible {
+                        continue;
+                    }
+
+                    let mut needed_items = vec![];
+                    let needed_count = count_missing(&obtained, required, &mut needed_items)
+                        + count_missing(&obtained, escape, &mut needed_items);
+                    if needed_count == smallest_needed_count {
+                        for pt in needed_items {
+                            if !smallest_needed_items.contains(&pt) {
+                                smallest_needed_items.push(pt);
+                            }
+                        }
+                    } else if needed_count < smallest_needed_count {
+                        smallest_needed_count = needed_count;
+                        smallest_needed_items = needed_items;
+                    }
+                }
+            }
+        }
+
+        if smallest_needed_items.is_empty()
+            || smallest_needed_count as usize > reachable_unplaced_locations.len() {
+            Err("Not enough reachable locations to make any other location reachable")?
+        }
+
+        // Place one item from the smallest sets. The set it came from will now be (one of) the
+        // smallest, so another of its members will (potentially) be placed next iteration.
+        let item = *smallest_needed_items.choose(&mut rng).unwrap();
+        let mut new_obtained = obtained.clone();
+        *new_obtained.entry(item).or_insert(0) += 1;
+
+        // Every location that only needs this item to be escapable is a valid place to put it,
+        // and will be reachable after it's placed.
+        let escapable: Vec<_> = unreachable_locations.iter()
+            .cloned()
+            .filter(|i| {
+                let loc = &difficulty.locations[*i];
+                loc.required.is_satisfied(&obtained) && loc.escape.is_satisfied(&new_obtained)
+            })
+            .collect();
+        for i in escapable {
+            unreachable_locations.remove(&i);
+            reachable_unplaced_locations.push(i);
+        }
+
+        let loc = reachable_unplaced_locations
+            .remove(rng.gen_range(0, reachable_unplaced_locations.len()));
+        placed[loc] = Some(item);
+        *placed_counts.entry(item).or_insert(0) += 1;
+        obtained = new_obtained;
+    }
+
+    // Every location is reachable, so the rest of the items can go anywhere
+    for pt in PickupType::iter() {
+        let count = item_counts.get(&pt).copied().unwrap_or(0);
+        let current = placed_counts.get(&pt).copied().unwrap_or(0);
+        if current > count {
+            Err(format!("Placed too many of {}", pt.name()))?
+        }
+        for _ in current..count {
+            if reachable_unplaced_locations.is_empty() {
+                Err(format!("Ran out of locations while placing {}", pt.name()))?
+            }
+            let loc = reachable_unplaced_locations
+                .remove(rng.gen_range(0, reachable_unplaced_locations.len()));
+            placed[loc] = Some(pt);
+        }
+    }
+
+    let pickups = placed.into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Not every location was given an item")?;
+
+    Ok(Layout {
+        pickups,
+        starting_location,
+        elevators,
+        starting_items: None,
+        doors: vec![],
+        seed,
+    })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::patch_config::ArtifactTempleConfig;
+
+    #[test]
+    fn generated_layouts_are_completable()
+    {
+        let difficulty = crate::normal_difficulty::difficulty();
+        let mut elevators = EnumMap::<Elevator, SpawnRoom>::new();
+        elevators.extend(Elevator::iter()
+            .map(|elv| (elv, elv.elevator_data().default_dest.into())));
+        let starting_items = StartingItems::from_u64(1);
+
+        for pool in [ItemPool::All, ItemPool::Some, ItemPool::None].iter() {
+            for seed in 0..20 {
+                let item_counts = item_pool_counts(seed, &difficulty, *pool);
+                let layout = generate_layout(
+                    seed,
+                    &difficulty,
+                    &item_counts,
+                    &starting_items,
+                    SpawnRoom::LandingSite,
+                    elevators,
+                ).unwrap();
+                assert_eq!(layout.pickups.len(), difficulty.locations.len());
+
+                for (pt, count) in &item_counts {
+                    let placed = layout.pickups.iter().filter(|p| *p == pt).count();
+                    assert_eq!(placed, *count as usize, "{:?} with {:?}", pt, pool);
+                }
+
+                let goal = difficulty.goal(&layout.pickups, &ArtifactTempleConfig::default());
+                let report = logic::check_solvability(
+                    &difficulty,
+                    &layout,
+                    &starting_items,
+                    &[],
+                    &goal,
+                ).unwrap();
+                assert!(report.completable, "seed {} with {:?}", seed, pool);
+                for i in report.unreachable_locations {
+                    assert!(!difficulty.locations[i].is_satisfied(&item_counts));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,471 @@
+//! The versioned layout string format. A layout string is the base64url encoding (without
+//! padding) of:
+//!
+//! - a header byte holding the format version
+//! - a sequence of sections, each a varint tag, a varint length and then that many bytes
+//! - a big-endian CRC-32 of everything before it
+//!
+//! All integers in the sections are LEB128 varints. Sections with unknown tags are skipped, so
+//! new kinds of data can be added without breaking older patchers. Legacy layout strings are
+//! still handled by `Layout::from_str`.
+
+use enum_map::EnumMap;
+
+use crate::{
+    door_meta::{BlastShieldType, DoorType},
+    elevators::{Elevator, SpawnRoom},
+    patch_config::{DoorAssignment, Layout},
+    pickup_meta::{self, PickupType},
+    starting_items::StartingItems,
+};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::Hasher,
+};
+
+const VERSION: u8 = 2;
+
+// Section tags
+const PICKUPS: u64 = 1;
+const STARTING_LOCATION: u64 = 2;
+const ELEVATORS: u64 = 3;
+const STARTING_ITEMS: u64 = 4;
+const DOORS: u64 = 5;
+const SEED: u64 = 6;
+
+// The numbers doors and blast shields are stored as. These must never change, so that old layout
+// strings keep decoding to the same doors.
+const DOOR_TYPE_CODES: [(u32, DoorType); 31] = [
+    (0, DoorType::Blue),
+    (1, DoorType::Purple),
+    (2, DoorType::White),
+    (3, DoorType::Red),
+    (4, DoorType::PowerOnly),
+    (5, DoorType::PowerBomb),
+    (6, DoorType::Bomb),
+    (7, DoorType::Boost),
+    (8, DoorType::Missile),
+    (9, DoorType::Charge),
+    (10, DoorType::Super),
+    (11, DoorType::Wavebuster),
+    (12, DoorType::Icespreader),
+    (13, DoorType::Flamethrower),
+    (14, DoorType::Ai),
+    (15, DoorType::Disabled),
+    (16, DoorType::VerticalBlue),
+    (17, DoorType::VerticalPowerOnly),
+    (18, DoorType::VerticalPurple),
+    (19, DoorType::VerticalWhite),
+    (20, DoorType::VerticalRed),
+    (21, DoorType::VerticalPowerBomb),
+    (22, DoorType::VerticalBomb),
+    (23, DoorType::VerticalMissile),
+    (24, DoorType::VerticalCharge),
+    (25, DoorType::VerticalSuper),
+    (26, DoorType::VerticalDisabled),
+    (27, DoorType::VerticalWavebuster),
+    (28, DoorType::VerticalIcespreader),
+    (29, DoorType::VerticalFlamethrower),
+    (30, DoorType::VerticalAi),
+];
+
+const BLAST_SHIELD_CODES: [(u32, BlastShieldType); 7] = [
+    (0, BlastShieldType::None),
+    (1, BlastShieldType::Missile),
+    (2, BlastShieldType::PowerBomb),
+    (3, BlastShieldType::Super),
+    (4, BlastShieldType::Wavebuster),
+    (5, BlastShieldType::Icespreader),
+    (6, BlastShieldType::Flamethrower),
+];
+
+const BASE64_TABLE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn crc32(bytes: &[u8]) -> u32
+{
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn base64_encode(bytes: &[u8]) -> String
+{
+    let mut s = String::with_capacity((bytes.len() * 4 + 2) / 3);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..(chunk.len() + 1) {
+            s.push(BASE64_TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    s
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, String>
+{
+    if text.len() % 4 == 1 {
+        Err("Layout string has an invalid length")?
+    }
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64_TABLE.iter().position(|t| t == c)
+                .ok_or_else(|| format!("Layout contains invalid character '{}'.", *c as char))?;
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        bytes.extend((0..(chunk.len() - 1)).map(|i| (n >> (16 - 8 * i)) as u8));
+    }
+    Ok(bytes)
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut n: u64)
+{
+    while n >= 0x80 {
+        buf.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+struct SectionReader<'a>
+{
+    bytes: &'a [u8],
+}
+
+impl<'a> SectionReader<'a>
+{
+    fn is_empty(&self) -> bool
+    {
+        self.bytes.is_empty()
+    }
+
+    fn read_varint(&mut self) -> Result<u64, String>
+    {
+        let mut n = 0u64;
+        for (i, byte) in self.bytes.iter().enumerate().take(10) {
+            n |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                self.bytes = &self.bytes[i + 1..];
+                return Ok(n);
+            }
+        }
+        Err("Layout string contains a malformed number".to_string())
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String>
+    {
+        let n = self.read_varint()?;
+        if n > u32::MAX as u64 {
+            Err("Layout string contains an out of range number")?
+        }
+        Ok(n as u32)
+    }
+
+    fn read_section(&mut self) -> Result<(u64, SectionReader<'a>), String>
+    {
+        let tag = self.read_varint()?;
+        let len = self.read_varint()? as usize;
+        if len > self.bytes.len() {
+            Err("Layout string section is truncated")?
+        }
+        let (section, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok((tag, SectionReader { bytes: section }))
+    }
+}
+
+pub(crate) fn spawn_room_number(spawn_room: SpawnRoom) -> Result<u32, String>
+{
+    (0..)
+        .map(|i| (i, SpawnRoom::from_u32(i)))
+        .take_while(|(_, room)| room.is_some())
+        .find(|(_, room)| *room == Some(spawn_room))
+        .map(|(i, _)| i)
+        .ok_or_else(|| format!("{:?} cannot be stored in a layout string", spawn_room))
+}
+
+fn read_spawn_room(reader: &mut SectionReader) -> Result<SpawnRoom, String>
+{
+    let n = reader.read_u32()?;
+    SpawnRoom::from_u32(n).ok_or_else(|| format!("Layout string has an invalid room {}", n))
+}
+
+fn door_location_count() -> usize
+{
+    pickup_meta::ROOM_INFO.iter()
+        .flat_map(|(_, rooms)| rooms.iter())
+        .map(|room| room.door_locations.len())
+        .sum()
+}
+
+/// Encodes a layout in the current layout string format
+pub fn encode(layout: &Layout) -> Result<String, String>
+{
+    let mut sections: Vec<(u64, Vec<u8>)> = vec![];
+
+    let mut pickups = vec![];
+    write_varint(&mut pickups, layout.pickups.len() as u64);
+    for pt in &layout.pickups {
+        write_varint(&mut pickups, pt.idx() as u64);
+    }
+    sections.push((PICKUPS, pickups));
+
+    let mut starting_location = vec![];
+    write_varint(&mut starting_location, spawn_room_number(layout.starting_location)? as u64);
+    sections.push((STARTING_LOCATION, starting_location));
+
+    // Only the elevators that don't go to their usual destination are stored
+    let changed_elevators: Vec<_> = Elevator::iter()
+        .filter(|elv| layout.elevators[*elv] != elv.elevator_data().default_dest)
+        .collect();
+    if !changed_elevators.is_empty() {
+        let mut elevators = vec![];
+        write_varint(&mut elevators, changed_elevators.len() as u64);
+        for elv in changed_elevators {
+            write_varint(&mut elevators, elv as u64);
+            write_varint(&mut elevators, spawn_room_number(layout.elevators[elv])? as u64);
+        }
+        sections.push((ELEVATORS, elevators));
+    }
+
+    if let Some(starting_items) = &layout.starting_items {
+        let counts = starting_items.item_counts();
+        let mut section = vec![];
+        write_varint(&mut section, counts.len() as u64);
+        for (pt, count) in counts {
+            write_varint(&mut section, pt.idx() as u64);
+            write_varint(&mut section, count as u64);
+        }
+        sections.push((STARTING_ITEMS, section));
+    }
+
+    if !layout.doors.is_empty() {
+        let mut doors = vec![];
+        write_varint(&mut doors, layout.doors.len() as u64);
+        for door in &layout.doors {
+            write_varint(&mut doors, door.location as u64);
+            let door_type_code = DOOR_TYPE_CODES.iter()
+                .find(|(_, door_type)| *door_type == door.door_type)
+                .map(|(code, _)| *code)
+                .ok_or_else(|| format!("{:?} cannot be stored in a layout string", door.door_type))?;
+            write_varint(&mut doors, door_type_code as u64);
+            let blast_shield_code = BLAST_SHIELD_CODES.iter()
+                .find(|(_, blast_shield)| *blast_shield == door.blast_shield)
+                .map(|(code, _)| *code)
+                .ok_or_else(|| {
+                    format!("{:?} cannot be stored in a layout string", door.blast_shield)
+                })?;
+            write_varint(&mut doors, blast_shield_code as u64);
+        }
+        sections.push((DOORS, doors));
+    }
+
+    let mut seed = vec![];
+    write_varint(&mut seed, layout.seed);
+    sections.push((SEED, seed));
+
+    let mut bytes = vec![VERSION];
+    for (tag, contents) in sections {
+        write_varint(&mut bytes, tag);
+        write_varint(&mut bytes, contents.len() as u64);
+        bytes.extend(contents);
+    }
+    let crc = crc32(&bytes);
+    bytes.extend(&crc.to_be_bytes());
+    Ok(base64_encode(&bytes))
+}
+
+/// Decodes a layout string in the current format
+pub fn decode(text: &str) -> Result<Layout, String>
+{
+    let bytes = base64_decode(text)?;
+    if bytes.len() < 5 {
+        Err("Layout string is too short")?
+    }
+    let (data, crc) = bytes.split_at(bytes.len() - 4);
+    if crc32(data).to_be_bytes() != crc {
+        Err("Layout checksum failed.")?
+    }
+    if data[0] != VERSION {
+        Err(format!("Unsupported layout string version {}", data[0]))?
+    }
+
+    let mut pickups = None;
+    let mut starting_location = SpawnRoom::LandingSite;
+    let mut elevators = EnumMap::<Elevator, SpawnRoom>::new();
+    elevators.extend(Elevator::iter()
+        .map(|elv| (elv, elv.elevator_data().default_dest.into())));
+    let mut starting_items = None;
+    let mut doors = vec![];
+    let mut seed = None;
+
+    let mut reader = SectionReader { bytes: &data[1..] };
+    while !reader.is_empty() {
+        let (tag, mut section) = reader.read_section()?;
+        match tag {
+            PICKUPS => {
+                let count = section.read_varint()?;
+                let pickup_types = (0..count)
+                    .map(|_| {
+                        let idx = section.read_varint()?;
+                        PickupType::from_idx(idx as usize)
+                            .ok_or_else(|| format!("Layout string has an invalid pickup {}", idx))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                pickups = Some(pickup_types);
+            },
+            STARTING_LOCATION => starting_location = read_spawn_room(&mut section)?,
+            ELEVATORS => {
+                for _ in 0..section.read_varint()? {
+                    let n = section.read_u32()?;
+                    let elv = Elevator::from_u32(n)
+                        .ok_or_else(|| format!("Layout string has an invalid elevator {}", n))?;
+                    elevators[elv] = read_spawn_room(&mut section)?;
+                }
+            },
+            STARTING_ITEMS => {
+                let counts = (0..section.read_varint()?)
+                    .map(|_| {
+                        let idx = section.read_varint()?;
+                        let pt = PickupType::from_idx(idx as usize)
+                            .ok_or_else(|| format!("Layout string has an invalid item {}", idx))?;
+                        Ok((pt, section.read_u32()?))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                starting_items = Some(StartingItems::from_item_counts(&counts)?);
+            },
+            DOORS => {
+                let location_count = door_location_count();
+                for _ in 0..section.read_varint()? {
+                    let location = section.read_u32()?;
+                    if location as usize >= location_count {
+                        Err(format!("Layout string has an invalid door location {}", location))?
+                    }
+                    let n = section.read_u32()?;
+                    let door_type = DOOR_TYPE_CODES.iter()
+                        .find(|(code, _)| *code == n)
+                        .map(|(_, door_type)| *door_type)
+                        .ok_or_else(|| format!("Layout string has an invalid door {}", n))?;
+                    let n = section.read_u32()?;
+                    let blast_shield = BLAST_SHIELD_CODES.iter()
+                        .find(|(code, _)| *code == n)
+                        .map(|(_, blast_shield)| *blast_shield)
+                        .ok_or_else(|| format!("Layout string has an invalid blast shield {}", n))?;
+                    doors.push(DoorAssignment { location, door_type, blast_shield });
+                }
+            },
+            SEED => seed = Some(section.read_varint()?),
+            _ => (),
+        }
+    }
+
+    let pickups = pickups.ok_or("Layout string has no pickups")?;
+    let seed = seed.unwrap_or_else(|| {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(data);
+        hasher.finish()
+    });
+
+    Ok(Layout {
+        pickups,
+        starting_location,
+        elevators,
+        starting_items,
+        doors,
+        seed,
+    })
+}
+
+/// Whether a layout string is in the current format rather than the legacy one. Legacy strings
+/// are 87 characters, optionally preceded by a '!' and an elevator section ending in a '.',
+/// neither of which can appear in the current format.
+pub fn is_v2(text: &str) -> bool
+{
+    if text.contains(|c| c == '.' || c == '!') {
+        false
+    } else {
+        text.len() != 87 || decode(text).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const LEGACY_LAYOUT: &str =
+        "NCiq7nTAtTnqPcap9VMQk_o8Qj6ZjbPiOdYDB5tgtwL_f01-UpYklNGnL-gTu5IeVW3IoUiflH5LqNXB3wVEER4";
+
+    #[test]
+    fn base64_round_trips()
+    {
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 200) as u8).collect();
+            assert_eq!(base64_decode(&base64_encode(&bytes)).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn layout_round_trips()
+    {
+        let mut layout: Layout = LEGACY_LAYOUT.parse().unwrap();
+        assert!(!is_v2(LEGACY_LAYOUT));
+
+        layout.pickups.push(PickupType::ScanVisor);
+        layout.starting_location = SpawnRoom::Elevator(Elevator::ChozoRuinsWestMainPlaza);
+        layout.elevators[Elevator::ArtifactTemple] = SpawnRoom::LandingSite;
+        let mut starting_items = StartingItems::from_u64(1);
+        starting_items.missiles = 25;
+        starting_items.morph_ball = true;
+        starting_items.artifacts = vec![PickupType::ArtifactOfTruth];
+        layout.starting_items = Some(starting_items);
+        layout.doors = vec![DoorAssignment {
+            location: 3,
+            door_type: DoorType::VerticalAi,
+            blast_shield: BlastShieldType::None,
+        }];
+
+        let text = encode(&layout).unwrap();
+        assert!(is_v2(&text));
+        let decoded: Layout = text.parse().unwrap();
+        assert_eq!(decoded.pickups, layout.pickups);
+        assert_eq!(decoded.starting_location, layout.starting_location);
+        assert_eq!(decoded.elevators, layout.elevators);
+        assert_eq!(decoded.starting_items, layout.starting_items);
+        assert_eq!(decoded.doors, layout.doors);
+        assert_eq!(decoded.seed, layout.seed);
+    }
+
+    #[test]
+    fn every_door_has_a_code()
+    {
+        for door_type in DoorType::iter() {
+            assert_eq!(DOOR_TYPE_CODES.iter().filter(|(_, d)| *d == door_type).count(), 1);
+        }
+        for blast_shield in BlastShieldType::iter().chain(std::iter::once(BlastShieldType::None)) {
+            assert_eq!(BLAST_SHIELD_CODES.iter().filter(|(_, b)| *b == blast_shield).count(), 1);
+        }
+        let mut codes: Vec<_> = DOOR_TYPE_CODES.iter().map(|(code, _)| *code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), DOOR_TYPE_CODES.len());
+    }
+
+    #[test]
+    fn corrupted_layout_is_rejected()
+    {
+        let layout: Layout = LEGACY_LAYOUT.parse().unwrap();
+        let mut text = encode(&layout).unwrap().into_bytes();
+        text[10] = if text[10] == b'A' { b'B' } else { b'A' };
+        let text = String::from_utf8(text).unwrap();
+        assert_eq!(decode(&text).unwrap_err(), "Layout checksum failed.");
+    }
+}
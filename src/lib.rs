@@ -21,12 +21,19 @@ pub mod custom_assets;
 pub mod ciso_writer;
 pub mod dol_patcher;
 pub mod elevators;
+pub mod enemy_weaknesses;
 pub mod gcz_writer;
+pub mod generator;
+pub mod layout_string;
+pub mod logic;
 pub mod mlvl_wrapper;
+pub mod multiworld;
+pub mod normal_difficulty;
 pub mod patch_config;
 pub mod patcher;
 pub mod patches;
 pub mod pickup_meta;
+pub mod spoiler;
 pub mod door_meta;
 pub mod starting_items;
 pub mod txtr_conversions;
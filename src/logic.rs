@@ -0,0 +1,413 @@
+use enum_map::EnumMap;
+
+use crate::{
+    elevators::{Elevator, SpawnRoom},
+    patch_config::{ArtifactTempleConfig, Layout, ProgressiveItem, TempleBoss},
+    pickup_meta::PickupType,
+    starting_items::StartingItems,
+};
+
+use std::collections::HashMap;
+
+pub type ItemCounts = HashMap<PickupType, u32>;
+
+/// A set of item requirements in disjunctive normal form. It is satisfied if all of the item
+/// counts in any one of its conjuncts are met.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Requirement
+{
+    pub conjuncts: Vec<Vec<(PickupType, u32)>>,
+}
+
+impl Requirement
+{
+    /// A requirement that is always satisfied
+    pub fn none() -> Self
+    {
+        Requirement { conjuncts: vec![vec![]] }
+    }
+
+    pub fn n_of(pickup_type: PickupType, count: u32) -> Self
+    {
+        Requirement { conjuncts: vec![vec![(pickup_type, count)]] }
+    }
+
+    /// Satisfied if every one of `reqs` is satisfied
+    pub fn all<I>(reqs: I) -> Self
+        where I: IntoIterator<Item = Requirement>
+    {
+        fn merge(a: &[(PickupType, u32)], b: &[(PickupType, u32)]) -> Vec<(PickupType, u32)>
+        {
+            let mut merged = a.to_vec();
+            for &(pt, count) in b {
+                match merged.iter_mut().find(|(other, _)| *other == pt) {
+                    Some(existing) => existing.1 = existing.1.max(count),
+                    None => merged.push((pt, count)),
+                }
+            }
+            merged
+        }
+
+        let mut conjuncts = vec![vec![]];
+        for req in reqs {
+            conjuncts = conjuncts.iter()
+                .flat_map(|conjunct| req.conjuncts.iter().map(move |other| merge(conjunct, other)))
+                .collect();
+        }
+        Requirement { conjuncts }
+    }
+
+    /// Satisfied if any one of `reqs` is satisfied
+    pub fn any<I>(reqs: I) -> Self
+        where I: IntoIterator<Item = Requirement>
+    {
+        Requirement {
+            conjuncts: reqs.into_iter().flat_map(|req| req.conjuncts).collect(),
+        }
+    }
+
+    pub fn is_satisfied(&self, items: &ItemCounts) -> bool
+    {
+        self.conjuncts.iter().any(|conjunct| {
+            conjunct.iter()
+                .all(|(pt, count)| items.get(pt).copied().unwrap_or(0) >= *count)
+        })
+    }
+}
+
+impl From<PickupType> for Requirement
+{
+    fn from(pickup_type: PickupType) -> Self
+    {
+        Requirement::n_of(pickup_type, 1)
+    }
+}
+
+impl From<&PickupType> for Requirement
+{
+    fn from(pickup_type: &PickupType) -> Self
+    {
+        Requirement::n_of(*pickup_type, 1)
+    }
+}
+
+impl From<&Requirement> for Requirement
+{
+    fn from(req: &Requirement) -> Self
+    {
+        req.clone()
+    }
+}
+
+/// What's needed to collect the item at a pickup location
+#[derive(Clone, Debug)]
+pub struct LocationRequirements
+{
+    /// Needed to reach the location
+    pub required: Requirement,
+    /// Needed to leave the location again after collecting its item
+    pub escape: Requirement,
+}
+
+impl LocationRequirements
+{
+    pub fn new(required: Requirement) -> Self
+    {
+        LocationRequirements {
+            required,
+            escape: Requirement::none(),
+        }
+    }
+
+    pub fn with_escape(mut self, escape: Requirement) -> Self
+    {
+        self.escape = escape;
+        self
+    }
+
+    pub fn is_satisfied(&self, items: &ItemCounts) -> bool
+    {
+        self.required.is_satisfied(items) && self.escape.is_satisfied(items)
+    }
+}
+
+/// A requirement database for every pickup location, in layout order
+#[derive(Clone, Debug)]
+pub struct Difficulty
+{
+    /// Items that are never needed to finish the game and how many of each there are
+    pub optional_items: Vec<(PickupType, u32)>,
+    pub locations: Vec<LocationRequirements>,
+    /// The location guarded by each boss, which can only be reached by defeating it
+    pub boss_locations: Vec<(TempleBoss, usize)>,
+    /// Needed to defeat Meta Ridley once the Artifact Temple is open
+    pub meta_ridley: Requirement,
+    /// Needed to get through the Impact Crater and defeat Metroid Prime
+    pub impact_crater: Requirement,
+}
+
+impl Difficulty
+{
+    /// What's needed to finish the game with the given layout and Artifact Temple settings
+    pub fn goal(&self, pickup_layout: &[PickupType], config: &ArtifactTempleConfig) -> Requirement
+    {
+        let mut reqs: Vec<Requirement> = config.required_artifact_types(pickup_layout).iter()
+            .map(Requirement::from)
+            .collect();
+        for boss in &config.required_bosses {
+            let (_, i) = self.boss_locations.iter().find(|(b, _)| b == boss).unwrap();
+            let loc = &self.locations[*i];
+            reqs.push(Requirement::all(vec![loc.required.clone(), loc.escape.clone()]));
+        }
+        if !config.skip_ridley {
+            reqs.push(self.meta_ridley.clone());
+        }
+        if !config.skip_impact_crater {
+            reqs.push(self.impact_crater.clone());
+        }
+        Requirement::all(reqs)
+    }
+}
+
+/// The requirement databases only describe the vanilla world, where the game starts at the
+/// Landing Site and every elevator leads to its usual destination
+pub fn check_vanilla_connections(
+    starting_location: SpawnRoom,
+    elevators: &EnumMap<Elevator, SpawnRoom>,
+) -> Result<(), String>
+{
+    if starting_location != SpawnRoom::LandingSite {
+        Err("The item requirements only support starting at the Landing Site")?
+    }
+    let changed = Elevator::iter()
+        .find(|elv| elevators[*elv] != elv.elevator_data().default_dest);
+    if let Some(elv) = changed {
+        Err(format!(
+            "The item requirements only support vanilla elevators, but {} was changed",
+            elv.elevator_data().name,
+        ))?
+    }
+    Ok(())
+}
+
+/// The item the player gets from a pickup. A progressive item's pickups give the first of its
+/// items the player doesn't have yet, and nothing once they have all of them.
+pub fn collected_item(
+    pickup_type: PickupType,
+    obtained: &ItemCounts,
+    progressive_items: &[ProgressiveItem],
+) -> Option<PickupType>
+{
+    match progressive_items.iter().find(|p| p.items.contains(&pickup_type)) {
+        Some(progressive_item) => progressive_item.items.iter()
+            .find(|pt| obtained.get(pt).copied().unwrap_or(0) == 0)
+            .copied(),
+        None => Some(pickup_type),
+    }
+}
+
+/// The result of simulating a playthrough of a layout
+#[derive(Clone, Debug)]
+pub struct SolvabilityReport
+{
+    /// The locations whose items can be collected at each step of the playthrough
+    pub spheres: Vec<Vec<usize>>,
+    /// The locations that can never be reached, in layout order
+    pub unreachable_locations: Vec<usize>,
+    /// The items obtained by the end of the playthrough, including starting items
+    pub obtained_items: ItemCounts,
+    pub completable: bool,
+}
+
+/// Collects every item that can be reached from the start of the game, repeating until no new
+/// locations become reachable, and checks whether the goal can be met with what was collected.
+pub fn check_solvability(
+    difficulty: &Difficulty,
+    layout: &Layout,
+    starting_items: &StartingItems,
+    progressive_items: &[ProgressiveItem],
+    goal: &Requirement,
+) -> Result<SolvabilityReport, String>
+{
+    check_vanilla_connections(layout.starting_location, &layout.elevators)?;
+    if layout.pickups.len() != difficulty.locations.len() {
+        Err(format!(
+            "Layout has {} pickups, but there are {} pickup locations",
+            layout.pickups.len(),
+            difficulty.locations.len(),
+        ))?
+    }
+
+    let mut obtained = starting_item_counts(starting_items);
+    let mut collected = vec![false; layout.pickups.len()];
+    let mut spheres = vec![];
+    loop {
+        let sphere: Vec<usize> = difficulty.locations.iter().enumerate()
+            .filter(|(i, loc)| {
+                // The item at a location may be what's needed to escape it
+                let mut with_item = obtained.clone();
+                if let Some(pt) = collected_item(layout.pickups[*i], &obtained, progressive_items) {
+                    *with_item.entry(pt).or_insert(0) += 1;
+                }
+                !collected[*i]
+                    && loc.required.is_satisfied(&obtained)
+                    && loc.escape.is_satisfied(&with_item)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if sphere.is_empty() {
+            break;
+        }
+        for i in &sphere {
+            collected[*i] = true;
+            if let Some(pt) = collected_item(layout.pickups[*i], &obtained, progressive_items) {
+                *obtained.entry(pt).or_insert(0) += 1;
+            }
+        }
+        spheres.push(sphere);
+    }
+
+    Ok(SolvabilityReport {
+        spheres,
+        unreachable_locations: (0..collected.len()).filter(|i| !collected[*i]).collect(),
+        completable: goal.is_satisfied(&obtained),
+        obtained_items: obtained,
+    })
+}
+
+/// The number of each item in the vanilla game
+pub fn vanilla_item_counts() -> ItemCounts
+{
+    PickupType::iter()
+        .map(|pt| {
+            let count = match pt {
+                PickupType::Missile => 50,
+                PickupType::EnergyTank => 14,
+                PickupType::PowerBombExpansion => 4,
+                PickupType::Nothing | PickupType::ScanVisor | PickupType::ShinyMissile => 0,
+                _ => 1,
+            };
+            (pt, count)
+        })
+        .filter(|&(_, count)| count > 0)
+        .collect()
+}
+
+/// The items a player starts with, counted the same way as the items placed in a layout
+pub fn starting_item_counts(starting_items: &StartingItems) -> ItemCounts
+{
+    let mut counts: ItemCounts = PickupType::iter()
+        .filter(|pt| starting_items.contains(*pt))
+        .map(|pt| (pt, 1))
+        .collect();
+    // Requirements count missiles by expansion, which gives 5 each
+    counts.insert(PickupType::Missile, (starting_items.missiles as u32 + 4) / 5);
+    counts.insert(PickupType::EnergyTank, starting_items.energy_tanks as u32);
+    counts.remove(&PickupType::PowerBombExpansion);
+    counts.retain(|_, count| *count > 0);
+    counts
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn requirements_are_distributed_into_conjuncts()
+    {
+        let req = Requirement::all(vec![
+            Requirement::from(PickupType::Missile),
+            Requirement::any(vec![PickupType::IceBeam.into(), PickupType::WaveBeam.into()]),
+            Requirement::n_of(PickupType::Missile, 3),
+        ]);
+        assert_eq!(req.conjuncts, vec![
+            vec![(PickupType::Missile, 3), (PickupType::IceBeam, 1)],
+            vec![(PickupType::Missile, 3), (PickupType::WaveBeam, 1)],
+        ]);
+
+        let mut items = ItemCounts::new();
+        items.insert(PickupType::WaveBeam, 1);
+        items.insert(PickupType::Missile, 2);
+        assert!(!req.is_satisfied(&items));
+        items.insert(PickupType::Missile, 3);
+        assert!(req.is_satisfied(&items));
+    }
+
+    #[test]
+    fn empty_layout_is_not_completable()
+    {
+        let difficulty = crate::normal_difficulty::difficulty();
+        let mut elevators = EnumMap::<Elevator, SpawnRoom>::new();
+        elevators.extend(Elevator::iter()
+            .map(|elv| (elv, elv.elevator_data().default_dest.into())));
+        let layout = Layout {
+            pickups: vec![PickupType::Nothing; difficulty.locations.len()],
+            starting_location: SpawnRoom::LandingSite,
+            elevators,
+            starting_items: None,
+            doors: vec![],
+            seed: 0,
+        };
+        let starting_items = StartingItems::from_u64(1);
+        let goal = difficulty.goal(&layout.pickups, &ArtifactTempleConfig::default());
+
+        let report = check_solvability(&difficulty, &layout, &starting_items, &[], &goal).unwrap();
+        assert!(!report.completable);
+        assert_eq!(report.spheres.len(), 1);
+        for (i, loc) in difficulty.locations.iter().enumerate() {
+            let reachable = loc.is_satisfied(&starting_item_counts(&starting_items));
+            assert_eq!(report.unreachable_locations.contains(&i), !reachable);
+        }
+    }
+
+    #[test]
+    fn progressive_pickups_give_the_first_missing_item()
+    {
+        let beams = [ProgressiveItem {
+            name: "Progressive Beam".to_string(),
+            items: vec![PickupType::WaveBeam, PickupType::IceBeam, PickupType::PlasmaBeam],
+        }];
+        let mut obtained = ItemCounts::new();
+        obtained.insert(PickupType::IceBeam, 1);
+        assert_eq!(
+            collected_item(PickupType::PlasmaBeam, &obtained, &beams),
+            Some(PickupType::WaveBeam),
+        );
+        obtained.insert(PickupType::WaveBeam, 1);
+        assert_eq!(
+            collected_item(PickupType::WaveBeam, &obtained, &beams),
+            Some(PickupType::PlasmaBeam),
+        );
+        obtained.insert(PickupType::PlasmaBeam, 1);
+        assert_eq!(collected_item(PickupType::IceBeam, &obtained, &beams), None);
+        assert_eq!(
+            collected_item(PickupType::Missile, &obtained, &beams),
+            Some(PickupType::Missile),
+        );
+
+        let difficulty = crate::normal_difficulty::difficulty();
+        let starting_items = StartingItems::from_u64(1);
+        let first_reachable = difficulty.locations.iter()
+            .position(|loc| loc.is_satisfied(&starting_item_counts(&starting_items)))
+            .unwrap();
+        let mut elevators = EnumMap::<Elevator, SpawnRoom>::new();
+        elevators.extend(Elevator::iter()
+            .map(|elv| (elv, elv.elevator_data().default_dest.into())));
+        let mut layout = Layout {
+            pickups: vec![PickupType::Nothing; difficulty.locations.len()],
+            starting_location: SpawnRoom::LandingSite,
+            elevators,
+            starting_items: None,
+            doors: vec![],
+            seed: 0,
+        };
+        layout.pickups[first_reachable] = PickupType::PlasmaBeam;
+        let goal = Requirement::none();
+
+        let report = check_solvability(&difficulty, &layout, &starting_items, &beams, &goal)
+            .unwrap();
+        assert_eq!(report.obtained_items.get(&PickupType::WaveBeam), Some(&1));
+        assert_eq!(report.obtained_items.get(&PickupType::PlasmaBeam), None);
+    }
+}
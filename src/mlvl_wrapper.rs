@@ -5,7 +5,7 @@ use structs::{
 use reader_writer::{CStr, FourCC, LazyArray};
 
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct MlvlEditor<'r>
 {
@@ -16,6 +16,8 @@ pub struct MlvlArea<'r, 'mlvl, 'cursor, 'list>
 {
     pub mrea_cursor: &'cursor mut ResourceListCursor<'r, 'list>,
     pub mlvl_area: &'mlvl mut Area<'r>,
+    // The area's position in the MLVL's list of areas
+    pub area_index: u32,
     pub layer_flags: &'mlvl mut AreaLayerFlags,
     pub layer_names: &'mlvl mut Vec<CStr<'r>>,
     pub memory_relay_conns: &'mlvl mut LazyArray<'r, MemoryRelayConn>,
@@ -43,6 +45,7 @@ impl<'r> MlvlEditor<'r>
         MlvlArea {
             mrea_cursor,
             mlvl_area: area,
+            area_index: i as u32,
             layer_flags: self.mlvl.area_layer_flags.as_mut_vec().get_mut(i).unwrap(),
             layer_names: self.mlvl.area_layer_names.mut_names_for_area(i).unwrap(),
             memory_relay_conns: &mut self.mlvl.memory_relay_conns,
@@ -62,6 +65,19 @@ impl<'r, 'mlvl, 'cursor, 'list> MlvlArea<'r, 'mlvl, 'cursor, 'list>
         self.mrea_cursor.value().unwrap().kind.as_mrea_mut().unwrap()
     }
 
+    /// The instance ids of every object in the area, without parsing the MREA any more than
+    /// reading them needs
+    pub fn instance_ids(&mut self) -> Vec<u32>
+    {
+        let res = self.mrea_cursor.peek().unwrap();
+        let mrea = res.kind.as_mrea().unwrap();
+        let scly = mrea.scly_section();
+        let ids = scly.layers.iter()
+            .flat_map(|layer| layer.objects.iter().map(|obj| obj.instance_id).collect::<Vec<_>>())
+            .collect();
+        ids
+    }
+
     pub fn add_layer(&mut self, name: CStr<'r>)
     {
         // Mark this layer as active
@@ -78,6 +94,41 @@ impl<'r, 'mlvl, 'cursor, 'list> MlvlArea<'r, 'mlvl, 'cursor, 'list>
         self.mrea().scly_section_mut().layers.as_mut_vec().push(SclyLayer::new());
     }
 
+    pub fn set_layer_active(&mut self, layer: u32, active: bool) -> Result<(), String>
+    {
+        if layer >= self.layer_flags.layer_count {
+            return Err(format!(
+                "Area 0x{:08X} has no layer {}",
+                self.mlvl_area.mrea.to_u32(),
+                layer,
+            ))
+        }
+        if active {
+            self.layer_flags.flags |= 1 << layer;
+        } else {
+            self.layer_flags.flags &= !(1 << layer);
+        }
+        Ok(())
+    }
+
+    /// Copies the dependencies of `from_layer` whose asset ids are in `asset_ids` to
+    /// `to_layer`, e.g. so an object moved between layers still has its assets loaded
+    pub fn copy_dependencies(&mut self, from_layer: usize, to_layer: usize,
+                             asset_ids: &HashSet<u32>)
+    {
+        let layers = self.mlvl_area.dependencies.deps.as_mut_vec();
+        let deps: Vec<Dependency> = layers[from_layer].iter()
+            .filter(|dep| asset_ids.contains(&dep.asset_id))
+            .map(|dep| dep.into_owned())
+            .collect();
+        let to_layer = layers[to_layer].as_mut_vec();
+        for dep in deps {
+            if !to_layer.contains(&dep) {
+                to_layer.push(dep);
+            }
+        }
+    }
+
     pub fn add_dependencies<I>(&mut self, pickup_resources: &HashMap<(u32, FourCC), Resource<'r>>,
                                layer_num: usize, deps: I)
         where I: Iterator<Item=Dependency>,
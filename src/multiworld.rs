@@ -0,0 +1,717 @@
+//! The host side of multiworld games. While a patched game is running, its REL keeps a mailbox
+//! in memory: it lists the locations of the pickups the player has collected for other players,
+//! and the host fills it with the items other players have collected for this one. The bridge
+//! reads and writes that mailbox through Dolphin's memory (or a file standing in for it) and
+//! relays the pickups between the players' bridges through a relay server.
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::patch_config::MultiworldConfig;
+use crate::pickup_meta::PickupType;
+
+include!("../compile_to_ppc/multiworld_mailbox.rs");
+
+// Where the fields of the mailbox are, relative to its start. Every field after the magic is a
+// u32.
+const ACTIVE_OFFSET: u32 = 8;
+const SENT_COUNT_OFFSET: u32 = ACTIVE_OFFSET + 4;
+const SENT_OFFSET: u32 = SENT_COUNT_OFFSET + 4;
+const SENT_ACKED_OFFSET: u32 = SENT_OFFSET + 4 * MULTIWORLD_MAILBOX_SENT_SLOTS as u32;
+const RECEIVED_COUNT_OFFSET: u32 = SENT_ACKED_OFFSET + 4;
+const RECEIVED_OFFSET: u32 = RECEIVED_COUNT_OFFSET + 4;
+const RECEIVED_ITEM_SIZE: u32 = 12;
+const RECEIVED_GIVEN_OFFSET: u32 =
+    RECEIVED_OFFSET + RECEIVED_ITEM_SIZE * MULTIWORLD_MAILBOX_RECEIVED_SLOTS as u32;
+const MAILBOX_SIZE: u32 = RECEIVED_GIVEN_OFFSET + 4;
+
+pub const MEM1_START: u32 = 0x80000000;
+pub const MEM1_SIZE: u32 = 0x01800000;
+
+/// Access to the main memory of a running game, by the addresses the game itself uses
+pub trait GameMemory
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>;
+    fn write(&mut self, addr: u32, bytes: &[u8]) -> Result<(), String>;
+
+    fn read_u32(&mut self, addr: u32) -> Result<u32, String>
+    {
+        let mut buf = [0; 4];
+        self.read(addr, &mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn write_u32(&mut self, addr: u32, val: u32) -> Result<(), String>
+    {
+        self.write(addr, &val.to_be_bytes())
+    }
+}
+
+fn mem1_offset(addr: u32, len: usize) -> Result<u64, String>
+{
+    let offset = addr.wrapping_sub(MEM1_START);
+    if offset > MEM1_SIZE || len as u32 > MEM1_SIZE - offset {
+        Err(format!("Address 0x{:08x} is outside of the game's memory", addr))?
+    }
+    Ok(offset as u64)
+}
+
+/// A file holding a copy of the game's main memory, e.g. a memory dump or one written by a
+/// program standing in for the game
+pub struct FileMemory
+{
+    file: File,
+}
+
+impl FileMemory
+{
+    pub fn open(path: &str) -> Result<Self, String>
+    {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        Ok(FileMemory { file })
+    }
+}
+
+impl GameMemory for FileMemory
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+    {
+        let offset = mem1_offset(addr, buf.len())?;
+        self.file.seek(SeekFrom::Start(offset))
+            .and_then(|_| self.file.read_exact(buf))
+            .map_err(|e| format!("Failed to read 0x{:08x}: {}", addr, e))
+    }
+
+    fn write(&mut self, addr: u32, bytes: &[u8]) -> Result<(), String>
+    {
+        let offset = mem1_offset(addr, bytes.len())?;
+        self.file.seek(SeekFrom::Start(offset))
+            .and_then(|_| self.file.write_all(bytes))
+            .map_err(|e| format!("Failed to write 0x{:08x}: {}", addr, e))
+    }
+}
+
+/// The memory of a game running in Dolphin, through the emulator process's memory. This needs
+/// the same permissions as attaching a debugger to it.
+#[cfg(target_os = "linux")]
+pub struct DolphinMemory
+{
+    mem: File,
+    mem1_addr: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl DolphinMemory
+{
+    pub fn attach() -> Result<Self, String>
+    {
+        let proc_dir = std::fs::read_dir("/proc")
+            .map_err(|e| format!("Failed to list processes: {}", e))?;
+        for entry in proc_dir.filter_map(|entry| entry.ok()) {
+            let pid = match entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) {
+                Some(pid) => pid,
+                None => continue,
+            };
+            let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+            if !comm.trim().starts_with("dolphin-emu") {
+                continue;
+            }
+            if let Some(mem1_addr) = Self::find_mem1(pid)? {
+                let mem = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(format!("/proc/{}/mem", pid))
+                    .map_err(|e| format!("Failed to open Dolphin's memory: {}", e))?;
+                return Ok(DolphinMemory { mem, mem1_addr });
+            }
+        }
+        Err("Couldn't find a running Dolphin with a game started".to_string())
+    }
+
+    /// Dolphin keeps the emulated memory in shared memory, and the first mapping of it is MEM1
+    fn find_mem1(pid: u32) -> Result<Option<u64>, String>
+    {
+        let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid))
+            .map_err(|e| format!("Failed to read Dolphin's memory map: {}", e))?;
+        for line in maps.lines() {
+            // e.g. "7f1c00000000-7f1c02000000 rw-s 00000000 00:19 12 /dev/shm/dolphin-emu.42"
+            let fields: Vec<_> = line.split_whitespace().collect();
+            if fields.len() < 6 || fields[2] != "00000000" {
+                continue;
+            }
+            if !fields[5].contains("dolphin-emu") && !fields[5].contains("dolphinmem") {
+                continue;
+            }
+            let mut range = fields[0].split('-')
+                .map(|s| u64::from_str_radix(s, 16).unwrap_or(0));
+            let (start, end) = (range.next().unwrap_or(0), range.next().unwrap_or(0));
+            if end.saturating_sub(start) >= MEM1_SIZE as u64 {
+                return Ok(Some(start));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl GameMemory for DolphinMemory
+{
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+    {
+        let offset = self.mem1_addr + mem1_offset(addr, buf.len())?;
+        self.mem.seek(SeekFrom::Start(offset))
+            .and_then(|_| self.mem.read_exact(buf))
+            .map_err(|e| format!("Failed to read 0x{:08x}: {}", addr, e))
+    }
+
+    fn write(&mut self, addr: u32, bytes: &[u8]) -> Result<(), String>
+    {
+        let offset = self.mem1_addr + mem1_offset(addr, bytes.len())?;
+        self.mem.seek(SeekFrom::Start(offset))
+            .and_then(|_| self.mem.write_all(bytes))
+            .map_err(|e| format!("Failed to write 0x{:08x}: {}", addr, e))
+    }
+}
+
+/// The items given to a player when they receive a pickup from another player
+pub fn received_item(pickup: PickupType) -> MultiworldReceivedItem
+{
+    let pickup_data = pickup.pickup_data();
+    MultiworldReceivedItem {
+        item_type: pickup_data.kind,
+        amount: pickup_data.curr_increase,
+        capacity: pickup_data.max_increase,
+    }
+}
+
+/// The mailbox of a running game
+pub struct Mailbox<M>
+{
+    memory: M,
+    addr: u32,
+}
+
+impl<M: GameMemory> Mailbox<M>
+{
+    /// Searches the game's memory for a mailbox the game is using
+    pub fn find(mut memory: M) -> Result<Result<Self, M>, String>
+    {
+        const CHUNK_SIZE: u32 = 0x10000;
+        let mut chunk = vec![0; (CHUNK_SIZE + MAILBOX_SIZE) as usize];
+        let mut chunk_start = MEM1_START;
+        while chunk_start < MEM1_START + MEM1_SIZE {
+            let len = (MEM1_START + MEM1_SIZE - chunk_start).min(CHUNK_SIZE + MAILBOX_SIZE);
+            memory.read(chunk_start, &mut chunk[..len as usize])?;
+            // The REL's data is 4-byte aligned
+            for i in (0..(len.saturating_sub(MAILBOX_SIZE) + 1).min(CHUNK_SIZE)).step_by(4) {
+                let i = i as usize;
+                if chunk[i..i + 8] != MULTIWORLD_MAILBOX_MAGIC {
+                    continue;
+                }
+                let active = &chunk[i + ACTIVE_OFFSET as usize..i + ACTIVE_OFFSET as usize + 4];
+                if active != [0; 4] {
+                    return Ok(Ok(Mailbox { memory, addr: chunk_start + i as u32 }));
+                }
+            }
+            chunk_start += CHUNK_SIZE;
+        }
+        Ok(Err(memory))
+    }
+
+    pub fn addr(&self) -> u32
+    {
+        self.addr
+    }
+
+    /// Whether the mailbox is still there, i.e. the game hasn't been reset or closed
+    pub fn is_valid(&mut self) -> bool
+    {
+        let mut magic = [0; 8];
+        self.memory.read(self.addr, &mut magic).is_ok() && magic == MULTIWORLD_MAILBOX_MAGIC
+    }
+
+    /// The locations of the pickups the player has collected for other players since the last
+    /// call
+    pub fn take_sent(&mut self) -> Result<Vec<usize>, String>
+    {
+        let sent_count = self.memory.read_u32(self.addr + SENT_COUNT_OFFSET)?;
+        let mut sent_acked = self.memory.read_u32(self.addr + SENT_ACKED_OFFSET)?;
+        if sent_acked > sent_count {
+            // The REL has been reloaded at the same address
+            sent_acked = 0;
+        }
+        let mut locations = vec![];
+        for i in sent_acked..sent_count {
+            let slot = i % MULTIWORLD_MAILBOX_SENT_SLOTS as u32;
+            let location = self.memory.read_u32(self.addr + SENT_OFFSET + slot * 4)?;
+            locations.push(location as usize);
+        }
+        self.memory.write_u32(self.addr + SENT_ACKED_OFFSET, sent_count)?;
+        Ok(locations)
+    }
+
+    /// Offers the game every pickup the player has received so far, in the order they were
+    /// received. The game only takes the ones the player doesn't have yet.
+    pub fn offer_received(&mut self, received: &[PickupType]) -> Result<(), String>
+    {
+        let given = self.memory.read_u32(self.addr + RECEIVED_GIVEN_OFFSET)? as usize;
+        let end = received.len().min(given + MULTIWORLD_MAILBOX_RECEIVED_SLOTS);
+        for (i, &pickup) in received.iter().enumerate().take(end).skip(given) {
+            let item = received_item(pickup);
+            let slot = (i % MULTIWORLD_MAILBOX_RECEIVED_SLOTS) as u32;
+            let item_addr = self.addr + RECEIVED_OFFSET + slot * RECEIVED_ITEM_SIZE;
+            self.memory.write_u32(item_addr, item.item_type)?;
+            self.memory.write_u32(item_addr + 4, item.amount)?;
+            self.memory.write_u32(item_addr + 8, item.capacity)?;
+        }
+        // Written last, so the game never sees a count that includes unwritten items
+        self.memory.write_u32(self.addr + RECEIVED_COUNT_OFFSET, end as u32)
+    }
+
+    pub fn into_memory(self) -> M
+    {
+        self.memory
+    }
+}
+
+/// Does what the REL does with the mailbox, for trying out the bridge without the game
+pub struct StandInGame<M>
+{
+    memory: M,
+    addr: u32,
+    sent_count: u32,
+    send_queue: MultiworldSendQueue,
+    // The items the player has been given
+    pub given: Vec<MultiworldReceivedItem>,
+}
+
+impl<M: GameMemory> StandInGame<M>
+{
+    pub const MAILBOX_ADDR: u32 = 0x80F00000;
+
+    pub fn new(mut memory: M) -> Result<Self, String>
+    {
+        let mut bytes = vec![0; MAILBOX_SIZE as usize];
+        bytes[..8].copy_from_slice(&MULTIWORLD_MAILBOX_MAGIC);
+        bytes[ACTIVE_OFFSET as usize..ACTIVE_OFFSET as usize + 4]
+            .copy_from_slice(&1u32.to_be_bytes());
+        memory.write(Self::MAILBOX_ADDR, &bytes)?;
+        Ok(StandInGame {
+            memory,
+            addr: Self::MAILBOX_ADDR,
+            sent_count: 0,
+            send_queue: MultiworldSendQueue::new(),
+            given: vec![],
+        })
+    }
+
+    /// Collects the pickup at a location that belongs to another player
+    pub fn collect_remote_pickup(&mut self, location_idx: usize) -> Result<(), String>
+    {
+        self.send_queue.push(location_idx as u32);
+        self.send_queued_pickups()
+    }
+
+    fn send_queued_pickups(&mut self) -> Result<(), String>
+    {
+        let sent_acked = self.memory.read_u32(self.addr + SENT_ACKED_OFFSET)?;
+        let mut sent = vec![];
+        self.sent_count = self.send_queue.flush(self.sent_count, sent_acked, |slot, location| {
+            sent.push((slot as u32, location));
+        });
+        for (slot, location) in sent {
+            self.memory.write_u32(self.addr + SENT_OFFSET + slot * 4, location)?;
+        }
+        self.memory.write_u32(self.addr + SENT_COUNT_OFFSET, self.sent_count)
+    }
+
+    /// Sends the pickups that didn't fit in the mailbox before and gives the player the items
+    /// the host has put in it
+    pub fn update(&mut self) -> Result<(), String>
+    {
+        self.send_queued_pickups()?;
+        let received_count = self.memory.read_u32(self.addr + RECEIVED_COUNT_OFFSET)?;
+        while (self.given.len() as u32) < received_count {
+            let slot = self.given.len() as u32 % MULTIWORLD_MAILBOX_RECEIVED_SLOTS as u32;
+            let item_addr = self.addr + RECEIVED_OFFSET + slot * RECEIVED_ITEM_SIZE;
+            self.given.push(MultiworldReceivedItem {
+                item_type: self.memory.read_u32(item_addr)?,
+                amount: self.memory.read_u32(item_addr + 4)?,
+                capacity: self.memory.read_u32(item_addr + 8)?,
+            });
+        }
+        self.memory.write_u32(self.addr + RECEIVED_GIVEN_OFFSET, self.given.len() as u32)
+    }
+
+    pub fn memory(&mut self) -> &mut M
+    {
+        &mut self.memory
+    }
+}
+
+/// A line of JSON sent between a bridge and the relay
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RelayMessage
+{
+    // The first message from a bridge. The relay answers with every pickup already sent to
+    // the player.
+    #[serde(rename_all = "camelCase")]
+    Hello { player: u32 },
+    #[serde(rename_all = "camelCase")]
+    Pickup { from_player: u32, location: usize, to_player: u32, pickup: PickupType },
+}
+
+fn send_message(stream: &mut TcpStream, message: &RelayMessage) -> Result<(), String>
+{
+    let mut line = serde_json::to_string(message).unwrap();
+    line.push('\n');
+    stream.write_all(line.as_bytes()).map_err(|e| format!("Failed to send to the relay: {}", e))
+}
+
+fn read_messages(stream: TcpStream) -> impl Iterator<Item = Result<RelayMessage, String>>
+{
+    BufReader::new(stream).lines().map(|line| {
+        let line = line.map_err(|e| format!("Failed to read from the relay: {}", e))?;
+        serde_json::from_str(&line).map_err(|e| format!("Invalid relay message: {}", e))
+    })
+}
+
+/// Something the relay or a bridge has done, for showing to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultiworldEvent
+{
+    PlayerConnected(u32),
+    PlayerDisconnected(u32),
+    PickupSent { from_player: u32, location: usize, to_player: u32, pickup: PickupType },
+    PickupReceived { from_player: u32, pickup: PickupType },
+    MailboxFound(u32),
+    // Something went wrong, but the relay or bridge carries on
+    Error(String),
+}
+
+#[derive(Default)]
+struct RelayState
+{
+    bridges: HashMap<u32, TcpStream>,
+    // The pickups sent to each player, in the order they were sent
+    sent_pickups: HashMap<u32, Vec<RelayMessage>>,
+}
+
+/// Passes pickups between the players' bridges, keeping them so that bridges that connect later
+/// still get every pickup sent to their player
+pub fn run_relay<F>(addr: &str, on_event: F) -> Result<(), String>
+    where F: Fn(MultiworldEvent) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(addr)
+        .map_err(|e| format!("Failed to listen on {}: {}", addr, e))?;
+    let state = Arc::new(Mutex::new(RelayState::default()));
+    let on_event = Arc::new(on_event);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                on_event(MultiworldEvent::Error(format!("Failed to accept a bridge: {}", e)));
+                continue
+            },
+        };
+        let state = state.clone();
+        let on_event = on_event.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_bridge(stream, &state, &*on_event) {
+                on_event(MultiworldEvent::Error(e));
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_bridge(
+    stream: TcpStream,
+    state: &Mutex<RelayState>,
+    on_event: &dyn Fn(MultiworldEvent),
+) -> Result<(), String>
+{
+    let reader = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut player = None;
+    for message in read_messages(reader) {
+        let message = message?;
+        let mut state = state.lock().unwrap();
+        match &message {
+            RelayMessage::Hello { player: p } => {
+                let mut stream = stream.try_clone().map_err(|e| e.to_string())?;
+                for pickup in state.sent_pickups.get(p).into_iter().flatten() {
+                    send_message(&mut stream, pickup)?;
+                }
+                state.bridges.insert(*p, stream);
+                player = Some(*p);
+                on_event(MultiworldEvent::PlayerConnected(*p));
+            },
+            RelayMessage::Pickup { from_player, location, to_player, pickup } => {
+                let sent_pickups = state.sent_pickups.entry(*to_player).or_default();
+                if sent_pickups.contains(&message) {
+                    // Collected again after the game was reloaded
+                    continue;
+                }
+                sent_pickups.push(message.clone());
+                on_event(MultiworldEvent::PickupSent {
+                    from_player: *from_player,
+                    location: *location,
+                    to_player: *to_player,
+                    pickup: *pickup,
+                });
+                if let Some(stream) = state.bridges.get_mut(to_player) {
+                    if send_message(stream, &message).is_err() {
+                        state.bridges.remove(to_player);
+                    }
+                }
+            },
+        }
+    }
+    if let Some(player) = player {
+        state.lock().unwrap().bridges.remove(&player);
+        on_event(MultiworldEvent::PlayerDisconnected(player));
+    }
+    Ok(())
+}
+
+/// Reads the multiworld settings out of a patcher config file
+pub fn multiworld_config_from_json(json: &str) -> Result<MultiworldConfig, String>
+{
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct GameConfig
+    {
+        multiworld: Option<MultiworldConfig>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Config
+    {
+        game_config: GameConfig,
+    }
+
+    let config: Config = serde_json::from_str(json)
+        .map_err(|e| format!("JSON parse failed: {}", e))?;
+    config.game_config.multiworld
+        .ok_or_else(|| "The config doesn't have any multiworld settings".to_string())
+}
+
+/// Relays one player's pickups between their game and the relay until the connection to the
+/// relay is lost. `connect` is called whenever the game's mailbox needs to be found (again).
+pub fn run_bridge<M, F, E>(
+    config: &MultiworldConfig,
+    relay_addr: &str,
+    mut connect: F,
+    mut on_event: E,
+) -> Result<(), String>
+    where M: GameMemory,
+          F: FnMut() -> Result<M, String>,
+          E: FnMut(MultiworldEvent),
+{
+    let mut relay = TcpStream::connect(relay_addr)
+        .map_err(|e| format!("Failed to connect to the relay at {}: {}", relay_addr, e))?;
+    send_message(&mut relay, &RelayMessage::Hello { player: config.player })?;
+
+    let (received_tx, received_rx) = mpsc::channel();
+    let reader = relay.try_clone().map_err(|e| e.to_string())?;
+    thread::spawn(move || {
+        for message in read_messages(reader) {
+            if received_tx.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut received = vec![];
+    let mut received_from = HashSet::new();
+    let mut mailbox = None;
+    loop {
+        loop {
+            match received_rx.try_recv() {
+                Ok(Ok(RelayMessage::Pickup { from_player, location, pickup, .. })) => {
+                    if received_from.insert((from_player, location)) {
+                        on_event(MultiworldEvent::PickupReceived { from_player, pickup });
+                        received.push(pickup);
+                    }
+                },
+                Ok(Ok(RelayMessage::Hello { .. })) => (),
+                Ok(Err(e)) => Err(e)?,
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => Err("Lost the connection to the relay")?,
+            }
+        }
+
+        if mailbox.is_none() {
+            match connect().and_then(Mailbox::find) {
+                Ok(Ok(found)) => {
+                    on_event(MultiworldEvent::MailboxFound(found.addr()));
+                    mailbox = Some(found);
+                },
+                // Most likely the game hasn't been started yet
+                Ok(Err(_)) => thread::sleep(Duration::from_secs(1)),
+                Err(e) => {
+                    on_event(MultiworldEvent::Error(e));
+                    thread::sleep(Duration::from_secs(1));
+                },
+            }
+        }
+
+        if let Some(game) = &mut mailbox {
+            if let Err(e) = update_game(config, &mut relay, game, &received, &mut on_event) {
+                on_event(MultiworldEvent::Error(e));
+                mailbox = None;
+            }
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn update_game<M: GameMemory>(
+    config: &MultiworldConfig,
+    relay: &mut TcpStream,
+    mailbox: &mut Mailbox<M>,
+    received: &[PickupType],
+    on_event: &mut dyn FnMut(MultiworldEvent),
+) -> Result<(), String>
+{
+    if !mailbox.is_valid() {
+        Err("The game's mailbox is gone")?
+    }
+    for location in mailbox.take_sent()? {
+        let remote_pickup = match config.remote_pickups.get(&location) {
+            Some(remote_pickup) => remote_pickup,
+            None => {
+                on_event(MultiworldEvent::Error(format!(
+                    "Location {} doesn't have another player's item",
+                    location,
+                )));
+                continue
+            },
+        };
+        send_message(relay, &RelayMessage::Pickup {
+            from_player: config.player,
+            location,
+            to_player: remote_pickup.player,
+            pickup: remote_pickup.pickup,
+        })?;
+    }
+    mailbox.offer_received(received)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use std::mem;
+
+    struct TestMemory(Vec<u8>);
+
+    impl GameMemory for TestMemory
+    {
+        fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), String>
+        {
+            let offset = mem1_offset(addr, buf.len())? as usize;
+            buf.copy_from_slice(&self.0[offset..offset + buf.len()]);
+            Ok(())
+        }
+
+        fn write(&mut self, addr: u32, bytes: &[u8]) -> Result<(), String>
+        {
+            let offset = mem1_offset(addr, bytes.len())? as usize;
+            self.0[offset..offset + bytes.len()].copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    fn empty_memory() -> TestMemory
+    {
+        TestMemory(vec![0; MEM1_SIZE as usize])
+    }
+
+    #[test]
+    fn mailbox_offsets_match_its_layout()
+    {
+        assert_eq!(mem::size_of::<MultiworldMailbox>(), MAILBOX_SIZE as usize);
+        assert_eq!(mem::size_of::<MultiworldReceivedItem>(), RECEIVED_ITEM_SIZE as usize);
+    }
+
+    #[test]
+    fn mailbox_passes_pickups_both_ways()
+    {
+        let mut game = StandInGame::new(empty_memory()).unwrap();
+        game.collect_remote_pickup(12).unwrap();
+        game.collect_remote_pickup(40).unwrap();
+
+        let memory = mem::replace(game.memory(), TestMemory(vec![]));
+        let mut mailbox = Mailbox::find(memory).unwrap().ok().unwrap();
+        assert_eq!(mailbox.addr(), StandInGame::<TestMemory>::MAILBOX_ADDR);
+        assert_eq!(mailbox.take_sent().unwrap(), vec![12, 40]);
+        assert_eq!(mailbox.take_sent().unwrap(), Vec::<usize>::new());
+
+        let received = vec![PickupType::Missile; MULTIWORLD_MAILBOX_RECEIVED_SLOTS + 4];
+        mailbox.offer_received(&received).unwrap();
+        *game.memory() = mailbox.into_memory();
+        game.update().unwrap();
+        assert_eq!(game.given.len(), MULTIWORLD_MAILBOX_RECEIVED_SLOTS);
+
+        let memory = mem::replace(game.memory(), TestMemory(vec![]));
+        let mut mailbox = Mailbox::find(memory).unwrap().ok().unwrap();
+        mailbox.offer_received(&received).unwrap();
+        *game.memory() = mailbox.into_memory();
+        game.update().unwrap();
+        assert_eq!(game.given.len(), received.len());
+        let missile = received_item(PickupType::Missile);
+        assert!(game.given.iter().all(|item| {
+            (item.item_type, item.amount, item.capacity)
+                == (missile.item_type, missile.amount, missile.capacity)
+        }));
+    }
+
+    #[test]
+    fn pickups_wait_for_room_in_a_full_mailbox()
+    {
+        let mut game = StandInGame::new(empty_memory()).unwrap();
+        let locations: Vec<usize> = (0..MULTIWORLD_MAILBOX_SENT_SLOTS + 4).collect();
+        for &location in &locations {
+            game.collect_remote_pickup(location).unwrap();
+        }
+        // Collected again after reloading a save, while it's still waiting
+        game.collect_remote_pickup(MULTIWORLD_MAILBOX_SENT_SLOTS + 1).unwrap();
+
+        let memory = mem::replace(game.memory(), TestMemory(vec![]));
+        let mut mailbox = Mailbox::find(memory).unwrap().ok().unwrap();
+        assert_eq!(mailbox.take_sent().unwrap(), locations[..MULTIWORLD_MAILBOX_SENT_SLOTS]);
+
+        *game.memory() = mailbox.into_memory();
+        game.update().unwrap();
+        let memory = mem::replace(game.memory(), TestMemory(vec![]));
+        let mut mailbox = Mailbox::find(memory).unwrap().ok().unwrap();
+        assert_eq!(mailbox.take_sent().unwrap(), locations[MULTIWORLD_MAILBOX_SENT_SLOTS..]);
+        assert_eq!(mailbox.take_sent().unwrap(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn inactive_mailboxes_are_ignored()
+    {
+        let mut memory = empty_memory();
+        GameMemory::write(&mut memory, 0x80004000, &MULTIWORLD_MAILBOX_MAGIC).unwrap();
+        assert!(Mailbox::find(memory).unwrap().is_err());
+    }
+}
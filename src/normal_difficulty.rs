@@ -0,0 +1,498 @@
+use crate::{
+    logic::{Difficulty, LocationRequirements, Requirement},
+    patch_config::TempleBoss,
+    pickup_meta::PickupType,
+};
+
+macro_rules! all {
+    ($($req:expr),* $(,)?) => {
+        Requirement::all(vec![$(Requirement::from(&$req)),*])
+    };
+}
+
+macro_rules! any {
+    ($($req:expr),* $(,)?) => {
+        Requirement::any(vec![$(Requirement::from(&$req)),*])
+    };
+}
+
+/// The requirements for the "normal" difficulty, which expects no sequence breaks or tricks.
+/// Kept in sync with `web/normal_difficulty.js`.
+pub fn difficulty() -> Difficulty
+{
+    let missile = Requirement::from(PickupType::Missile);
+    let thermal_visor = Requirement::from(PickupType::ThermalVisor);
+    let xray_visor = Requirement::from(PickupType::XRayVisor);
+    let gravity_suit = Requirement::from(PickupType::GravitySuit);
+    let phazon_suit = Requirement::from(PickupType::PhazonSuit);
+    let morph_ball = Requirement::from(PickupType::MorphBall);
+    let boost_ball = all!(morph_ball, PickupType::BoostBall);
+    let spider_ball = all!(morph_ball, PickupType::SpiderBall);
+    let morph_ball_bomb = all!(morph_ball, PickupType::MorphBallBomb);
+    let power_bomb = all!(morph_ball, PickupType::PowerBomb);
+    let charge_beam = Requirement::from(PickupType::ChargeBeam);
+    let space_jump_boots = Requirement::from(PickupType::SpaceJumpBoots);
+    let grapple_beam = Requirement::from(PickupType::GrappleBeam);
+    let super_missile = all!(charge_beam, missile, PickupType::SuperMissile);
+    let wave_beam = Requirement::from(PickupType::WaveBeam);
+    let ice_beam = Requirement::from(PickupType::IceBeam);
+    let plasma_beam = Requirement::from(PickupType::PlasmaBeam);
+    let any_suit = any!(PickupType::VariaSuit, PickupType::GravitySuit, PickupType::PhazonSuit);
+    let any_power_bombs = any!(PickupType::PowerBomb, PickupType::PowerBombExpansion);
+    let mbb_or_pb = any!(PickupType::MorphBallBomb, PickupType::PowerBomb);
+    let phendrana_reqs = all!(missile, morph_ball_bomb, any_suit);
+    let backwards_phendrana_reqs = all!(missile, any_suit, spider_ball, space_jump_boots,
+        wave_beam);
+    let mines_from_tallon_reqs = all!(missile, morph_ball_bomb, space_jump_boots, gravity_suit,
+        thermal_visor, wave_beam, ice_beam);
+    let mines_from_magmoor_reqs = all!(missile, spider_ball, space_jump_boots, any_suit, wave_beam,
+        ice_beam, any_power_bombs);
+
+    Difficulty {
+        optional_items: vec![
+            (PickupType::Missile, 49),
+            (PickupType::EnergyTank, 14),
+            (PickupType::VariaSuit, 1),
+            (PickupType::PowerBomb, 1),
+            (PickupType::PowerBombExpansion, 3),
+            (PickupType::Wavebuster, 1),
+            (PickupType::IceSpreader, 1),
+            (PickupType::Flamethrower, 1),
+        ],
+        locations: vec![
+            // 0: Chozo - - - Main Plaza (Half-Pipe) - - - - - - - - Missile Expansion 1
+            LocationRequirements::new(all!(boost_ball)),
+            // 1: Chozo - - - Main Plaza (Grapple Ledge) - - - - - - Missile Expansion 2
+            LocationRequirements::new(all!(missile, grapple_beam, any_suit, boost_ball,
+                morph_ball_bomb, wave_beam)),
+            // 2: Chozo - - - Main Plaza (Tree)  - - - - - - - - - - Missile Expansion 3
+            LocationRequirements::new(all!(super_missile)),
+            // 3: Chozo - - - Main Plaza (Locked Door) - - - - - - - Energy Tank 1
+            LocationRequirements::new(all!(missile, morph_ball_bomb)),
+            // 4: Chozo - - - Ruined Fountain  - - - - - - - - - - - Missile Expansion 4
+            LocationRequirements::new(all!(missile, spider_ball)),
+            // 5: Chozo - - - Ruined Shrine ("Beetle Battle")  - - - Morph Ball
+            LocationRequirements {
+                required: all!(missile),
+                escape: any!(space_jump_boots, morph_ball),
+            },
+            // 6: Chozo - - - Ruined Shrine (Half-Pipe)  - - - - - - Missile Expansion 5
+            LocationRequirements::new(all!(missile, boost_ball)),
+            // 7: Chozo - - - Ruined Shrine (Lower Tunnel) - - - - - Missile Expansion 6
+            LocationRequirements::new(all!(missile, mbb_or_pb)),
+            // 8: Chozo - - - Vault  - - - - - - - - - - - - - - - - Missile Expansion 7
+            LocationRequirements::new(all!(missile, morph_ball_bomb)),
+            // 9: Chozo - - - Training Chamber - - - - - - - - - - - Energy Tank 2
+            LocationRequirements::new(all!(missile, any_suit, grapple_beam, wave_beam, boost_ball,
+                spider_ball)),
+            //10: Chozo - - - Ruined Nursery - - - - - - - - - - - - Missile Expansion 8
+            LocationRequirements::new(all!(morph_ball_bomb)),
+            //11: Chozo - - - Training Chamber Access  - - - - - - - Missile Expansion 9
+            LocationRequirements::new(all!(missile, any_suit, grapple_beam, wave_beam, morph_ball)),
+            //12: Chozo - - - Magma Pool - - - - - - - - - - - - - - Power Bomb Expansion 1
+            LocationRequirements::new(all!(missile, any_suit, grapple_beam, any_power_bombs)),
+            //13: Chozo - - - Tower of Light - - - - - - - - - - - - Wavebuster
+            // This actually only requires 75 missiles, but 80 is less hard
+            LocationRequirements::new(all!(Requirement::n_of(PickupType::Missile, 80 / 5),
+                boost_ball, spider_ball, wave_beam, space_jump_boots)),
+            //14: Chozo - - - Tower Chamber  - - - - - - - - - - - - Artifact of Lifegiver
+            // XXX Requires SJB without GS, but w/ GS doesn't
+            LocationRequirements::new(all!(missile, boost_ball, spider_ball, wave_beam,
+                gravity_suit, space_jump_boots)),
+            //15: Chozo - - - Ruined Gallery (Missile Wall)  - - - - Missile Expansion 10
+            LocationRequirements::new(all!(missile)),
+            //16: Chozo - - - Ruined Gallery (Tunnel)  - - - - - - - Missile Expansion 11
+            LocationRequirements::new(all!(morph_ball_bomb)),
+            //17: Chozo - - - Transport Access North - - - - - - - - Energy Tank 3
+            LocationRequirements::new(all!(missile)),
+            //18: Chozo - - - Gathering Hall - - - - - - - - - - - - Missile Expansion 12
+            LocationRequirements::new(all!(missile, mbb_or_pb, space_jump_boots)),
+            //19: Chozo - - - Hive Totem - - - - - - - - - - - - - - Missile Launcher
+            LocationRequirements::new(all!()),
+            //20: Chozo - - - Sunchamber (Flaahgra)  - - - - - - - - Varia Suit
+            LocationRequirements::new(all!(missile, morph_ball_bomb)),
+            //21: Chozo - - - Sunchamber (Ghosts)  - - - - - - - - - Artifact of Wild
+            // XXX MBB is very questionable here...
+            //     Its not strickly needed, but it is needed to fight flaahgra
+            LocationRequirements::new(all!(morph_ball_bomb, spider_ball, super_missile)),
+            //22: Chozo - - - Watery Hall Access - - - - - - - - - - Missile Expansion 13
+            LocationRequirements::new(all!(missile, morph_ball)),
+            //23: Chozo - - - Watery Hall (Scan Puzzle)  - - - - - - Charge Beam
+            LocationRequirements::new(all!(missile, morph_ball)),
+            //24: Chozo - - - Watery Hall (Underwater) - - - - - - - Missile Expansion 14
+            // NOTE: Does this actually require Space Jump? (Yes, without a dbj + unmorph)
+            LocationRequirements::new(all!(missile, morph_ball_bomb, gravity_suit,
+                space_jump_boots)),
+            //25: Chozo - - - Dynamo (Lower) - - - - - - - - - - - - Missile Expansion 15
+            LocationRequirements::new(all!(missile, mbb_or_pb)),
+            //26: Chozo - - - Dynamo (Spider Track)  - - - - - - - - Missile Expansion 16
+            LocationRequirements::new(all!(missile, mbb_or_pb, spider_ball)),
+            //27: Chozo - - - Burn Dome (Missile)  - - - - - - - - - Missile Expansion 17
+            LocationRequirements {
+                required: all!(missile, mbb_or_pb),
+                escape: all!(morph_ball_bomb),
+            },
+            //28: Chozo - - - Burn Dome (I. Drone) - - - - - - - - - Morph Ball Bomb
+            LocationRequirements {
+                required: all!(missile, morph_ball),
+                escape: all!(morph_ball_bomb),
+            },
+            //29: Chozo - - - Furnace (Spider Tracks)  - - - - - - - Missile Expansion 18
+            LocationRequirements::new(all!(missile, morph_ball_bomb, any_power_bombs, boost_ball,
+                spider_ball)),
+            //30: Chozo - - - Furnace (Inside Furnace) - - - - - - - Energy Tank 4
+            LocationRequirements::new(all!(missile, morph_ball_bomb)),
+            //31: Chozo - - - Hall of the Elders - - - - - - - - - - Energy Tank 5
+            LocationRequirements::new(all!(missile, morph_ball_bomb, spider_ball, wave_beam,
+                boost_ball, ice_beam, space_jump_boots)),
+            //32: Chozo - - - Crossway - - - - - - - - - - - - - - - Missile Expansion 19
+            LocationRequirements::new(all!(missile, morph_ball_bomb, spider_ball, wave_beam,
+                boost_ball)),
+            //33: Chozo - - - Elder Chamber  - - - - - - - - - - - - Artifact of World
+            LocationRequirements {
+                required: all!(missile, morph_ball_bomb, spider_ball, wave_beam, boost_ball,
+                    plasma_beam, space_jump_boots),
+                escape: all!(ice_beam),
+            },
+            //34: Chozo - - - Antechamber  - - - - - - - - - - - - - Ice Beam
+            LocationRequirements {
+                required: all!(missile, morph_ball_bomb, spider_ball, wave_beam, boost_ball,
+                    space_jump_boots),
+                escape: all!(ice_beam),
+            },
+            //35: Phendrana - Phendrana Shorelines (Behind Ice)  - - Missile Expansion 20
+            LocationRequirements::new(all!(phendrana_reqs, plasma_beam)),
+            //36: Phendrana - Phendrana Shorelines (Spider Track)  - Missile Expansion 21
+            LocationRequirements::new(all!(phendrana_reqs, space_jump_boots, spider_ball,
+                super_missile)),
+            //37: Phendrana - Chozo Ice Temple - - - - - - - - - - - Artifact of Sun
+            LocationRequirements::new(all!(phendrana_reqs, space_jump_boots, plasma_beam)),
+            //38: Phendrana - Ice Ruins West - - - - - - - - - - - - Power Bomb Expansion 2
+            LocationRequirements::new(all!(phendrana_reqs, plasma_beam, space_jump_boots)),
+            //39: Phendrana - Ice Ruins East (Behind Ice)  - - - - - Missile Expansion 22
+            LocationRequirements::new(all!(phendrana_reqs, plasma_beam)),
+            //40: Phendrana - Ice Ruins East (Spider Track)  - - - - Missile Expansion 23
+            LocationRequirements::new(all!(phendrana_reqs, spider_ball)),
+            //41: Phendrana - Chapel of the Elders - - - - - - - - - Wave Beam
+            LocationRequirements {
+                required: all!(phendrana_reqs, space_jump_boots),
+                escape: all!(wave_beam),
+            },
+            //42: Phendrana - Ruined Courtyard - - - - - - - - - - - Energy Tank 6
+            LocationRequirements::new(all!(phendrana_reqs, boost_ball, space_jump_boots,
+                wave_beam)),
+            //43: Phendrana - Phendrana Canyon - - - - - - - - - - - Boost Ball
+            // XXX Strictly speaking, you can escape without either of
+            // these, but it requires jumping on destructable boxes, and
+            // thus makes this room a potential hazard
+            LocationRequirements {
+                required: all!(phendrana_reqs),
+                escape: any!(boost_ball, space_jump_boots),
+            },
+            //44: Phendrana - Quarantine Cave  - - - - - - - - - - - Spider Ball
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, thermal_visor),
+                    // XXX Thermal visor is only required for fighting Thardus. It could be removed
+                    all!(backwards_phendrana_reqs, thermal_visor),
+                ),
+                escape: all!(spider_ball),
+            },
+            //45: Phendrana - Research Lab Hydra - - - - - - - - - - Missile Expansion 24
+            LocationRequirements::new(any!(
+                all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, thermal_visor,
+                    super_missile),
+                // XXX Research Core Thermal Visor
+                all!(backwards_phendrana_reqs, thermal_visor, ice_beam, super_missile),
+            )),
+            //46: Phendrana - Quarantine Monitor - - - - - - - - - - Missile Expansion 25
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, thermal_visor,
+                        grapple_beam),
+                    // XXX Thermal visor is only required for fighting Thardus. It could be removed?
+                    all!(backwards_phendrana_reqs, thermal_visor, grapple_beam),
+                ),
+                escape: all!(spider_ball),
+            },
+            //47: Phendrana - Observatory  - - - - - - - - - - - - - Super Missile
+            LocationRequirements::new(all!(phendrana_reqs, boost_ball, space_jump_boots,
+                wave_beam)),
+            //48: Phendrana - Transport Access - - - - - - - - - - - Energy Tank 7
+            LocationRequirements::new(any!(
+                all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, thermal_visor,
+                    spider_ball, plasma_beam),
+                // XXX Research Core Thermal Visor
+                all!(backwards_phendrana_reqs, thermal_visor, ice_beam, plasma_beam),
+            )),
+            //49: Phendrana - Control Tower  - - - - - - - - - - - - Artifact of Elder
+            LocationRequirements::new(any!(
+                all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, plasma_beam),
+                // XXX Research Core Thermal Visor
+                all!(backwards_phendrana_reqs, thermal_visor, ice_beam, plasma_beam),
+            )),
+            //50: Phendrana - Research Core  - - - - - - - - - - - - Thermal Visor
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam),
+                    all!(backwards_phendrana_reqs, ice_beam),
+                ),
+                escape: any!(thermal_visor, ice_beam),
+            },
+            //51: Phendrana - Frost Cave - - - - - - - - - - - - - - Missile Expansion 26
+            // The thermal visor is required to escape either via Research
+            // Core or fight Thardus
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, ice_beam,
+                        grapple_beam),
+                    all!(backwards_phendrana_reqs, ice_beam, grapple_beam),
+                ),
+                escape: any!(thermal_visor, backwards_phendrana_reqs),
+            },
+            //52: Phendrana - Research Lab Aether (Tank) - - - - - - Energy Tank 8
+            LocationRequirements::new(any!(
+                all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam),
+                // XXX Research Core Thermal Visor
+                all!(backwards_phendrana_reqs, thermal_visor, ice_beam),
+            )),
+            //53: Phendrana - Research Lab Aether (Morph Track)  - - Missile Expansion 27
+            LocationRequirements::new(any!(
+                all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam),
+                // XXX Research Core Thermal Visor
+                all!(backwards_phendrana_reqs, thermal_visor, ice_beam),
+            )),
+            //54: Phendrana - Gravity Chamber (Underwater) - - - - - Gravity Suit
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, ice_beam),
+                    all!(backwards_phendrana_reqs, ice_beam),
+                ),
+                escape: all!(gravity_suit, any!(thermal_visor, backwards_phendrana_reqs)),
+            },
+            //55: Phendrana - Gravity Chamber (Grapple Ledge)  - - - Missile Expansion 28
+            // See 51
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, ice_beam,
+                        plasma_beam, grapple_beam, gravity_suit),
+                    all!(backwards_phendrana_reqs, ice_beam, plasma_beam, grapple_beam,
+                        gravity_suit),
+                ),
+                escape: any!(thermal_visor, backwards_phendrana_reqs),
+            },
+            //56: Phendrana - Storage Cave - - - - - - - - - - - - - Artifact of Spirit
+            // See 51
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, ice_beam,
+                        thermal_visor, plasma_beam, power_bomb, grapple_beam),
+                    all!(backwards_phendrana_reqs, ice_beam, plasma_beam, power_bomb, grapple_beam),
+                ),
+                escape: any!(thermal_visor, backwards_phendrana_reqs),
+            },
+            //57: Phendrana - Security Cave  - - - - - - - - - - - - Power Bomb Expansion 3
+            // See 51
+            LocationRequirements {
+                required: any!(
+                    all!(phendrana_reqs, boost_ball, space_jump_boots, wave_beam, ice_beam,
+                        grapple_beam),
+                    all!(backwards_phendrana_reqs, ice_beam, grapple_beam),
+                ),
+                escape: any!(thermal_visor, backwards_phendrana_reqs),
+            },
+            //58: Tallon  - - Landing Site - - - - - - - - - - - - - Missile Expansion 29
+            LocationRequirements::new(all!(morph_ball)),
+            //59: Tallon  - - Alcove - - - - - - - - - - - - - - - - Space Jump Boots
+            LocationRequirements::new(any!(all!(morph_ball_bomb, boost_ball), space_jump_boots)),
+            //60: Tallon  - - Frigate Crash Site - - - - - - - - - - Missile Expansion 30
+            LocationRequirements::new(all!(missile, morph_ball, gravity_suit, space_jump_boots)),
+            //61: Tallon  - - Overgrown Cavern - - - - - - - - - - - Missile Expansion 31
+            LocationRequirements::new(all!(missile, morph_ball_bomb, wave_beam, spider_ball,
+                boost_ball, ice_beam, space_jump_boots)),
+            //62: Tallon  - - Root Cave  - - - - - - - - - - - - - - Missile Expansion 32
+            LocationRequirements::new(all!(missile, space_jump_boots, grapple_beam, xray_visor)),
+            //63: Tallon  - - Artifact Temple  - - - - - - - - - - - Artifact of Truth
+            LocationRequirements::new(all!(missile)),
+            //64: Tallon  - - Transport Tunnel B - - - - - - - - - - Missile Expansion 33
+            LocationRequirements::new(all!(missile)),
+            //65: Tallon  - - Arbor Chamber  - - - - - - - - - - - - Missile Expansion 34
+            LocationRequirements::new(all!(missile, space_jump_boots, grapple_beam, xray_visor,
+                plasma_beam)),
+            //66: Tallon  - - Cargo Freight Lift to Deck Gamma - - - Energy Tank 9
+            LocationRequirements::new(all!(missile, morph_ball, gravity_suit, thermal_visor,
+                wave_beam, ice_beam)),
+            //67: Tallon  - - Biohazard Containment  - - - - - - - - Missile Expansion 35
+            LocationRequirements {
+                required: any!(
+                    all!(morph_ball, gravity_suit, thermal_visor, wave_beam, ice_beam,
+                        space_jump_boots, super_missile),
+                    // Backwards through Phazon Mines. Requires this to contain the thermal visor.
+                    all!(missile, morph_ball, gravity_suit, spider_ball, space_jump_boots,
+                        wave_beam, ice_beam, any_power_bombs, grapple_beam),
+                ),
+                escape: any!(thermal_visor),
+            },
+            //68: Tallon  - - Hydro Access Tunnel  - - - - - - - - - Energy Tank 10
+            LocationRequirements::new(any!(
+                all!(missile, morph_ball, gravity_suit, thermal_visor, wave_beam, ice_beam,
+                    space_jump_boots, morph_ball_bomb),
+                // Backwards through Phazon Mines
+                // TODO Is the Grapple Beam requirement necessary? A simple l-jump
+                //      by passes it easily.
+                all!(missile, morph_ball, gravity_suit, spider_ball, space_jump_boots, wave_beam,
+                    ice_beam, any_power_bombs, grapple_beam),
+            )),
+            //69: Tallon  - - Great Tree Chamber - - - - - - - - - - Missile Expansion 36
+            LocationRequirements::new(any!(
+                // From frigate
+                all!(missile, morph_ball_bomb, wave_beam, ice_beam, space_jump_boots, xray_visor,
+                    gravity_suit, thermal_visor),
+                // From backwards mines
+                all!(missile, morph_ball_bomb, wave_beam, ice_beam, space_jump_boots, xray_visor,
+                    any_suit, spider_ball, any_power_bombs, grapple_beam),
+                // From chozo
+                all!(missile, morph_ball_bomb, wave_beam, ice_beam, space_jump_boots, xray_visor,
+                    spider_ball, boost_ball),
+            )),
+            //70: Tallon  - - Life Grove Tunnel  - - - - - - - - - - Missile Expansion 37
+            LocationRequirements::new(all!(missile, gravity_suit, wave_beam, ice_beam,
+                space_jump_boots, morph_ball_bomb, spider_ball, boost_ball, any_power_bombs)),
+            //71: Tallon  - - Life Grove (Start) - - - - - - - - - - X-Ray Visor
+            LocationRequirements::new(all!(missile, gravity_suit, wave_beam, ice_beam,
+                space_jump_boots, morph_ball_bomb, spider_ball, boost_ball, any_power_bombs)),
+            //72: Tallon  - - Life Grove (Underwater Spinner)  - - - Artifact of Chozo
+            // XXX Gravity suit: Its not actually required, but could be considered a glitch.
+            LocationRequirements::new(all!(missile, gravity_suit, wave_beam, ice_beam,
+                space_jump_boots, morph_ball_bomb, spider_ball, boost_ball, any_power_bombs)),
+            //73: Mines - - - Main Quarry  - - - - - - - - - - - - - Missile Expansion 38
+            LocationRequirements::new(all!(missile, any_suit, wave_beam, ice_beam, space_jump_boots,
+                morph_ball_bomb, spider_ball, thermal_visor)),
+            //74: Mines - - - Security Access A  - - - - - - - - - - Missile Expansion 39
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball),
+                all!(mines_from_magmoor_reqs, morph_ball_bomb),
+            )),
+            //75: Mines - - - Storage Depot B  - - - - - - - - - - - Grapple Beam
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs),
+                all!(mines_from_magmoor_reqs, grapple_beam, morph_ball_bomb),
+            )),
+            //76: Mines - - - Storage Depot A  - - - - - - - - - - - Flamethrower
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, plasma_beam),
+                all!(mines_from_magmoor_reqs, grapple_beam, morph_ball_bomb, plasma_beam),
+            )),
+            //77: Mines - - - Elite Research (Phazon Elite)  - - - - Artifact of Warrior
+            // You need to #84 to unlock this fight. So, boost ball is required
+            // so one can go down to it and back up.
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball),
+                all!(mines_from_magmoor_reqs, grapple_beam, morph_ball_bomb, boost_ball),
+            )),
+            //78: Mines - - - Elite Research (Laser) - - - - - - - - Missile Expansion 40
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, boost_ball),
+                all!(mines_from_magmoor_reqs, grapple_beam, morph_ball_bomb, boost_ball),
+            )),
+            //79: Mines - - - Elite Control Access - - - - - - - - - Missile Expansion 41
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs),
+                all!(mines_from_magmoor_reqs),
+            )),
+            //80: Mines - - - Ventilation Shaft  - - - - - - - - - - Energy Tank 11
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, boost_ball, any_power_bombs),
+                all!(mines_from_magmoor_reqs, boost_ball),
+            )),
+            //81: Mines - - - Phazon Processing Center - - - - - - - Missile Expansion 42
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs),
+                all!(mines_from_magmoor_reqs),
+            )),
+            //82: Mines - - - Processing Center Access - - - - - - - Energy Tank 12
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball, plasma_beam, xray_visor,
+                    grapple_beam),
+                all!(mines_from_magmoor_reqs, boost_ball, plasma_beam, xray_visor, grapple_beam),
+            )),
+            //83: Mines - - - Elite Quarters - - - - - - - - - - - - Phazon Suit
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball, plasma_beam, xray_visor),
+                all!(mines_from_magmoor_reqs, boost_ball, plasma_beam, xray_visor),
+            )),
+            //84: Mines - - - Central Dynamo - - - - - - - - - - - - Power Bomb
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, boost_ball),
+                all!(mines_from_magmoor_reqs, boost_ball),
+            )),
+            //85: Mines - - - Metroid Quarantine B - - - - - - - - - Missile Expansion 43
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball, xray_visor, grapple_beam,
+                    plasma_beam),
+                all!(mines_from_magmoor_reqs, boost_ball, xray_visor, grapple_beam, plasma_beam),
+            )),
+            //86: Mines - - - Metroid Quarantine A - - - - - - - - - Missile Expansion 44
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball, xray_visor),
+                all!(mines_from_magmoor_reqs, boost_ball, xray_visor),
+            )),
+            //87: Mines - - - Fungal Hall B  - - - - - - - - - - - - Missile Expansion 45
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, boost_ball, xray_visor, plasma_beam),
+                all!(mines_from_magmoor_reqs, boost_ball, xray_visor, plasma_beam),
+            )),
+            //88: Mines - - - Phazon Mining Tunnel - - - - - - - - - Artifact of Newborn
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball, xray_visor, plasma_beam,
+                    grapple_beam, phazon_suit),
+                all!(mines_from_magmoor_reqs, boost_ball, xray_visor, plasma_beam, grapple_beam,
+                    phazon_suit),
+            )),
+            //89: Mines - - - Fungal Hall Access - - - - - - - - - - Missile Expansion 46
+            LocationRequirements::new(any!(
+                all!(mines_from_tallon_reqs, any_power_bombs, boost_ball, xray_visor),
+                all!(mines_from_magmoor_reqs, boost_ball, xray_visor, plasma_beam),
+            )),
+            //90: Magmoor - - Lava Lake  - - - - - - - - - - - - - - Artifact of Nature
+            LocationRequirements::new(all!(missile, mbb_or_pb, any_suit, space_jump_boots)),
+            //91: Magmoor - - Triclops Pit - - - - - - - - - - - - - Missile Expansion 47
+            // TODO Double if x-ray is needed if space jump??
+            LocationRequirements::new(all!(
+                missile,
+                morph_ball,
+                any_suit,
+                any!(space_jump_boots, xray_visor),
+            )),
+            //92: Magmoor - - Storage Cavern - - - - - - - - - - - - Missile Expansion 48
+            LocationRequirements::new(all!(missile, morph_ball, any_suit)),
+            //93: Magmoor - - Transport Tunnel A - - - - - - - - - - Energy Tank 13
+            LocationRequirements::new(all!(missile, morph_ball_bomb, any_suit)),
+            //94: Magmoor - - Warrior Shrine - - - - - - - - - - - - Artifact of Strength
+            LocationRequirements::new(all!(missile, any_suit, space_jump_boots, boost_ball)),
+            //95: Magmoor - - Shore Tunnel - - - - - - - - - - - - - Ice Spreader
+            LocationRequirements::new(all!(missile, any_power_bombs, any_suit, space_jump_boots)),
+            //96: Magmoor - - Fiery Shores (Morph Track) - - - - - - Missile Expansion 49
+            // TODO This can be done using space jump, but is that too much of a glitch?
+            LocationRequirements::new(all!(missile, morph_ball_bomb, any_suit)),
+            //97: Magmoor - - Fiery Shores (Warrior Shrine Tunnel) - Power Bomb Expansion 4
+            LocationRequirements::new(all!(missile, any_suit, space_jump_boots, boost_ball,
+                any_power_bombs)),
+            //98: Magmoor - - Plasma Processing  - - - - - - - - - - Plasma Beam
+            LocationRequirements::new(all!(missile, any_suit, space_jump_boots, boost_ball,
+                spider_ball, grapple_beam, wave_beam, ice_beam)),
+            //99: Magmoor - - Magmoor Workstation  - - - - - - - - - Energy Tank 14
+            LocationRequirements::new(all!(missile, any_suit, space_jump_boots, spider_ball,
+                wave_beam, thermal_visor)),
+        ],
+        boss_locations: vec![
+            (TempleBoss::Flaahgra, 20),
+            (TempleBoss::Thardus, 44),
+            (TempleBoss::PhazonElite, 77),
+            (TempleBoss::OmegaPirate, 83),
+        ],
+        meta_ridley: all!(missile),
+        impact_crater: all!(missile, morph_ball_bomb, space_jump_boots, wave_beam, ice_beam,
+            plasma_beam, thermal_visor, xray_visor, phazon_suit),
+    }
+}
@@ -22,8 +22,12 @@ use clap::{
 use serde::Deserialize;
 
 use enum_map::EnumMap;
+use crate::door_meta::{BlastShieldType, DoorType};
 use crate::elevators::{Elevator, SpawnRoom};
-use crate::pickup_meta::PickupType;
+use crate::generator::{self, ItemPool};
+use crate::layout_string;
+use crate::normal_difficulty;
+use crate::pickup_meta::{self, PickupType};
 use crate::starting_items::StartingItems;
 
 /*** Parsed Config (fn patch_iso) ***/
@@ -43,9 +47,22 @@ pub struct Layout
     pub pickups: Vec<PickupType>,
     pub starting_location: SpawnRoom,
     pub elevators: EnumMap<Elevator, SpawnRoom>,
+    // Used unless the game config gives its own starting items
+    pub starting_items: Option<StartingItems>,
+    pub doors: Vec<DoorAssignment>,
     pub seed: u64,
 }
 
+/// The door and blast shield to put at one of the door locations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DoorAssignment
+{
+    /// Index into the door locations of every room in `pickup_meta::ROOM_INFO`, in order
+    pub location: u32,
+    pub door_type: DoorType,
+    pub blast_shield: BlastShieldType,
+}
+
 #[derive(Deserialize, Debug, Copy, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum ArtifactHintBehavior
@@ -111,6 +128,280 @@ pub struct RoomConfig
     // pub extra_pickups: Vec<PickupConfig>,
     // pub extra_scans: Vec<ScanConfig>,
     // pub aether_transform: Vec<AetherTransformConfig>,
+    #[serde(default)]
+    pub script_edits: Vec<ScriptEdit>,
+    /// Layer index -> whether that layer is active when starting a new game
+    #[serde(default)]
+    pub layers: HashMap<u32, bool>,
+}
+
+/// A single change to a room's script objects, applied in the order they're listed.
+/// Connection states and messages use the names from `structs::ConnectionState` and
+/// `structs::ConnectionMsg` (e.g. "ZERO", "ACTIVATE").
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", tag = "action")]
+pub enum ScriptEdit
+{
+    #[serde(rename_all = "camelCase")]
+    DeleteObject {
+        instance_id: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    MoveObject {
+        instance_id: u32,
+        layer: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    AddConnection {
+        instance_id: u32,
+        state: String,
+        message: String,
+        target: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    RemoveConnection {
+        instance_id: u32,
+        state: String,
+        message: String,
+        target: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    SetLayerActive {
+        layer: u32,
+        active: bool,
+    },
+    /// Copies an object (without its connections) to a fresh instance id. The position is
+    /// ignored for object types that don't have one.
+    #[serde(rename_all = "camelCase")]
+    CloneObject {
+        instance_id: u32,
+        #[serde(default)]
+        position: Option<[f32; 3]>,
+        layer: Option<u32>,
+    },
+    /// Sets a property by its path in the SCLY property schema, e.g. "Health.Health"
+    #[serde(rename_all = "camelCase")]
+    SetProperty {
+        instance_id: u32,
+        property: String,
+        value: serde_json::Value,
+    },
+}
+
+fn default_multiplier() -> f32
+{
+    1.0
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnemyStatsConfig
+{
+    #[serde(default = "default_multiplier")]
+    pub health_multiplier: f32,
+    #[serde(default = "default_multiplier")]
+    pub damage_multiplier: f32,
+
+    // Keyed by script object type name, e.g. "SpacePirate"
+    #[serde(default)]
+    pub overrides: HashMap<String, EnemyStatsOverride>,
+
+    pub randomize: Option<EnemyStatsRandomization>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnemyStatsOverride
+{
+    pub health_multiplier: Option<f32>,
+    pub damage_multiplier: Option<f32>,
+}
+
+/// Each range is a [min, max] multiplier, rolled separately for every enemy
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnemyStatsRandomization
+{
+    pub health: Option<[f32; 2]>,
+    pub damage: Option<[f32; 2]>,
+    pub size: Option<[f32; 2]>,
+}
+
+fn default_required_bosses() -> Vec<String>
+{
+    ["NewIntroBoss", "Flaahgra", "Thardus", "OmegaPirate", "Ridley"].iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnemyVulnerabilityConfig
+{
+    // Script object type names of the bosses that must be beaten to finish the game
+    #[serde(default = "default_required_bosses")]
+    pub required_bosses: Vec<String>,
+}
+
+/// Bosses that can be required to open the Artifact Temple
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TempleBoss
+{
+    Flaahgra,
+    Thardus,
+    PhazonElite,
+    OmegaPirate,
+}
+
+impl TempleBoss
+{
+    pub fn name(&self) -> &'static str
+    {
+        match self {
+            TempleBoss::Flaahgra => "Flaahgra",
+            TempleBoss::Thardus => "Thardus",
+            TempleBoss::PhazonElite => "Phazon Elite",
+            TempleBoss::OmegaPirate => "Omega Pirate",
+        }
+    }
+}
+
+fn default_required_artifacts() -> u32
+{
+    12
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactTempleConfig
+{
+    // Only the first this many artifacts (in totem order) present in the layout are needed,
+    // the rest are treated as already collected
+    #[serde(default = "default_required_artifacts")]
+    pub required_artifacts: u32,
+
+    // Each boss takes the place of an artifact that isn't needed
+    #[serde(default)]
+    pub required_bosses: Vec<TempleBoss>,
+
+    // Open the portal as soon as the temple's requirements are met
+    #[serde(default)]
+    pub skip_ridley: bool,
+
+    // The portal goes straight to the credits instead of the Impact Crater
+    #[serde(default)]
+    pub skip_impact_crater: bool,
+}
+
+impl Default for ArtifactTempleConfig
+{
+    fn default() -> Self
+    {
+        ArtifactTempleConfig {
+            required_artifacts: default_required_artifacts(),
+            required_bosses: vec![],
+            skip_ridley: false,
+            skip_impact_crater: false,
+        }
+    }
+}
+
+impl ArtifactTempleConfig
+{
+    /// The artifacts in a layout that are needed to open the temple, in totem order
+    pub fn required_artifact_types(&self, pickup_layout: &[PickupType]) -> Vec<PickupType>
+    {
+        let mut artifacts: Vec<_> = PickupType::iter()
+            .filter(|pt| pt.is_artifact() && pickup_layout.contains(pt))
+            .collect();
+        artifacts.sort_by_key(|pt| pt.pickup_data().kind);
+        artifacts.truncate(self.required_artifacts as usize);
+        artifacts
+    }
+}
+
+/// What happens to the player's items during the escape from the Frigate. Both settings only
+/// apply when starting on the Frigate.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemLossConfig
+{
+    // Keep the starting items through the escape instead of being left with the item loss items
+    #[serde(default)]
+    pub skip_item_loss: bool,
+
+    // The items taken away are placed as pickups in the room the escape leads to
+    #[serde(default)]
+    pub replace_lost_items: bool,
+}
+
+/// An exact place to appear in the starting room, instead of at one of its spawn points. If the
+/// player couldn't stand there, the closest place they can walk to from the room's doors and
+/// spawn points is used instead.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnPointConfig
+{
+    pub position: [f32; 3],
+
+    // Degrees counterclockwise from facing along the room's +Y axis
+    #[serde(default)]
+    pub yaw: f32,
+}
+
+/// Where the item at one of this player's pickup locations goes in a multiworld game
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePickup
+{
+    pub player: u32,
+    pub pickup: PickupType,
+}
+
+/// This player's part of a multiworld game. The multiworld bridge reads the same settings to
+/// relay pickups between the players' games.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiworldConfig
+{
+    pub player: u32,
+
+    // Pickup locations (by layout index) holding another player's item. The item replaces the
+    // layout's, and collecting it sends it to that player instead of giving it to this one.
+    #[serde(default)]
+    pub remote_pickups: HashMap<usize, RemotePickup>,
+}
+
+/// Settings for generating a layout when one isn't provided
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratorConfig
+{
+    // A random seed is used if this isn't given
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub item_pool: ItemPool,
+    // Overrides the number of each item placed, which is otherwise decided by the item pool
+    pub item_counts: Option<HashMap<PickupType, u32>>,
+}
+
+/// Items that are given out in order, no matter which of their pickups is collected first
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressiveItem
+{
+    // Shown in the hudmemo in place of the item's name, e.g. "Progressive Beam"
+    pub name: String,
+    pub items: Vec<PickupType>,
+}
+
+/// Extra items given along with every pickup of a type
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PickupBundle
+{
+    pub pickup: PickupType,
+    pub extra_items: Vec<PickupType>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -118,6 +409,7 @@ pub struct RoomConfig
 pub struct LevelConfig
 {
     pub transports: HashMap<String, String>,
+    // Keyed by room name, or by MREA id (e.g. "0x31C44B23") for rooms whose names aren't unique
     pub rooms: HashMap<String, RoomConfig>,
 }
 
@@ -127,12 +419,14 @@ pub struct PatchConfig
     pub input_iso: memmap::Mmap,
     pub iso_format: IsoFormat,
     pub output_iso: File,
+    pub spoiler_path: Option<String>,
 
     pub layout: Layout,
 
     pub level_data: HashMap<String, LevelConfig>,
 
     pub starting_room: String,
+    pub starting_spawn_point: Option<SpawnPointConfig>,
     pub starting_memo: Option<String>,
 
     pub skip_hudmenus: bool,
@@ -147,9 +441,11 @@ pub struct PatchConfig
     pub map_default_state: MapState,
     pub auto_enabled_elevators: bool,
     pub quiet: bool,
+    pub check_solvability: bool,
 
     pub starting_items: StartingItems,
     pub item_loss_items: StartingItems,
+    pub item_loss: ItemLossConfig,
 
     pub enable_vault_ledge_door: bool,
     pub artifact_hint_behavior: ArtifactHintBehavior,
@@ -159,6 +455,8 @@ pub struct PatchConfig
     pub suit_hue_rotate_angle: Option<i32>,
 
     pub quickplay: bool,
+    pub item_tracker: bool,
+    pub practice_mode: bool,
 
     pub game_banner: GameBanner,
     pub comment: String,
@@ -167,6 +465,18 @@ pub struct PatchConfig
     pub credits_string: Option<String>,
     pub artifact_hints: Option<HashMap<String,String>>, // e.g. "Strength":"This item can be found in Ruined Fountain"
 
+    pub enemy_stats: Option<EnemyStatsConfig>,
+    pub enemy_vulnerabilities: Option<EnemyVulnerabilityConfig>,
+
+    pub artifact_temple: ArtifactTempleConfig,
+    pub progressive_items: Vec<ProgressiveItem>,
+    pub pickup_bundles: Vec<PickupBundle>,
+    pub expansion_amounts: HashMap<PickupType, u32>,
+    pub location_expansion_amounts: HashMap<usize, u32>,
+
+    pub multiworld: Option<MultiworldConfig>,
+
+    pub disabled_patches: Vec<String>,
 }
 
 
@@ -200,6 +510,8 @@ impl TryInto<Layout> for LayoutWrapper
                     pickups,
                     starting_location,
                     elevators,
+                    starting_items: None,
+                    doors: vec![],
                     seed: hasher.finish(),
                 })
             },
@@ -218,7 +530,12 @@ struct Preferences
     trilogy_disc_path: Option<String>,
     keep_fmvs: Option<bool>,
     quickplay: Option<bool>,
+    // Show the collected items on the pause screen
+    item_tracker: Option<bool>,
+    // Button combos for refilling, saving positions and warping, and room timers
+    practice_mode: Option<bool>,
     quiet: Option<bool>,
+    check_solvability: Option<bool>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -226,6 +543,7 @@ struct Preferences
 struct GameConfig
 {
     starting_room: Option<String>,
+    starting_spawn_point: Option<SpawnPointConfig>,
     starting_memo: Option<String>,
 
     nonvaria_heat_damage: Option<bool>,
@@ -236,6 +554,7 @@ struct GameConfig
 
     starting_items: Option<StartingItems>,
     item_loss_items: Option<StartingItems>,
+    item_loss: Option<ItemLossConfig>,
 
     etank_capacity: Option<u32>,
     missile_capacity: Option<u32>,
@@ -247,6 +566,23 @@ struct GameConfig
 
     credits_string: Option<String>,
     artifact_hints: Option<HashMap<String,String>>, // e.g. "Strength":"This item can be found in Ruined Fountain"
+
+    enemy_stats: Option<EnemyStatsConfig>,
+    enemy_vulnerabilities: Option<EnemyVulnerabilityConfig>,
+
+    artifact_temple: Option<ArtifactTempleConfig>,
+    progressive_items: Option<Vec<ProgressiveItem>>,
+    pickup_bundles: Option<Vec<PickupBundle>>,
+
+    // How much each Missile Expansion, Energy Tank or Power Bomb Expansion gives
+    expansion_amounts: Option<HashMap<PickupType, u32>>,
+    // Overrides the amount given by the expansion at a pickup location (by layout index)
+    location_expansion_amounts: Option<HashMap<usize, u32>>,
+
+    multiworld: Option<MultiworldConfig>,
+
+    // The names of patches not to apply, as listed in randomprime.txt
+    disabled_patches: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -255,6 +591,7 @@ struct PatchConfigPrivate
 {
     input_iso: Option<String>,
     output_iso: Option<String>,
+    spoiler_path: Option<String>,
 
     #[serde(default)]
     preferences: Preferences,
@@ -266,6 +603,7 @@ struct PatchConfigPrivate
     level_data: HashMap<String, LevelConfig>,
 
     layout: Option<LayoutWrapper>, // TODO: only support struct (because of doors)
+    generator: Option<GeneratorConfig>,
 }
 
 /*** Parse Patcher Input ***/
@@ -279,6 +617,23 @@ impl PatchConfig
         json_config.parse()
     }
 
+    /// The item at a pickup location that's sent to another player in a multiworld game
+    pub fn remote_pickup(&self, location_idx: usize) -> Option<RemotePickup>
+    {
+        self.multiworld.as_ref()?.remote_pickups.get(&location_idx).copied()
+    }
+
+    /// The amount given by the expansion at a pickup location, if it isn't the vanilla amount
+    pub fn expansion_amount(&self, location_idx: usize, pickup_type: PickupType) -> Option<u32>
+    {
+        if !pickup_type.is_expansion() || self.remote_pickup(location_idx).is_some() {
+            return None;
+        }
+        self.location_expansion_amounts.get(&location_idx)
+            .or_else(|| self.expansion_amounts.get(&pickup_type))
+            .copied()
+    }
+
     pub fn from_cli_options() -> Result<Self, String>
     {
         let matches = App::new("randomprime ISO patcher")
@@ -289,6 +644,10 @@ impl PatchConfig
             .arg(Arg::with_name("output iso path")
                 .long("output-iso")
                 .takes_value(true))
+            .arg(Arg::with_name("spoiler path")
+                .long("spoiler-path")
+                .help("Write a JSON spoiler log for the layout to this path")
+                .takes_value(true))
             .arg(Arg::with_name("profile json path")
                 .long("profile")
                 .help("Path to JSON file with patch configuration (cli config takes priority). See documentation for details.")
@@ -297,6 +656,12 @@ impl PatchConfig
                 .long("layout")
                 .takes_value(true)
                 .allow_hyphen_values(true))
+            .arg(Arg::with_name("generate seed")
+                .long("generate-seed")
+                .help("Generate a layout from this seed instead of using --layout")
+                .takes_value(true)
+                .validator(|s| s.parse::<u64>().map(|_| ())
+                                            .map_err(|_| "Expected an integer".to_string())))
             .arg(Arg::with_name("starting room")
                 .long("starting-room")
                 .help("Room which the player starts their adventure from. Format - <world>:<room name>, where <world> is [Frigate|Tallon|Chozo|Magmoor|Phendrana|Mines|Crater]")
@@ -364,6 +729,9 @@ impl PatchConfig
             .arg(Arg::with_name("quiet")
                 .long("quiet")
                 .help("Don't print the progress messages"))
+            .arg(Arg::with_name("check solvability")
+                .long("check-solvability")
+                .help("Refuse to patch layouts that can't be completed with the normal logic"))
             .arg(Arg::with_name("main menu message")
                 .long("main-menu-message")
                 .hidden(true)
@@ -381,6 +749,12 @@ impl PatchConfig
             .arg(Arg::with_name("quickplay")
                 .long("quickplay")
                 .hidden(true))
+            .arg(Arg::with_name("item tracker")
+                .long("item-tracker")
+                .help("Show the collected items and artifacts on the pause screen"))
+            .arg(Arg::with_name("practice mode")
+                .long("practice-mode")
+                .help("Add button combos for refilling, saving positions and warping, and room timers"))
             .arg(Arg::with_name("text file comment")
                 .long("text-file-comment")
                 .hidden(true)
@@ -413,7 +787,10 @@ impl PatchConfig
             "obfuscate items" => patch_config.preferences.obfuscate_items,
             "keep attract mode" => patch_config.preferences.keep_fmvs,
             "quickplay" => patch_config.preferences.quickplay,
+            "item tracker" => patch_config.preferences.item_tracker,
+            "practice mode" => patch_config.preferences.practice_mode,
             "quiet" => patch_config.preferences.quiet,
+            "check solvability" => patch_config.preferences.check_solvability,
             "nonvaria heat damage" => patch_config.game_config.nonvaria_heat_damage,
             "staggered suit damage" => patch_config.game_config.staggered_suit_damage,
             "auto enabled elevators" => patch_config.game_config.auto_enabled_elevators,
@@ -427,6 +804,9 @@ impl PatchConfig
         if let Some(output_iso_path) = matches.value_of("output iso path") {
             patch_config.output_iso = Some(output_iso_path.to_string());
         }
+        if let Some(spoiler_path) = matches.value_of("spoiler path") {
+            patch_config.spoiler_path = Some(spoiler_path.to_string());
+        }
         if let Some(map_default_state) = matches.value_of("map default state") {
             patch_config.preferences.map_default_state = Some(map_default_state.to_string());
         }
@@ -458,6 +838,10 @@ impl PatchConfig
         if let Some(pickup_layout_str) = matches.value_of("pickup layout") {
             patch_config.layout = Some(LayoutWrapper::String(pickup_layout_str.to_string()));
         }
+        if let Some(seed) = matches.value_of("generate seed") {
+            let generator = patch_config.generator.get_or_insert_with(Default::default);
+            generator.seed = Some(seed.parse::<u64>().unwrap());
+        }
         if let Some(starting_items_str) = matches.value_of("starting items") {
             patch_config.game_config.starting_items = Some(
                 StartingItems::from_u64(starting_items_str.parse::<u64>().unwrap())
@@ -502,12 +886,148 @@ impl PatchConfigPrivate
             IsoFormat::Iso
         };
 
-        let _layout = self.layout.clone()
-            .unwrap_or_else(|| LayoutWrapper::String(
-                "NCiq7nTAtTnqPcap9VMQk_o8Qj6ZjbPiOdYDB5tgtwL_f01-UpYklNGnL-gTu5IeVW3IoUiflH5LqNXB3wVEER4".to_string()
-            ));
+        let default_starting_items = || StartingItems::from_u64(1);
+        let mut layout: Layout = match (&self.layout, &self.generator) {
+            (Some(_), Some(_)) => Err("A layout cannot be both given and generated")?,
+            (None, Some(generator_config)) => {
+                let seed = generator_config.seed.unwrap_or_else(rand::random);
+                let difficulty = normal_difficulty::difficulty();
+                let item_counts = generator_config.item_counts.clone()
+                    .unwrap_or_else(|| {
+                        generator::item_pool_counts(seed, &difficulty, generator_config.item_pool)
+                    });
+                let mut elevators = EnumMap::<Elevator, SpawnRoom>::new();
+                elevators.extend(Elevator::iter()
+                    .map(|elv| (elv, elv.elevator_data().default_dest.into())));
+                generator::generate_layout(
+                    seed,
+                    &difficulty,
+                    &item_counts,
+                    &self.game_config.starting_items.clone()
+                        .unwrap_or_else(default_starting_items),
+                    SpawnRoom::LandingSite,
+                    elevators,
+                ).map_err(|e| format!("Failed to generate layout: {}", e))?
+            },
+            (layout, None) => {
+                let _layout = layout.clone()
+                    .unwrap_or_else(|| LayoutWrapper::String(
+                        "NCiq7nTAtTnqPcap9VMQk_o8Qj6ZjbPiOdYDB5tgtwL_f01-UpYklNGnL-gTu5IeVW3IoUiflH5LqNXB3wVEER4".to_string()
+                    ));
+                _layout.try_into()?
+            },
+        };
+
+        // A layout may describe fewer locations than the game has (the rest are left empty) or
+        // more, as long as the extra ones are empty
+        let pickup_location_count = pickup_meta::pickup_location_room_names().count();
+        if let Some(pt) = layout.pickups.iter().skip(pickup_location_count)
+            .find(|pt| **pt != PickupType::Nothing)
+        {
+            Err(format!(
+                "Layout places {} past the last of the {} pickup locations",
+                pt.name(),
+                pickup_location_count,
+            ))?
+        }
+        layout.pickups.resize(pickup_location_count, PickupType::Nothing);
 
-        let layout = _layout.try_into()?;
+        if !layout.doors.is_empty() {
+            Err("Layouts with door assignments are not supported yet")?
+        }
+
+        let starting_items = self.game_config.starting_items.clone()
+            .or_else(|| layout.starting_items.clone())
+            .unwrap_or_else(default_starting_items);
+        if let Some(pt) = starting_items.artifacts.iter().find(|pt| !pt.is_artifact()) {
+            Err(format!("Cannot start with {} as an artifact", pt.name()))?
+        }
+
+        let progressive_items = self.game_config.progressive_items.clone().unwrap_or_default();
+        let mut progressive_pickups = vec![];
+        for progressive_item in &progressive_items {
+            if progressive_item.items.len() < 2 {
+                Err(format!("{} needs at least two items", progressive_item.name))?
+            }
+            for pt in &progressive_item.items {
+                if pt.is_artifact() || *pt == PickupType::Nothing {
+                    Err(format!("{} cannot be part of {}", pt.name(), progressive_item.name))?
+                }
+                if progressive_pickups.contains(pt) {
+                    Err(format!("{} is part of more than one progressive item", pt.name()))?
+                }
+                progressive_pickups.push(*pt);
+            }
+        }
+        if !progressive_items.is_empty() && self.generator.is_some() {
+            Err("Progressive items cannot be used with a generated layout")?
+        }
+        let pickup_bundles = self.game_config.pickup_bundles.clone().unwrap_or_default();
+        for bundle in &pickup_bundles {
+            if progressive_pickups.contains(&bundle.pickup) {
+                Err(format!("{} cannot be both progressive and bundled", bundle.pickup.name()))?
+            }
+            if pickup_bundles.iter().filter(|other| other.pickup == bundle.pickup).count() > 1 {
+                Err(format!("{} has more than one bundle", bundle.pickup.name()))?
+            }
+            if let Some(pt) = bundle.extra_items.iter().find(|pt| pt.is_artifact()) {
+                Err(format!("{} cannot be bundled with {}", pt.name(), bundle.pickup.name()))?
+            }
+        }
+
+        let starting_spawn_point = self.game_config.starting_spawn_point;
+        if let Some(spawn_point) = &starting_spawn_point {
+            let finite = spawn_point.position.iter()
+                .chain(iter::once(&spawn_point.yaw))
+                .all(|f| f.is_finite());
+            if !finite {
+                Err("The starting spawn point's position and yaw must be finite numbers")?
+            }
+        }
+
+        let item_loss = self.game_config.item_loss.clone().unwrap_or_default();
+        if item_loss.skip_item_loss && item_loss.replace_lost_items {
+            Err("Lost items cannot be replaced when item loss is skipped")?
+        }
+
+        let expansion_amounts = self.game_config.expansion_amounts.clone().unwrap_or_default();
+        if let Some(pt) = expansion_amounts.keys().find(|pt| !pt.is_expansion()) {
+            Err(format!("Cannot change the amount given by {}", pt.name()))?
+        }
+        let location_expansion_amounts = self.game_config.location_expansion_amounts.clone()
+            .unwrap_or_default();
+        for &location_idx in location_expansion_amounts.keys() {
+            match layout.pickups.get(location_idx) {
+                Some(pt) if pt.is_expansion() => (),
+                Some(pt) => Err(format!(
+                    "Cannot change the amount given by {} at location {}",
+                    pt.name(),
+                    location_idx,
+                ))?,
+                None => Err(format!("There is no pickup location {}", location_idx))?,
+            }
+        }
+
+        let multiworld = self.game_config.multiworld.clone();
+        if let Some(multiworld) = &multiworld {
+            for (&location_idx, remote_pickup) in &multiworld.remote_pickups {
+                if location_idx >= layout.pickups.len() {
+                    Err(format!("There is no pickup location {}", location_idx))?
+                }
+                if remote_pickup.player == multiworld.player {
+                    Err(format!(
+                        "The item at location {} belongs to this player, so it can't be remote",
+                        location_idx,
+                    ))?
+                }
+                if location_expansion_amounts.contains_key(&location_idx) {
+                    Err(format!(
+                        "Cannot change the amount given by the remote item at location {}",
+                        location_idx,
+                    ))?
+                }
+            }
+        }
 
         let artifact_hint_behavior = {
             let artifact_hint_behavior_string = self.preferences.artifact_hint_behavior
@@ -547,6 +1067,45 @@ impl PatchConfigPrivate
             }
         };
 
+        if let Some(enemy_stats) = &self.game_config.enemy_stats {
+            for name in enemy_stats.overrides.keys() {
+                let is_enemy = structs::scly_schema().object_type(name)
+                    .map(structs::SclyProperty::is_patterned_ai_type)
+                    .unwrap_or(false);
+                if !is_enemy {
+                    Err(format!("Unknown enemy type '{}' in enemy stat overrides", name))?
+                }
+            }
+            let ranges = enemy_stats.randomize.iter()
+                .flat_map(|r| vec![r.health, r.damage, r.size])
+                .flatten();
+            for [min, max] in ranges {
+                if !(min > 0.0 && min <= max) {
+                    Err(format!("Invalid enemy stat randomization range [{}, {}]", min, max))?
+                }
+            }
+        }
+
+        if let Some(enemy_vulnerabilities) = &self.game_config.enemy_vulnerabilities {
+            for name in &enemy_vulnerabilities.required_bosses {
+                let is_enemy = structs::scly_schema().object_type(name)
+                    .map(structs::SclyProperty::is_patterned_ai_type)
+                    .unwrap_or(false);
+                if !is_enemy {
+                    Err(format!("Unknown boss type '{}' in required bosses", name))?
+                }
+            }
+        }
+
+        if let Some(artifact_temple) = &self.game_config.artifact_temple {
+            if artifact_temple.required_artifacts > 12 {
+                Err(format!(
+                    "Invalid number of required artifacts - {}",
+                    artifact_temple.required_artifacts
+                ))?
+            }
+        }
+
         let flaahgra_music_files = self.preferences.trilogy_disc_path.as_ref()
             .map(|path| extract_flaahgra_music_files(path))
             .transpose()?;
@@ -555,6 +1114,7 @@ impl PatchConfigPrivate
             input_iso,
             iso_format,
             output_iso,
+            spoiler_path: self.spoiler_path.clone(),
             layout,
             level_data: self.level_data.clone(),
 
@@ -565,9 +1125,13 @@ impl PatchConfigPrivate
             keep_fmvs: self.preferences.keep_fmvs.unwrap_or(false),
             suit_hue_rotate_angle: None,
             quiet: self.preferences.quiet.unwrap_or(false),
+            check_solvability: self.preferences.check_solvability.unwrap_or(false),
             quickplay: self.preferences.quickplay.unwrap_or(false),
+            item_tracker: self.preferences.item_tracker.unwrap_or(false),
+            practice_mode: self.preferences.practice_mode.unwrap_or(false),
 
             starting_room: self.game_config.starting_room.clone().unwrap_or("Tallon:Landing Site".to_string()),
+            starting_spawn_point,
             starting_memo: self.game_config.starting_memo.clone(),
 
             nonvaria_heat_damage: self.game_config.nonvaria_heat_damage.unwrap_or(false),
@@ -577,10 +1141,10 @@ impl PatchConfigPrivate
             map_default_state,
             enable_vault_ledge_door: self.game_config.enable_vault_ledge_door.unwrap_or(false),
 
-            starting_items: self.game_config.starting_items.clone()
-            .unwrap_or_else(|| StartingItems::from_u64(1)),
+            starting_items,
             item_loss_items: self.game_config.item_loss_items.clone()
             .unwrap_or_else(|| StartingItems::from_u64(1)),
+            item_loss,
 
             etank_capacity: self.game_config.etank_capacity.unwrap_or(100),
             missile_capacity: self.game_config.missile_capacity.unwrap_or(999),
@@ -593,6 +1157,15 @@ impl PatchConfigPrivate
             
             credits_string: self.game_config.credits_string.clone(),
             artifact_hints: self.game_config.artifact_hints.clone(),
+            enemy_stats: self.game_config.enemy_stats.clone(),
+            enemy_vulnerabilities: self.game_config.enemy_vulnerabilities.clone(),
+            artifact_temple: self.game_config.artifact_temple.clone().unwrap_or_default(),
+            progressive_items,
+            pickup_bundles,
+            expansion_amounts,
+            location_expansion_amounts,
+            multiworld,
+            disabled_patches: self.game_config.disabled_patches.clone().unwrap_or_default(),
         })
     }
 }
@@ -611,13 +1184,40 @@ pub fn extract_flaahgra_music_files(iso_path: &str) -> Result<[nod_wrapper::File
     res.map_err(|s: String| format!("Failed to extract Flaahgra music files: {}", s))
 }
 
+const LAYOUT_CHAR_TABLE: [u8; 64] =
+    *b"ABCDEFGHIJKLMNOPQRSTUWVXYZabcdefghijklmnopqrstuwvxyz0123456789-_";
+
+/// The elevator section that's left out of layout strings with vanilla elevators
+const DEFAULT_ELEVATOR_LAYOUT: &str = "qzoCAr2fwehJmRjM";
+
+/// Reverses the order of the odd bits. Doing this twice gives back the original number.
+fn reverse_odd_bits(sum: &BigUint) -> BigUint
+{
+    let mut bits = sum.to_str_radix(2).into_bytes();
+    for i in 0..(bits.len() / 4) {
+        let len = bits.len() - bits.len() % 2;
+        bits.swap(i * 2 + 1, len - i * 2 - 1);
+    }
+    BigUint::parse_bytes(&bits, 2).unwrap()
+}
+
+fn layout_checksum(sum: &BigUint, checksum_size: usize) -> u8
+{
+    let checksum_bitmask = (1u8 << checksum_size) - 1;
+    let mut checksum = 0;
+    let mut sum = sum.clone();
+    while sum > 0u8.into() {
+        let remainder = (sum.clone() & BigUint::from(checksum_bitmask)).to_u8().unwrap();
+        checksum = (checksum + remainder) & checksum_bitmask;
+        sum >>= checksum_size;
+    }
+    checksum
+}
+
 pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, checksum_size: usize, is: I)
     -> Result<Vec<u8>, String>
     where I: Iterator<Item = u8> + Clone
 {
-    const LAYOUT_CHAR_TABLE: [u8; 64] =
-        *b"ABCDEFGHIJKLMNOPQRSTUWVXYZabcdefghijklmnopqrstuwvxyz0123456789-_";
-
     let mut sum: BigUint = 0u8.into();
     for c in bytes.iter().rev() {
         if let Some(idx) = LAYOUT_CHAR_TABLE.iter().position(|i| i == c) {
@@ -627,13 +1227,7 @@ pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, chec
         }
     }
 
-    // Reverse the order of the odd bits
-    let mut bits = sum.to_str_radix(2).into_bytes();
-    for i in 0..(bits.len() / 4) {
-        let len = bits.len() - bits.len() % 2;
-        bits.swap(i * 2 + 1, len - i * 2 - 1);
-    }
-    sum = BigUint::parse_bytes(&bits, 2).unwrap();
+    sum = reverse_odd_bits(&sum);
 
     // The upper `checksum_size` bits are a checksum, so seperate them from the sum.
     let checksum_bitmask = (1u8 << checksum_size) - 1;
@@ -641,16 +1235,7 @@ pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, chec
     sum -= checksum.clone();
     let checksum = (checksum >> layout_data_size).to_u8().unwrap();
 
-    let mut computed_checksum = 0;
-    {
-        let mut sum = sum.clone();
-        while sum > 0u8.into() {
-            let remainder = (sum.clone() & BigUint::from(checksum_bitmask)).to_u8().unwrap();
-            computed_checksum = (computed_checksum + remainder) & checksum_bitmask;
-            sum >>= checksum_size;
-        }
-    }
-    if checksum != computed_checksum {
+    if checksum != layout_checksum(&sum, checksum_size) {
         return Err("Layout checksum failed.".to_string());
     }
 
@@ -667,11 +1252,92 @@ pub fn parse_layout_chars_to_ints<I>(bytes: &[u8], layout_data_size: usize, chec
     Ok(res)
 }
 
+/// The inverse of `parse_layout_chars_to_ints`. `is` gives the number of possible values of
+/// each int in the same (reversed) order that the parser uses.
+pub fn encode_ints_to_layout_chars<I>(ints: &[u8], layout_data_size: usize, checksum_size: usize, is: I)
+    -> Result<String, String>
+    where I: Iterator<Item = u8>
+{
+    let sizes: Vec<u8> = is.collect();
+    if sizes.len() != ints.len() {
+        return Err(format!("Expected {} values but got {}.", sizes.len(), ints.len()));
+    }
+
+    let mut sum: BigUint = 0u8.into();
+    for (i, size) in ints.iter().zip(sizes.iter().rev()) {
+        if i >= size {
+            return Err(format!("Layout value {} is out of range.", i));
+        }
+        sum = sum * BigUint::from(*size) + BigUint::from(*i);
+    }
+    if sum.bits() as usize > layout_data_size {
+        return Err("Layout data is too large.".to_string());
+    }
+
+    let checksum = layout_checksum(&sum, checksum_size);
+    sum += BigUint::from(checksum) << layout_data_size;
+    let mut sum = reverse_odd_bits(&sum);
+
+    let char_count = (layout_data_size + checksum_size + 5) / 6;
+    let mut res = String::with_capacity(char_count);
+    for _ in 0..char_count {
+        let (quotient, remainder) = sum.div_rem(&BigUint::from(64u8));
+        res.push(LAYOUT_CHAR_TABLE[remainder.to_usize().unwrap()] as char);
+        sum = quotient;
+    }
+    Ok(res)
+}
+
+impl Layout
+{
+    /// Encodes the pickups, elevators and starting location in the format used by the web
+    /// interface. Everything else in the layout (including the seed) is lost.
+    pub fn to_layout_string(&self) -> Result<String, String>
+    {
+        if self.pickups.len() != 100 {
+            return Err(format!("Layout strings must have 100 pickups, not {}.", self.pickups.len()));
+        }
+        let has_scan_visor = self.pickups.contains(&PickupType::ScanVisor);
+        let pickup_layout: Vec<u8> = self.pickups.iter().map(|pt| pt.idx() as u8).collect();
+        let pickup_chars = encode_ints_to_layout_chars(
+                &pickup_layout,
+                if has_scan_visor { 521 } else { 517 },
+                if has_scan_visor { 1 } else { 5 },
+                iter::repeat(if has_scan_visor { 37u8 } else { 36u8 }).take(100)
+            ).map_err(|err| format!("Encoding pickup layout: {}", err))?;
+
+        let mut elevator_nums = Elevator::iter()
+            .map(|elv| layout_string::spawn_room_number(self.elevators[elv]).map(|n| n as u8))
+            .collect::<Result<Vec<_>, _>>()?;
+        elevator_nums.push(layout_string::spawn_room_number(self.starting_location)? as u8);
+        let elevator_chars = encode_ints_to_layout_chars(
+                &elevator_nums,
+                91, 5,
+                iter::once(21u8).chain(iter::repeat(20u8).take(20))
+            ).map_err(|err| format!("Encoding elevator layout: {}", err))?;
+
+        let mut res = String::new();
+        if elevator_chars != DEFAULT_ELEVATOR_LAYOUT {
+            res.push_str(&elevator_chars);
+            res.push('.');
+        }
+        if has_scan_visor {
+            res.push('!');
+        }
+        res.push_str(&pickup_chars);
+        Ok(res)
+    }
+}
+
 impl std::str::FromStr for Layout
 {
     type Err = String;
     fn from_str(text: &str) -> Result<Layout, String>
     {
+        if layout_string::is_v2(text) {
+            return layout_string::decode(text);
+        }
+
         if !text.is_ascii() {
             return Err("Layout string contains non-ascii characters.".to_string());
         }
@@ -680,7 +1346,7 @@ impl std::str::FromStr for Layout
         let (elevator_bytes, pickup_bytes) = if let Some(n) = text.iter().position(|c| *c == b'.') {
             (&text[..n], &text[(n + 1)..])
         } else {
-            (b"qzoCAr2fwehJmRjM" as &[u8], text)
+            (DEFAULT_ELEVATOR_LAYOUT.as_bytes(), text)
         };
 
         if elevator_bytes.len() != 16 {
@@ -732,7 +1398,44 @@ impl std::str::FromStr for Layout
             pickups,
             starting_location,
             elevators,
+            starting_items: None,
+            doors: vec![],
             seed,
         })
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn layout_strings_round_trip()
+    {
+        const LAYOUT: &str =
+            "NCiq7nTAtTnqPcap9VMQk_o8Qj6ZjbPiOdYDB5tgtwL_f01-UpYklNGnL-gTu5IeVW3IoUiflH5LqNXB3wVEER4";
+        let layout: Layout = LAYOUT.parse().unwrap();
+        assert_eq!(layout.to_layout_string().unwrap(), LAYOUT);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let mut layout = layout.clone();
+            let max_idx = if rng.gen() { 37 } else { 36 };
+            for pt in layout.pickups.iter_mut() {
+                *pt = PickupType::from_idx(rng.gen_range(0, max_idx)).unwrap();
+            }
+            for elv in Elevator::iter() {
+                layout.elevators[elv] = SpawnRoom::from_u32(rng.gen_range(0, 20)).unwrap();
+            }
+            layout.starting_location = SpawnRoom::from_u32(rng.gen_range(0, 21)).unwrap();
+
+            let text = layout.to_layout_string().unwrap();
+            let decoded: Layout = text.parse().unwrap();
+            assert_eq!(decoded.pickups, layout.pickups);
+            assert_eq!(decoded.starting_location, layout.starting_location);
+            assert_eq!(decoded.elevators, layout.elevators);
+        }
+    }
+}
@@ -1,11 +1,16 @@
 use reader_writer::FourCC;
-use structs::{FstEntryFile, GcDisc, Resource, ResourceKind};
+use structs::{FstEntryFile, GcDisc, Pak, Resource, ResourceKind};
 
-use crate::mlvl_wrapper::{MlvlArea, MlvlEditor};
+use crate::{
+    mlvl_wrapper::{MlvlArea, MlvlEditor},
+    patches::Version,
+};
 
 use std::{
+    cmp,
     collections::{HashMap, HashSet},
-    ops::RangeFrom,
+    panic,
+    thread,
 };
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
@@ -23,71 +28,314 @@ struct MreaKey<'r>
     room_id: u32,
 }
 
-type SclyPatch<'r, 's> = dyn FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's;
+/// A named patch that a `PrimePatcher` can apply. The file, resource and SCLY patches added
+/// under its name make up the patch, and are the resources it touches.
+///
+/// Only the script objects a patch changes by instance id are checked for conflicts. Patches
+/// that find the objects they change by type when they run (e.g. every enemy in a room for
+/// "enemy_stats", or a room's World Transporter) can't list them, so they aren't checked.
+pub struct PatchInfo
+{
+    pub name: &'static str,
+    // The versions of the game the patch is for, or every version if it's empty
+    pub versions: &'static [Version],
+    // The existing script objects the patch changes or removes, by the MREA they're in. Ones
+    // that are only known once the patch is added go through `add_touched_objects` instead.
+    pub objects: &'static [(u32, &'static [u32])],
+    // The patches that must be applied before this one, if they're for the game's version. They
+    // have to be listed before it too.
+    pub requires: &'static [&'static str],
+}
+
+type FilePatch<'r, 's> = dyn FnMut(&mut FstEntryFile<'r>) -> Result<(), String> + 's;
+type ResourcePatch<'r, 's> = dyn FnMut(&mut Resource<'r>) -> Result<(), String> + Send + 's;
+type SclyPatch<'r, 's> = dyn FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + Send + 's;
+// The SCLY patches for a room, along with the index of the named patch each is part of
+type RoomPatches<'r, 's> = Vec<(usize, Box<SclyPatch<'r, 's>>)>;
 pub struct PrimePatcher<'r, 's>
 {
-    file_patches: HashMap<&'s [u8], Box<dyn FnMut(&mut FstEntryFile<'r>) -> Result<(), String> + 's>>,
+    patches: &'static [PatchInfo],
+    version: Version,
+    disabled_patches: &'s [String],
+    // The patches something has been added for, in the order they were first added
+    added_patches: Vec<usize>,
+    // Objects touched by the patches that are only known once they're added, e.g. because they
+    // come from the config
+    added_objects: Vec<(usize, u32, u32)>,
+
+    // The file and resource patches are stored along with the index of the named patch they're
+    // part of
+    file_patches: HashMap<&'s [u8], Vec<(usize, Box<FilePatch<'r, 's>>)>>,
     // TODO: Come up with a better data structure for this. A per PAK list of patches, for example.
-    resource_patches: Vec<(ResourceKey<'s>, Box<dyn FnMut(&mut Resource<'r>) -> Result<(), String> + 's>)>,
-    scly_patches: Vec<(MreaKey<'s>, Vec<Box<SclyPatch<'r, 's>>>)>,
+    resource_patches: Vec<(ResourceKey<'s>, usize, Box<ResourcePatch<'r, 's>>)>,
+    scly_patches: Vec<(MreaKey<'s>, RoomPatches<'r, 's>)>,
 }
 
 pub struct PatcherState
 {
-    pub fresh_instance_id_range: RangeFrom<u32>,
+    instance_ids: InstanceIdAllocator,
+}
+
+impl PatcherState
+{
+    /// A new instance id for an object being added to `layer` of the area being patched
+    pub fn fresh_instance_id(&mut self, layer: u32) -> u32
+    {
+        self.instance_ids.allocate(layer)
+    }
+}
+
+/// Hands out instance ids in the form the game's own objects use: the layer in the top 6 bits,
+/// the area's index in its MLVL in the next 10 and an index in the bottom 16. The game ignores
+/// the layer when looking objects up, so the index is unique across the whole area rather than
+/// just the layer.
+#[derive(Default)]
+struct InstanceIdAllocator
+{
+    // The area the SCLY patch being run is for
+    area_index: u32,
+    // For every area, an index greater than any used in it so far
+    next_indices: HashMap<u32, u32>,
+}
+
+impl InstanceIdAllocator
+{
+    // Called before each patch for an area, as the earlier ones may have added objects with
+    // ids of their own
+    fn enter_area<I>(&mut self, area_index: u32, instance_ids: I)
+        where I: Iterator<Item = u32>
+    {
+        let next_index = instance_ids.map(|id| (id & 0xFFFF) + 1).max().unwrap_or(0);
+        let entry = self.next_indices.entry(area_index).or_insert(0);
+        *entry = cmp::max(*entry, next_index);
+        self.area_index = area_index;
+    }
+
+    fn allocate(&mut self, layer: u32) -> u32
+    {
+        assert!(layer < 64, "Instance ids can't refer to layer {}", layer);
+        let index = self.next_indices.get_mut(&self.area_index).unwrap();
+        assert!(*index <= 0xFFFF, "Area {} has run out of instance ids", self.area_index);
+        let id = (layer << 26) | (self.area_index << 16) | *index;
+        *index += 1;
+        id
+    }
+}
+
+// The patches for a single PAK, borrowed from the PrimePatcher so they can be run on their own
+// thread
+struct PakPatches<'a, 'r, 's>
+{
+    resource_patches: Vec<&'a mut (ResourceKey<'s>, usize, Box<ResourcePatch<'r, 's>>)>,
+    scly_patches: Vec<&'a mut (MreaKey<'s>, RoomPatches<'r, 's>)>,
 }
 
 impl<'r, 's> PrimePatcher<'r, 's>
 {
-    pub fn new() -> PrimePatcher<'r, 's>
+    /// A patcher that can apply any of `patches` that are for `version`, apart from the ones
+    /// named in `disabled_patches`
+    pub fn new(patches: &'static [PatchInfo], version: Version, disabled_patches: &'s [String])
+        -> PrimePatcher<'r, 's>
     {
+        for (i, patch) in patches.iter().enumerate() {
+            assert!(
+                patches[..i].iter().all(|p| p.name != patch.name),
+                "There is more than one patch named \"{}\"",
+                patch.name,
+            );
+            for required in patch.requires {
+                assert!(
+                    patches[..i].iter().any(|p| p.name == *required),
+                    "The \"{}\" patch has to be listed after the \"{}\" patch it requires",
+                    patch.name,
+                    required,
+                );
+            }
+        }
         PrimePatcher {
+            patches,
+            version,
+            disabled_patches,
+            added_patches: Vec::new(),
+            added_objects: Vec::new(),
             file_patches: HashMap::new(),
             resource_patches: Vec::new(),
             scly_patches: Vec::new(),
         }
     }
 
-    pub fn add_file_patch<F>(&mut self, name: &'s [u8], f: F)
+    fn patch_index(&self, name: &str) -> Option<usize>
+    {
+        self.patches.iter().position(|p| p.name == name)
+    }
+
+    fn add_to_patch(&mut self, name: &str) -> usize
+    {
+        let idx = self.patch_index(name)
+            .unwrap_or_else(|| panic!("There is no patch named \"{}\"", name));
+        if !self.added_patches.contains(&idx) {
+            self.added_patches.push(idx);
+        }
+        idx
+    }
+
+    /// Adds a patch for a file on the disc to the patch named `patch`. It replaces any file
+    /// patches added for the same file before it.
+    pub fn add_file_patch<F>(&mut self, patch: &str, name: &'s [u8], f: F)
         where F: FnMut(&mut FstEntryFile<'r>) -> Result<(), String> + 's
     {
-        self.file_patches.insert(name, Box::new(f));
+        let idx = self.add_to_patch(patch);
+        self.file_patches.entry(name).or_default().push((idx, Box::new(f)));
     }
 
     pub fn add_resource_patch<F>(
         &mut self,
+        patch: &str,
         (paks, res_id, fourcc): (&'_ [&'s [u8]], u32, FourCC),
         f: F,
     )
-        where F: Clone + FnMut(&mut Resource<'r>) -> Result<(), String> + 's
+        where F: Clone + FnMut(&mut Resource<'r>) -> Result<(), String> + Send + 's
     {
+        let idx = self.add_to_patch(patch);
         for pak_name in paks {
             let key = ResourceKey {
                 pak_name,
                 kind: fourcc,
                 id: res_id,
             };
-            self.resource_patches.push((key, Box::new(f.clone())));
+            self.resource_patches.push((key, idx, Box::new(f.clone())));
         }
     }
 
-    pub fn add_scly_patch<F>(&mut self, (pak_name, room_id): (&'s [u8], u32), f: F)
-        where F: FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + 's
+    pub fn add_scly_patch<F>(&mut self, patch: &str, (pak_name, room_id): (&'s [u8], u32), f: F)
+        where F: FnMut(&mut PatcherState, &mut MlvlArea<'r, '_, '_, '_>) -> Result<(), String> + Send + 's
     {
+        let idx = self.add_to_patch(patch);
         let key = MreaKey { pak_name, room_id, };
         if let Some((_, v)) = self.scly_patches.iter_mut().find(|p| p.0 == key) {
-            v.push(Box::new(f));
+            v.push((idx, Box::new(f)));
         } else {
-            self.scly_patches.push((key, vec![Box::new(f)]));
+            self.scly_patches.push((key, vec![(idx, Box::new(f))]));
         }
     }
 
-    pub fn run(&mut self, gc_disc: &mut GcDisc<'r>) -> Result<(), String>
+    /// Records objects in the MREA `room_id` that the patch named `patch` changes, for when they
+    /// can't be listed in its `PatchInfo`
+    pub fn add_touched_objects<I>(&mut self, patch: &str, room_id: u32, instance_ids: I)
+        where I: IntoIterator<Item = u32>
     {
-        let mut patcher_state = PatcherState {
-            fresh_instance_id_range: 0xDEADBABE..
+        let idx = self.add_to_patch(patch);
+        self.added_objects.extend(instance_ids.into_iter().map(|id| (idx, room_id, id)));
+    }
+
+    // Whether patch `a` requires patch `b`, directly or through its other prerequisites
+    fn requires(&self, a: usize, b: usize) -> bool
+    {
+        self.patches[a].requires.iter()
+            .map(|name| self.patch_index(name).unwrap())
+            .any(|i| i == b || self.requires(i, b))
+    }
+
+    /// Works out which of the patches that were added will be applied. Fails if a disabled
+    /// patch doesn't exist, if a patch's prerequisites won't be applied before it or if two
+    /// patches change the same object without one requiring the other.
+    fn enabled_patches(&self) -> Result<Vec<bool>, String>
+    {
+        for name in self.disabled_patches {
+            if self.patch_index(name).is_none() {
+                Err(format!("Cannot disable \"{}\", there is no patch with that name", name))?
+            }
+        }
+        let is_for_version = |patch: &PatchInfo| {
+            patch.versions.is_empty() || patch.versions.contains(&self.version)
+        };
+        let is_disabled = |patch: &PatchInfo| {
+            self.disabled_patches.iter().any(|name| name == patch.name)
         };
 
+        let mut enabled = vec![false; self.patches.len()];
+        for &i in &self.added_patches {
+            enabled[i] = is_for_version(&self.patches[i]) && !is_disabled(&self.patches[i]);
+        }
+
+        for (order, &i) in self.added_patches.iter().enumerate() {
+            if !enabled[i] {
+                continue;
+            }
+            let patch = &self.patches[i];
+            for required_name in patch.requires {
+                let required_idx = self.patch_index(required_name).unwrap();
+                let required = &self.patches[required_idx];
+                if !is_for_version(required) {
+                    continue;
+                }
+                if is_disabled(required) {
+                    Err(format!(
+                        "The \"{}\" patch requires the \"{}\" patch, which has been disabled",
+                        patch.name,
+                        required.name,
+                    ))?
+                }
+                match self.added_patches.iter().position(|&j| j == required_idx) {
+                    Some(required_order) if required_order < order => (),
+                    Some(_) => Err(format!(
+                        "The \"{}\" patch was added before the \"{}\" patch it requires",
+                        patch.name,
+                        required.name,
+                    ))?,
+                    None => Err(format!(
+                        "The \"{}\" patch requires the \"{}\" patch, which isn't being applied",
+                        patch.name,
+                        required.name,
+                    ))?,
+                }
+            }
+        }
+
+        let listed_objects = self.patches.iter()
+            .enumerate()
+            .flat_map(|(i, patch)| patch.objects.iter().map(move |&(room_id, ids)| (i, room_id, ids)))
+            .flat_map(|(i, room_id, ids)| ids.iter().map(move |&id| (i, room_id, id)));
+        let mut touched_by = HashMap::<_, Vec<usize>>::new();
+        for (i, room_id, instance_id) in listed_objects.chain(self.added_objects.iter().copied()) {
+            if !enabled[i] {
+                continue;
+            }
+            // The game ignores the layer when looking objects up
+            let key = (room_id, instance_id & 0x3FFFFFF);
+            let patches = touched_by.entry(key).or_default();
+            let conflict = patches.iter()
+                .find(|&&j| j != i && !self.requires(i, j) && !self.requires(j, i));
+            if let Some(&j) = conflict {
+                Err(format!(
+                    "The \"{}\" and \"{}\" patches both change object 0x{:08X} in room 0x{:08X}",
+                    self.patches[j].name,
+                    self.patches[i].name,
+                    instance_id,
+                    room_id,
+                ))?
+            }
+            patches.push(i);
+        }
+
+        Ok(enabled)
+    }
+
+    /// Applies the patches, returning the names of the ones that were applied in the order
+    /// they were added
+    pub fn run(&mut self, gc_disc: &mut GcDisc<'r>) -> Result<Vec<&'static str>, String>
+    {
+        let enabled = self.enabled_patches()?;
+        for patches in self.file_patches.values_mut() {
+            patches.retain(|p| enabled[p.0]);
+        }
+        self.file_patches.retain(|_, patches| !patches.is_empty());
+        self.resource_patches.retain(|p| enabled[p.1]);
+        for (_, patches) in self.scly_patches.iter_mut() {
+            patches.retain(|p| enabled[p.0]);
+        }
+        self.scly_patches.retain(|(_, patches)| !patches.is_empty());
+
         let files_to_patch = self.file_patches.keys()
             .map(|k| *k)
             .chain(self.scly_patches.iter().map(|p| p.0.pak_name))
@@ -96,9 +344,12 @@ impl<'r, 's> PrimePatcher<'r, 's>
         let files = gc_disc.file_system_root.dir_files_iter_mut()
             .filter(|(path, _)| files_to_patch.contains(&path[..]));
 
-
+        // File patches can replace a PAK entirely, so they're all run before any of the patches
+        // for the PAKs' contents
+        let mut paks = vec![];
         for (name, fst_entry) in files {
-            if let Some(patch) = self.file_patches.get_mut(&name[..]) {
+            if let Some(patches) = self.file_patches.get_mut(&name[..]) {
+                let (_, patch) = patches.last_mut().unwrap();
                 fst_entry.guess_kind();
                 patch(&mut fst_entry.file_mut().unwrap())?
             }
@@ -116,62 +367,411 @@ impl<'r, 's> PrimePatcher<'r, 's>
                 structs::FstEntryFile::Pak(pak) => pak,
                 _ => panic!(),
             };
+            paks.push((name, pak));
+        }
 
-            // Frequently when patching the scripting for a room, we want to modify both the MREA
-            // for that room and the MLVL for the whole region at the same. The borrow checker
-            // doesn't allow us to hold mutable references to both at the same time, so create a
-            // copy on the stack to modify and then overwrite the canonical MLVL at the end of the
-            // PAK.
-            let scly_patch_exists = self.scly_patches.iter().any(|p| p.0.pak_name == &name[..]);
-            let mut mlvl_editor = if scly_patch_exists {
-
-                // If the pak has few or no resources in it, assume it's been gutted (e.g. frigate skip) //
-                // and don't bother looking for a mlvl resource inside //
-                if pak.resources.len() as u32 <= 1 {
-                    continue;
-                }
+        let mut pak_patches = HashMap::new();
+        for patch in self.resource_patches.iter_mut() {
+            pak_patches.entry(patch.0.pak_name)
+                .or_insert_with(|| PakPatches { resource_patches: vec![], scly_patches: vec![] })
+                .resource_patches.push(patch);
+        }
+        for patch in self.scly_patches.iter_mut() {
+            pak_patches.entry(patch.0.pak_name)
+                .or_insert_with(|| PakPatches { resource_patches: vec![], scly_patches: vec![] })
+                .scly_patches.push(patch);
+        }
+
+        // The PAKs don't share anything, so they're patched concurrently. The instance ids a
+        // patch receives only depend on its area and the patches run on it before, not on how
+        // the threads happen to be scheduled.
+        let results = thread::scope(|s| {
+            let handles = paks.into_iter()
+                .map(|(name, pak)| {
+                    let patches = pak_patches.remove(&name[..]).unwrap();
+                    s.spawn(move || Self::patch_pak(&name, pak, patches))
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect::<Vec<_>>()
+        });
+        results.into_iter().collect::<Result<(), String>>()?;
 
-                let mlvl = pak.resources.iter()
-                    .find(|i| i.fourcc() == reader_writer::FourCC::from_bytes(b"MLVL"))
-                    .unwrap()
-                    .kind.as_mlvl().unwrap().into_owned();
-                Some(MlvlEditor::new(mlvl))
-            } else {
-                None
+        Ok(self.added_patches.iter()
+            .filter(|&&i| enabled[i])
+            .map(|&i| self.patches[i].name)
+            .collect())
+    }
+
+    fn patch_pak(
+        name: &[u8],
+        pak: &mut Pak<'r>,
+        mut patches: PakPatches<'_, 'r, 's>,
+    ) -> Result<(), String>
+    {
+        let mut patcher_state = PatcherState {
+            instance_ids: InstanceIdAllocator::default(),
+        };
+
+        // Frequently when patching the scripting for a room, we want to modify both the MREA
+        // for that room and the MLVL for the whole region at the same. The borrow checker
+        // doesn't allow us to hold mutable references to both at the same time, so create a
+        // copy on the stack to modify and then overwrite the canonical MLVL at the end of the
+        // PAK.
+        let mut mlvl_editor = if !patches.scly_patches.is_empty() {
+
+            // If the pak has few or no resources in it, assume it's been gutted (e.g. frigate skip) //
+            // and don't bother looking for a mlvl resource inside //
+            if pak.resources.len() as u32 <= 1 {
+                return Ok(());
+            }
+
+            let mlvl = pak.resources.iter()
+                .find(|i| i.fourcc() == reader_writer::FourCC::from_bytes(b"MLVL"))
+                .unwrap()
+                .kind.as_mlvl().unwrap().into_owned();
+            Some(MlvlEditor::new(mlvl))
+        } else {
+            None
+        };
+
+        let mut cursor = pak.resources.cursor();
+        while cursor.peek().is_some() {
+            let mut cursor = cursor.cursor_advancer();
+            let res_key = ResourceKey {
+                pak_name: name,
+                kind: cursor.peek().unwrap().fourcc(),
+                id: cursor.peek().unwrap().file_id,
             };
 
-            let mut cursor = pak.resources.cursor();
-            while cursor.peek().is_some() {
-                let mut cursor = cursor.cursor_advancer();
-                let res_key = ResourceKey {
-                    pak_name: &name[..],
-                    kind: cursor.peek().unwrap().fourcc(),
-                    id: cursor.peek().unwrap().file_id,
-                };
-
-                for (patch_key, patch_func) in self.resource_patches.iter_mut() {
-                    if *patch_key == res_key {
-                        patch_func(cursor.value().unwrap())?;
-                    }
+            for (patch_key, _, patch_func) in patches.resource_patches.iter_mut().map(|p| &mut **p) {
+                if *patch_key == res_key {
+                    patch_func(cursor.value().unwrap())?;
                 }
+            }
 
-                let mrea_key = MreaKey {
-                    pak_name: &name[..],
-                    room_id: cursor.peek().unwrap().file_id,
-                };
-                if let Some((_, patches)) = self.scly_patches.iter_mut().find(|p| p.0 == mrea_key) {
-                    let mut mlvl_area = mlvl_editor.as_mut().unwrap().get_area(&mut cursor);
-                    for patch in patches.iter_mut() {
-                        patch(&mut patcher_state, &mut mlvl_area)?;
-                    }
+            let mrea_key = MreaKey {
+                pak_name: name,
+                room_id: cursor.peek().unwrap().file_id,
+            };
+            if let Some((_, patches)) = patches.scly_patches.iter_mut().map(|p| &mut **p).find(|p| p.0 == mrea_key) {
+                let mut mlvl_area = mlvl_editor.as_mut().unwrap().get_area(&mut cursor);
+                for (_, patch) in patches.iter_mut() {
+                    patcher_state.instance_ids.enter_area(
+                        mlvl_area.area_index,
+                        mlvl_area.instance_ids().into_iter(),
+                    );
+                    patch(&mut patcher_state, &mut mlvl_area)?;
                 }
+            }
 
-                if cursor.peek().unwrap().fourcc() == b"MLVL".into() && mlvl_editor.is_some() {
-                    let mlvl = mlvl_editor.take().unwrap().mlvl;
-                    cursor.value().unwrap().kind = ResourceKind::Mlvl(mlvl);
-                }
+            if cursor.peek().unwrap().fourcc() == b"MLVL".into() && mlvl_editor.is_some() {
+                let mlvl = mlvl_editor.take().unwrap().mlvl;
+                cursor.value().unwrap().kind = ResourceKind::Mlvl(mlvl);
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn instance_ids_are_unique_within_an_area()
+    {
+        let mut allocator = InstanceIdAllocator::default();
+        allocator.enter_area(3, [0x0003_0005, 0x0403_0010].iter().copied());
+        assert_eq!(allocator.allocate(0), 0x0003_0011);
+        assert_eq!(allocator.allocate(2), 0x0803_0012);
+
+        // Objects added with ids of their own are skipped over
+        allocator.enter_area(3, [0x0003_0005, 0x0003_0020].iter().copied());
+        assert_eq!(allocator.allocate(1), 0x0403_0021);
+
+        // Ids already handed out aren't reused, even if their objects weren't added
+        allocator.enter_area(3, [0x0003_0005].iter().copied());
+        assert_eq!(allocator.allocate(0), 0x0003_0022);
+
+        allocator.enter_area(7, std::iter::empty());
+        assert_eq!(allocator.allocate(0), 0x0007_0000);
+    }
+
+    const TEST_PATCHES: &[PatchInfo] = &[
+        PatchInfo {
+            name: "a",
+            versions: &[],
+            objects: &[(1, &[0x10, 0x11])],
+            requires: &[],
+        },
+        PatchInfo {
+            name: "b",
+            versions: &[Version::Pal],
+            objects: &[(1, &[0x0400_0010])],
+            requires: &[],
+        },
+        PatchInfo {
+            name: "c",
+            versions: &[],
+            objects: &[(1, &[0x11])],
+            requires: &["a"],
+        },
+        PatchInfo {
+            name: "d",
+            versions: &[],
+            objects: &[(2, &[0x11])],
+            requires: &["b", "c"],
+        },
+        PatchInfo {
+            name: "e",
+            versions: &[],
+            objects: &[],
+            requires: &[],
+        },
+    ];
+
+    fn add_test_patches(patcher: &mut PrimePatcher, names: &[&str])
+    {
+        for name in names {
+            patcher.add_scly_patch(name, (b"Metroid2.pak", 0), |_, _| Ok(()));
+        }
+    }
+
+    #[test]
+    fn patches_are_filtered_by_version_and_config()
+    {
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &[]);
+        add_test_patches(&mut patcher, &["a", "b", "c", "d"]);
+        assert_eq!(patcher.enabled_patches(), Ok(vec![true, false, true, true, false]));
+
+        let disabled = vec!["d".to_string()];
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &disabled);
+        add_test_patches(&mut patcher, &["a", "c", "d"]);
+        assert_eq!(patcher.enabled_patches(), Ok(vec![true, false, true, false, false]));
+
+        // There's no patch named "f"
+        let disabled = vec!["f".to_string()];
+        let patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &disabled);
+        assert!(patcher.enabled_patches().is_err());
+    }
+
+    #[test]
+    fn prerequisites_are_applied_first()
+    {
+        let disabled = vec!["a".to_string()];
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &disabled);
+        add_test_patches(&mut patcher, &["a", "c"]);
+        assert!(patcher.enabled_patches().is_err());
+
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &[]);
+        add_test_patches(&mut patcher, &["c", "a"]);
+        assert!(patcher.enabled_patches().is_err());
+
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &[]);
+        add_test_patches(&mut patcher, &["c"]);
+        assert!(patcher.enabled_patches().is_err());
+    }
+
+    #[test]
+    fn patches_changing_the_same_object_conflict()
+    {
+        // "b" changes an object "a" does, and neither requires the other
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::Pal, &[]);
+        add_test_patches(&mut patcher, &["a", "b"]);
+        assert!(patcher.enabled_patches().is_err());
+
+        let disabled = vec!["b".to_string()];
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::Pal, &disabled);
+        add_test_patches(&mut patcher, &["a", "b"]);
+        assert!(patcher.enabled_patches().is_ok());
+
+        // Objects only known once a patch is added are checked too, and patches may share
+        // objects with the ones they require indirectly
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &[]);
+        add_test_patches(&mut patcher, &["a", "c", "d"]);
+        assert!(patcher.enabled_patches().is_ok());
+        patcher.add_touched_objects("a", 2, vec![0x0800_0011]);
+        assert!(patcher.enabled_patches().is_ok());
+        patcher.add_touched_objects("e", 2, vec![0x11]);
+        assert!(patcher.enabled_patches().is_err());
+    }
+
+    const TEST_PAK_NAMES: &[&[u8]] = &[
+        b"Metroid1.pak", b"Metroid2.pak", b"Metroid3.pak", b"Metroid4.pak",
+        b"Metroid5.pak", b"Metroid6.pak", b"Metroid7.pak", b"Metroid8.pak",
+        b"Metroid9.pak", b"MiscData.pak",
+    ];
+
+    // A disc image holding a default.dol and the PAKs in TEST_PAK_NAMES, each with two
+    // resources
+    fn test_disc_image() -> Vec<u8>
+    {
+        fn put_u32(buf: &mut [u8], offset: usize, x: u32)
+        {
+            buf[offset..offset + 4].copy_from_slice(&x.to_be_bytes());
+        }
+        fn pad(buf: &mut Vec<u8>)
+        {
+            buf.resize((buf.len() + 31) & !31, 0);
+        }
+
+        let mut files = vec![(&b"default.dol"[..], vec![0x44; 64])];
+        for (i, name) in TEST_PAK_NAMES.iter().enumerate() {
+            let mut pak = vec![0; 16];
+            put_u32(&mut pak, 0, 0x00030005);
+            put_u32(&mut pak, 12, 2);
+            for j in 0..2u32 {
+                pak.extend_from_slice(&0u32.to_be_bytes());
+                pak.extend_from_slice(b"TEST");
+                pak.extend_from_slice(&(0x1000 + j).to_be_bytes());
+                pak.extend_from_slice(&32u32.to_be_bytes());
+                pak.extend_from_slice(&(64 + 32 * j).to_be_bytes());
+            }
+            pad(&mut pak);
+            for j in 0..64 {
+                pak.push((i * 64 + j) as u8);
+            }
+            files.push((name, pak));
+        }
+
+        let fst_offset = 0x2460;
+        let mut strings = vec![0];
+        let mut entries = vec![0; 12];
+        entries[0] = 1;
+        put_u32(&mut entries, 8, files.len() as u32 + 1);
+        let fst_length = entries.len() + 12 * files.len()
+            + files.iter().map(|(name, _)| name.len() + 1).sum::<usize>() + 1;
+        let mut offset = (fst_offset + fst_length + 31) & !31;
+        for (name, data) in &files {
+            let mut entry = vec![0; 12];
+            entry[2..4].copy_from_slice(&(strings.len() as u16).to_be_bytes());
+            put_u32(&mut entry, 4, offset as u32);
+            put_u32(&mut entry, 8, data.len() as u32);
+            entries.extend_from_slice(&entry);
+            strings.extend_from_slice(name);
+            strings.push(0);
+            offset += (data.len() + 31) & !31;
+        }
+
+        let mut image = vec![0; 0x2440];
+        image[..6].copy_from_slice(b"GM8E01");
+        put_u32(&mut image, 0x1C, 0xC2339F3D);
+        put_u32(&mut image, 0x424, fst_offset as u32);
+        put_u32(&mut image, 0x428, fst_length as u32);
+        put_u32(&mut image, 0x42C, fst_length as u32);
+        image.resize(fst_offset, 0);
+        image.extend_from_slice(&entries);
+        image.extend_from_slice(&strings);
+        for (_, data) in &files {
+            pad(&mut image);
+            image.extend_from_slice(data);
+        }
+        pad(&mut image);
+        image
+    }
+
+    // Records what's written to a disc as (offset, bytes) pairs, as most of a written disc
+    // is skipped over
+    #[derive(Default)]
+    struct SparseImage
+    {
+        pos: u64,
+        chunks: Vec<(u64, Vec<u8>)>,
+    }
+
+    impl std::io::Write for SparseImage
+    {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+        {
+            match self.chunks.last_mut() {
+                Some((start, chunk)) if *start + chunk.len() as u64 == self.pos =>
+                    chunk.extend_from_slice(buf),
+                _ => self.chunks.push((self.pos, buf.to_vec())),
+            }
+            self.pos += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()>
+        {
+            Ok(())
+        }
+    }
+
+    impl std::io::Seek for SparseImage
+    {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64>
+        {
+            match pos {
+                std::io::SeekFrom::Current(n) => self.pos = (self.pos as i64 + n) as u64,
+                _ => unimplemented!(),
+            }
+            Ok(self.pos)
+        }
+    }
+
+    struct NoProgress;
+    impl structs::ProgressNotifier for NoProgress
+    {
+        fn notify_total_bytes(&mut self, _: usize) { }
+        fn notify_writing_file(&mut self, _: &reader_writer::CStr, _: usize) { }
+        fn notify_writing_header(&mut self) { }
+        fn notify_flushing_to_disk(&mut self) { }
+    }
+
+    fn patch_test_disc(image: &[u8]) -> Vec<(u64, Vec<u8>)>
+    {
+        let mut gc_disc: GcDisc = reader_writer::Reader::new(image).read(());
+        let mut patcher = PrimePatcher::new(TEST_PATCHES, Version::NtscU0_00, &[]);
+        for (i, name) in TEST_PAK_NAMES.iter().enumerate() {
+            patcher.add_resource_patch("e", (&[*name], 0x1000, b"TEST".into()), move |res| {
+                res.kind = ResourceKind::External(vec![i as u8; 40], b"TEST".into());
+                Ok(())
+            });
+        }
+        patcher.run(&mut gc_disc).unwrap();
+        let mut written = SparseImage::default();
+        gc_disc.write(&mut written, &mut NoProgress).unwrap();
+        written.chunks
+    }
+
+    #[test]
+    fn patching_is_deterministic()
+    {
+        let image = test_disc_image();
+        let first = patch_test_disc(&image);
+        assert_eq!(first, patch_test_disc(&image));
+
+        // Every PAK's patched resource was written out, though there are more PAKs than fit in
+        // one batch
+        let written: Vec<u8> = first.into_iter().flat_map(|(_, chunk)| chunk).collect();
+        for i in 0..TEST_PAK_NAMES.len() {
+            assert!(written.windows(40).any(|w| w.iter().all(|b| *b == i as u8)));
+        }
+    }
+
+    #[test]
+    fn game_patches_are_consistent()
+    {
+        let versions = [
+            Version::NtscU0_00,
+            Version::NtscU0_02,
+            Version::NtscJ,
+            Version::Pal,
+            Version::NtscUTrilogy,
+            Version::NtscJTrilogy,
+            Version::PalTrilogy,
+        ];
+        for &version in &versions {
+            let mut patcher = PrimePatcher::new(crate::patches::PATCHES, version, &[]);
+            crate::patches::add_room_info_touched_objects(&mut patcher);
+            for patch in crate::patches::PATCHES {
+                patcher.add_file_patch(patch.name, b"default.dol", |_| Ok(()));
+            }
+            assert!(patcher.enabled_patches().is_ok(), "{}", version);
+        }
+    }
+}
@@ -13,22 +13,35 @@ use rand::{
 
 use crate::patch_config::{
     ArtifactHintBehavior,
+    ArtifactTempleConfig,
+    EnemyStatsConfig,
     MapState,
     IsoFormat,
     PatchConfig,
+    PickupBundle,
+    ProgressiveItem,
     GameBanner,
+    RemotePickup,
     LevelConfig,
+    ScriptEdit,
+    SpawnPointConfig,
+    TempleBoss,
 };
 
 use crate::{
-    custom_assets::{custom_asset_ids, collect_game_resources},
+    custom_assets::{self, custom_asset_ids, collect_game_resources},
     dol_patcher::DolPatcher,
     ciso_writer::CisoWriter,
     elevators::{Elevator, SpawnRoom, SpawnRoomData, World},
+    enemy_weaknesses::{self, EnemyWeaknesses},
     gcz_writer::GczWriter,
+    logic,
     mlvl_wrapper,
+    multiworld,
+    normal_difficulty,
     pickup_meta::{self, PickupType},
-    patcher::{PatcherState, PrimePatcher},
+    patcher::{PatchInfo, PatcherState, PrimePatcher},
+    spoiler,
     starting_items::StartingItems,
     txtr_conversions::{
         cmpr_compress, cmpr_decompress, huerotate_in_place, VARIA_SUIT_TEXTURES,
@@ -54,7 +67,7 @@ use structs::{res_id, ResId};
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::TryInto,
     ffi::CString,
     fmt,
@@ -86,6 +99,57 @@ fn artifact_layer_change_template<'r>(instance_id: u32, pickup_kind: u32)
     }
 }
 
+/// What activates one of the Artifact Temple's artifact layers. These are indexed by totem
+/// number - 1, which is also the artifact's pickup kind - 29.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ArtifactTempleLayer
+{
+    // Activated by collecting the artifact
+    Artifact,
+    // Active from the start of the game
+    Active,
+    // Activated by defeating the boss
+    Boss(TempleBoss),
+}
+
+fn artifact_temple_layer_number(artifact_idx: usize) -> u32
+{
+    if artifact_idx == 0 {
+        ARTIFACT_OF_TRUTH_REQ_LAYER
+    } else {
+        artifact_idx as u32 + 1
+    }
+}
+
+fn plan_artifact_temple_layers(
+    pickup_layout: &[PickupType],
+    starting_artifacts: &[PickupType],
+    config: &ArtifactTempleConfig,
+)
+    -> Result<[ArtifactTempleLayer; 12], String>
+{
+    // The totems of artifacts the player starts with are active from the beginning
+    let mut layers = [ArtifactTempleLayer::Active; 12];
+    for pt in config.required_artifact_types(pickup_layout) {
+        if !starting_artifacts.contains(&pt) {
+            layers[(pt.pickup_data().kind - 29) as usize] = ArtifactTempleLayer::Artifact;
+        }
+    }
+
+    // Bosses take over the layers of artifacts that aren't needed, starting from the last totem
+    for boss in &config.required_bosses {
+        let layer = layers.iter_mut()
+            .rev()
+            .find(|layer| **layer == ArtifactTempleLayer::Active)
+            .ok_or_else(|| format!(
+                "Not enough unneeded artifacts for {} to be required in the Artifact Temple",
+                boss.name(),
+            ))?;
+        *layer = ArtifactTempleLayer::Boss(*boss);
+    }
+    Ok(layers)
+}
+
 fn post_pickup_relay_template<'r>(instance_id: u32, connections: &'static [structs::Connection])
     -> structs::SclyObject<'r>
 {
@@ -99,10 +163,115 @@ fn post_pickup_relay_template<'r>(instance_id: u32, connections: &'static [struc
     }
 }
 
+fn player_state_change_template<'r>(instance_id: u32, pickup_type: PickupType, active: bool)
+    -> structs::SclyObject<'r>
+{
+    let pickup = pickup_type.pickup_data();
+    structs::SclyObject {
+        instance_id,
+        connections: vec![].into(),
+        property_data: structs::PlayerStateChange {
+            name: b"Randomizer Player State Change\0".as_cstr(),
+
+            active: active as u8,
+            item: pickup.kind,
+            amount: pickup.curr_increase,
+            capacity: pickup.max_increase,
+            control: 0,
+            control_command: 0,
+        }.into(),
+    }
+}
+
+/// The objects that give the first item of a progressive pickup the player doesn't have yet,
+/// and the connections from the pickup that run them. Each item has a state change that gives
+/// it and an inventory activator. The pickup runs the first activator; if the player has its
+/// item, it disables that item's state change, enables the next one and runs the next
+/// activator. The chain stops at the first item the player doesn't have, so only its state
+/// change is still active when the pickup sets all of them off.
+fn progressive_item_objects<'r>(items: &[PickupType], mut fresh_instance_id: impl FnMut() -> u32)
+    -> (Vec<structs::SclyObject<'r>>, Vec<structs::Connection>)
+{
+    let state_change_ids: Vec<u32> = items.iter().map(|_| fresh_instance_id()).collect();
+    let activator_ids: Vec<u32> = items.iter().map(|_| fresh_instance_id()).collect();
+
+    let mut objects = vec![];
+    for (i, pt) in items.iter().enumerate() {
+        let mut connections = vec![
+            structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::DEACTIVATE,
+                target_object_id: state_change_ids[i],
+            },
+        ];
+        if i + 1 < items.len() {
+            connections.push(structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::ACTIVATE,
+                target_object_id: state_change_ids[i + 1],
+            });
+            connections.push(structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::ACTION,
+                target_object_id: activator_ids[i + 1],
+            });
+        }
+        objects.push(inventory_activator_template(activator_ids[i], *pt, connections));
+        objects.push(player_state_change_template(state_change_ids[i], *pt, i == 0));
+    }
+
+    let mut connections = vec![
+        structs::Connection {
+            state: structs::ConnectionState::ARRIVED,
+            message: structs::ConnectionMsg::ACTION,
+            target_object_id: activator_ids[0],
+        },
+    ];
+    connections.extend(state_change_ids.iter().map(|id| structs::Connection {
+        state: structs::ConnectionState::ARRIVED,
+        message: structs::ConnectionMsg::SET_TO_ZERO,
+        target_object_id: *id,
+    }));
+    (objects, connections)
+}
+
+// Sends its connections when it receives an ACTION and the player has the item
+fn inventory_activator_template<'r>(
+    instance_id: u32,
+    pickup_type: PickupType,
+    connections: Vec<structs::Connection>,
+)
+    -> structs::SclyObject<'r>
+{
+    structs::SclyObject {
+        instance_id,
+        connections: connections.into(),
+        property_data: structs::SpecialFunction {
+            name: b"Randomizer Inventory Activator\0".as_cstr(),
+            position: [0., 0., 0.].into(),
+            rotation: [0., 0., 0.].into(),
+            type_: 5,
+            unknown0: b"\0".as_cstr(),
+            unknown1: 0.,
+            unknown2: 0.,
+            unknown3: 0.,
+            layer_change_room_id: 0xFFFFFFFF,
+            layer_change_layer_id: 0xFFFFFFFF,
+            item_id: pickup_type.pickup_data().kind,
+            unknown4: 1,
+            unknown5: 0.,
+            unknown6: 0xFFFFFFFF,
+            unknown7: 0xFFFFFFFF,
+            unknown8: 0xFFFFFFFF,
+        }.into(),
+    }
+}
+
 fn build_artifact_temple_totem_scan_strings<R>(
     pickup_layout: &[PickupType],
     rng: &mut R,
     artifact_hints: Option<HashMap<String,String>>,
+    artifact_layers: &[ArtifactTempleLayer; 12],
 )
     -> [String; 12]
     where R: Rng
@@ -178,6 +347,24 @@ fn build_artifact_temple_totem_scan_strings<R>(
         }
     }
 
+    // Totems that don't need their artifact say so instead of giving a hint
+    for pt in PickupType::iter().filter(|pt| pt.is_artifact()) {
+        let i = pt.idx() - PickupType::ArtifactOfLifegiver.idx();
+        let exists = pickup_layout.contains(&pt);
+        match artifact_layers[(pt.pickup_data().kind - 29) as usize] {
+            ArtifactTempleLayer::Boss(boss) => {
+                scan_text[i] = format!(
+                    "This totem will awaken once &push;&main-color=#43CD80;{}&pop; has been defeated.\0",
+                    boss.name(),
+                );
+            },
+            ArtifactTempleLayer::Active if exists => {
+                scan_text[i] = format!("The {} is not needed to open the temple.\0", pt.name());
+            },
+            _ => (),
+        }
+    }
+
     if artifact_hints.is_some() {
         for (artifact_name, hint) in artifact_hints.unwrap() {
             let idx = match artifact_name.trim().to_lowercase().as_str() {
@@ -273,6 +460,23 @@ fn patch_mines_savw_for_phazon_suit_scan(res: &mut structs::Resource)
     Ok(())
 }
 
+fn patch_savw_layer_toggles(res: &mut structs::Resource, layer_toggles: &[structs::LayerToggle])
+    -> Result<(), String>
+{
+    // Only the layers listed in the SAVW have their state saved, so any layer whose default
+    // we override needs to be listed for changes made to it in-game to persist.
+    let savw = res.kind.as_savw_mut().unwrap();
+    let toggles = savw.layer_toggle_array.as_mut_vec();
+    for toggle in layer_toggles {
+        let listed = toggles.iter()
+            .any(|t| t.area_id == toggle.area_id && t.layer_index == toggle.layer_index);
+        if !listed {
+            toggles.push(toggle.clone());
+        }
+    }
+    Ok(())
+}
+
 #[derive(Copy, Clone, Debug)]
 enum MaybeObfuscatedPickup
 {
@@ -338,17 +542,120 @@ impl MaybeObfuscatedPickup
     }
 }
 
+fn progressive_item_hudmemo_text(progressive_item: &ProgressiveItem) -> String
+{
+    format!("{} acquired!", progressive_item.name)
+}
+
+fn expansion_hudmemo_text(pickup_type: PickupType, amount: u32) -> String
+{
+    match pickup_type {
+        PickupType::EnergyTank if amount == 1 => "Energy Tank acquired!".to_string(),
+        PickupType::EnergyTank => format!("{} Energy Tanks acquired!", amount),
+        PickupType::Missile => format!(
+            "Missile Expansion acquired! Capacity increased by {}.", amount),
+        _ => format!("{} acquired! Capacity increased by {}.", pickup_type.name(), amount),
+    }
+}
+
+fn remote_pickup_hudmemo_text(remote_pickup: &RemotePickup) -> String
+{
+    format!("{} sent to player {}!", remote_pickup.pickup.name(), remote_pickup.player)
+}
+
+/// The text of every hudmemo that depends on the config, in the order their STRGs are
+/// numbered from `GENERATED_HUDMEMO_STRG_START`
+fn generated_hudmemos(config: &PatchConfig) -> Vec<String>
+{
+    let mut hudmemos: Vec<_> = config.progressive_items.iter()
+        .map(progressive_item_hudmemo_text)
+        .collect();
+    for (location_idx, &pickup_type) in config.layout.pickups.iter().enumerate() {
+        let text = if let Some(remote_pickup) = config.remote_pickup(location_idx) {
+            remote_pickup_hudmemo_text(&remote_pickup)
+        } else if let Some(amount) = config.expansion_amount(location_idx, pickup_type) {
+            expansion_hudmemo_text(pickup_type, amount)
+        } else {
+            continue
+        };
+        if !hudmemos.contains(&text) {
+            hudmemos.push(text);
+        }
+    }
+    hudmemos
+}
+
+// The parts of the PatchConfig the pickup patches use. The PatchConfig itself holds the disc's
+// files, so it can't be shared with the threads the PAKs are patched on.
+#[derive(Clone, Copy)]
+struct PickupPatchConfig<'a>
+{
+    progressive_items: &'a [ProgressiveItem],
+    pickup_bundles: &'a [PickupBundle],
+    obfuscate_items: bool,
+    skip_hudmenus: bool,
+}
+
+impl<'a> PickupPatchConfig<'a>
+{
+    fn new(config: &'a PatchConfig) -> Self
+    {
+        PickupPatchConfig {
+            progressive_items: &config.progressive_items,
+            pickup_bundles: &config.pickup_bundles,
+            obfuscate_items: config.obfuscate_items,
+            skip_hudmenus: config.skip_hudmenus,
+        }
+    }
+}
+
 fn modify_pickups_in_mrea<'r>(
     ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     pickup_type: PickupType,
     pickup_location: pickup_meta::PickupLocation,
     game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
-    config: &PatchConfig,
+    config: PickupPatchConfig,
+    artifact_layers: &[ArtifactTempleLayer; 12],
+    expansion_amount: Option<u32>,
+    generated_hudmemos: &[String],
+    // Another player's item, and the index of the location it's at
+    remote_pickup: Option<(usize, RemotePickup)>,
 ) -> Result<(), String>
 {
     let location_idx = 0;
 
+    // Progressive pickups look like the first of their items and give nothing by themselves.
+    // Progressive items and bundles are this player's settings, so another player's item is
+    // never one of them.
+    let layout_pickup_type = match pickup_type {
+        PickupType::ShinyMissile => PickupType::Missile,
+        pt => pt,
+    };
+    let progressive_item = config.progressive_items.iter()
+        .filter(|_| remote_pickup.is_none())
+        .find(|progressive_item| progressive_item.items.contains(&layout_pickup_type));
+    let bundle = config.pickup_bundles.iter()
+        .filter(|_| remote_pickup.is_none())
+        .find(|bundle| bundle.pickup == layout_pickup_type);
+    let pickup_type = match progressive_item {
+        Some(progressive_item) => progressive_item.items[0],
+        None => pickup_type,
+    };
+
+    // Progressive items, custom expansion amounts and other players' items need their own
+    // hudmemo text
+    let generated_hudmemo = match (progressive_item, expansion_amount, &remote_pickup) {
+        (_, _, Some((_, remote_pickup))) => Some(remote_pickup_hudmemo_text(remote_pickup)),
+        (Some(progressive_item), _, None) => Some(progressive_item_hudmemo_text(progressive_item)),
+        (None, Some(amount), None) => Some(expansion_hudmemo_text(layout_pickup_type, amount)),
+        (None, None, None) => None,
+    };
+    let generated_hudmemo = generated_hudmemo.map(|text| {
+        let i = generated_hudmemos.iter().position(|other| *other == text).unwrap();
+        custom_assets::generated_hudmemo_strg(i)
+    });
+
     let pickup_type = if config.obfuscate_items {
         MaybeObfuscatedPickup::Obfuscated(pickup_type)
     } else {
@@ -368,7 +675,9 @@ fn modify_pickups_in_mrea<'r>(
     let new_layer_idx = area.layer_flags.layer_count as usize - 1;
 
     // Add our custom STRG
-    let hudmemo_dep = if config.skip_hudmenus && !ALWAYS_MODAL_HUDMENUS.contains(&location_idx) {
+    let hudmemo_dep = if let Some(strg) = generated_hudmemo {
+        strg.into()
+    } else if config.skip_hudmenus && !ALWAYS_MODAL_HUDMENUS.contains(&location_idx) {
         pickup_type.skip_hudmemos_strg().into()
     } else {
         pickup_type.hudmemo_strg().into()
@@ -382,7 +691,7 @@ fn modify_pickups_in_mrea<'r>(
     let mut additional_connections = Vec::new();
 
     // Add a post-pickup relay. This is used to support cutscene-skipping
-    let instance_id = ps.fresh_instance_id_range.next().unwrap();
+    let instance_id = ps.fresh_instance_id(new_layer_idx as u32);
     let relay = post_pickup_relay_template(instance_id,
                                             pickup_location.post_pickup_relay_connections);
     layers[new_layer_idx].objects.as_mut_vec().push(relay);
@@ -392,10 +701,12 @@ fn modify_pickups_in_mrea<'r>(
         target_object_id: instance_id,
     });
 
-    // If this is an artifact, insert a layer change function
+    // If this is a needed artifact, insert a layer change function
     let pickup_kind = pickup_type.pickup_data().kind;
-    if pickup_kind >= 29 && pickup_kind <= 40 {
-        let instance_id = ps.fresh_instance_id_range.next().unwrap();
+    let is_needed_artifact = pickup_kind >= 29 && pickup_kind <= 40 && remote_pickup.is_none() &&
+        artifact_layers[(pickup_kind - 29) as usize] == ArtifactTempleLayer::Artifact;
+    if is_needed_artifact {
+        let instance_id = ps.fresh_instance_id(new_layer_idx as u32);
         let function = artifact_layer_change_template(instance_id, pickup_kind);
         layers[new_layer_idx].objects.as_mut_vec().push(function);
         additional_connections.push(structs::Connection {
@@ -405,10 +716,47 @@ fn modify_pickups_in_mrea<'r>(
         });
     }
 
+    if let Some(progressive_item) = progressive_item {
+        let (objects, connections) = progressive_item_objects(
+            &progressive_item.items,
+            || ps.fresh_instance_id(new_layer_idx as u32),
+        );
+        layers[new_layer_idx].objects.as_mut_vec().extend(objects);
+        additional_connections.extend(connections);
+    }
+
+    if let Some(bundle) = bundle {
+        for pt in &bundle.extra_items {
+            let instance_id = ps.fresh_instance_id(new_layer_idx as u32);
+            layers[new_layer_idx].objects.as_mut_vec()
+                .push(player_state_change_template(instance_id, *pt, true));
+            additional_connections.push(structs::Connection {
+                state: structs::ConnectionState::ARRIVED,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: instance_id,
+            });
+        }
+    }
+
     let pickup = layers[pickup_location.location.layer as usize].objects.iter_mut()
         .find(|obj| obj.instance_id ==  pickup_location.location.instance_id)
         .unwrap();
     update_pickup(pickup, pickup_type);
+    if let Some((remote_location_idx, _)) = remote_pickup {
+        // The REL passes the location on to the multiworld bridge and takes the item away again
+        let pickup = pickup.property_data.as_pickup_mut().unwrap();
+        pickup.kind = multiworld::MULTIWORLD_SEND_ITEM as u32;
+        pickup.curr_increase = remote_location_idx as u32 + 1;
+        pickup.max_increase = remote_location_idx as u32 + 1;
+    } else if progressive_item.is_some() {
+        let pickup = pickup.property_data.as_pickup_mut().unwrap();
+        pickup.curr_increase = 0;
+        pickup.max_increase = 0;
+    } else if let Some(amount) = expansion_amount {
+        let pickup = pickup.property_data.as_pickup_mut().unwrap();
+        pickup.curr_increase = amount;
+        pickup.max_increase = amount;
+    }
     if additional_connections.len() > 0 {
         pickup.connections.as_mut_vec().extend_from_slice(&additional_connections);
     }
@@ -417,6 +765,9 @@ fn modify_pickups_in_mrea<'r>(
         .find(|obj| obj.instance_id ==  pickup_location.hudmemo.instance_id)
         .unwrap();
     update_hudmemo(hudmemo, pickup_type, location_idx, config.skip_hudmenus);
+    if let Some(strg) = generated_hudmemo {
+        hudmemo.property_data.as_hud_memo_mut().unwrap().strg = strg;
+    }
 
 
     let location = pickup_location.attainment_audio;
@@ -559,7 +910,8 @@ fn make_elevators_patch<'a>(
                 skip_ending_cinematic = true;
             }
 
-            patcher.add_scly_patch((elv.pak_name.as_bytes(), elv.mrea), move |ps, area| {
+            patcher.add_touched_objects("elevators", elv.mrea, iter::once(elv.scly_id));
+            patcher.add_scly_patch("elevators", (elv.pak_name.as_bytes(), elv.mrea), move |ps, area| {
                 let scly = area.mrea().scly_section_mut();
                 for layer in scly.layers.iter_mut() {
                     let obj = layer.objects.iter_mut()
@@ -583,7 +935,7 @@ fn make_elevators_patch<'a>(
 
                     if let Some(mr_id) = mr_id {
                         layer.objects.as_mut_vec().push(structs::SclyObject {
-                            instance_id: ps.fresh_instance_id_range.next().unwrap(),
+                            instance_id: ps.fresh_instance_id(0),
                             property_data: structs::Timer {
                                 name: b"Auto enable elevator\0".as_cstr(),
 
@@ -610,13 +962,13 @@ fn make_elevators_patch<'a>(
             let room_dest_name = dest.name.replace('\0', "\n");
             let hologram_name = dest.name.replace('\0', " ");
             let control_name = dest.name.replace('\0', " ");
-            patcher.add_resource_patch((&[elv.pak_name.as_bytes()], elv.room_strg, b"STRG".into()), move |res| {
+            patcher.add_resource_patch("elevators", (&[elv.pak_name.as_bytes()], elv.room_strg, b"STRG".into()), move |res| {
                 let string = format!("Transport to {}\u{0}", room_dest_name);
                 let strg = structs::Strg::from_strings(vec![string]);
                 res.kind = structs::ResourceKind::Strg(strg);
                 Ok(())
             });
-            patcher.add_resource_patch((&[elv.pak_name.as_bytes()], elv.hologram_strg, b"STRG".into()), move |res| {
+            patcher.add_resource_patch("elevators", (&[elv.pak_name.as_bytes()], elv.hologram_strg, b"STRG".into()), move |res| {
                 let string = format!(
                     "Access to &main-color=#FF3333;{} &main-color=#89D6FF;granted. Please step into the hologram.\u{0}",
                     hologram_name,
@@ -625,7 +977,7 @@ fn make_elevators_patch<'a>(
                 res.kind = structs::ResourceKind::Strg(strg);
                 Ok(())
             });
-            patcher.add_resource_patch((&[elv.pak_name.as_bytes()], elv.control_strg, b"STRG".into()), move |res| {
+            patcher.add_resource_patch("elevators", (&[elv.pak_name.as_bytes()], elv.control_strg, b"STRG".into()), move |res| {
                 let string = format!(
                     "Transport to &main-color=#FF3333;{}&main-color=#89D6FF; active.\u{0}",
                     control_name,
@@ -640,6 +992,255 @@ fn make_elevators_patch<'a>(
     (skip_frigate, skip_ending_cinematic)
 }
 
+fn json_to_property_value(ty: structs::PropertyType, value: &serde_json::Value)
+    -> Result<structs::PropertyValue, String>
+{
+    use structs::{PropertyType, PropertyValue};
+    let floats = |n: usize| -> Option<Vec<f32>> {
+        let arr = value.as_array()?;
+        if arr.len() != n {
+            return None
+        }
+        arr.iter().map(|v| v.as_f64().map(|f| f as f32)).collect()
+    };
+    let res = match ty {
+        PropertyType::Bool => value.as_bool().map(PropertyValue::Bool),
+        PropertyType::U32 => value.as_u64().map(|i| PropertyValue::U32(i as u32)),
+        PropertyType::Asset => value.as_u64().map(|i| PropertyValue::Asset(i as u32)),
+        PropertyType::F32 => value.as_f64().map(|f| PropertyValue::F32(f as f32)),
+        PropertyType::Vec2 => floats(2).map(|v| PropertyValue::Vec2([v[0], v[1]])),
+        PropertyType::Vec3 => floats(3).map(|v| PropertyValue::Vec3([v[0], v[1], v[2]])),
+        PropertyType::Color => floats(4).map(|v| PropertyValue::Color([v[0], v[1], v[2], v[3]])),
+        PropertyType::String => value.as_str().map(|s| PropertyValue::String(s.to_owned())),
+    };
+    res.ok_or_else(|| format!("{} is not a valid {} value", value, ty))
+}
+
+fn parse_connection(state: &str, message: &str, target: u32) -> Result<structs::Connection, String>
+{
+    Ok(structs::Connection {
+        state: structs::ConnectionState::from_name(state)
+            .ok_or_else(|| format!("Unknown connection state \"{}\"", state))?,
+        message: structs::ConnectionMsg::from_name(message)
+            .ok_or_else(|| format!("Unknown connection message \"{}\"", message))?,
+        target_object_id: target,
+    })
+}
+
+fn patch_room_layers(area: &mut mlvl_wrapper::MlvlArea, layers: &HashMap<u32, bool>)
+    -> Result<(), String>
+{
+    for (layer, active) in layers.iter() {
+        area.set_layer_active(*layer, *active)?;
+    }
+    Ok(())
+}
+
+/// The existing object a script edit changes, if it changes one
+fn script_edit_target(edit: &ScriptEdit) -> Option<u32>
+{
+    match *edit {
+        ScriptEdit::DeleteObject { instance_id } |
+        ScriptEdit::MoveObject { instance_id, .. } |
+        ScriptEdit::AddConnection { instance_id, .. } |
+        ScriptEdit::RemoveConnection { instance_id, .. } |
+        ScriptEdit::SetProperty { instance_id, .. } => Some(instance_id),
+        // Cloning only reads the object
+        ScriptEdit::SetLayerActive { .. } | ScriptEdit::CloneObject { .. } => None,
+    }
+}
+
+/// Whether a key of a level's `rooms` config refers to the room, either by its name or by
+/// its MREA id in hex (needed for rooms whose names aren't unique)
+fn room_key_matches(key: &str, room_info: &pickup_meta::RoomInfo) -> bool
+{
+    match key.strip_prefix("0x").or_else(|| key.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok() == Some(room_info.room_id.to_u32()),
+        None => key == room_info.name,
+    }
+}
+
+fn patch_script_edits(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    edits: &[ScriptEdit],
+) -> Result<(), String>
+{
+    fn find_object<'a, 'r>(layers: &'a mut [structs::SclyLayer<'r>], instance_id: u32)
+        -> Result<(usize, &'a mut structs::SclyObject<'r>), String>
+    {
+        layers.iter_mut()
+            .enumerate()
+            .find_map(|(i, layer)| {
+                layer.objects.iter_mut()
+                    .find(|obj| obj.instance_id == instance_id)
+                    .map(|obj| (i, obj))
+            })
+            .ok_or_else(|| format!("Object 0x{:08X} not found", instance_id))
+    }
+
+    // Every u32 in the object's properties, at any offset, that could be an asset id. Only
+    // ones that are already dependencies of the object's layer are used, so stray matches
+    // are harmless.
+    fn referenced_asset_ids(obj: &structs::SclyObject) -> HashSet<u32>
+    {
+        obj.property_data.to_bytes()
+            .windows(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+            .collect()
+    }
+
+    fn check_layer(layers: &[structs::SclyLayer], layer: u32) -> Result<usize, String>
+    {
+        if (layer as usize) < layers.len() {
+            Ok(layer as usize)
+        } else {
+            Err(format!("Layer {} doesn't exist", layer))
+        }
+    }
+
+    for edit in edits {
+        if let ScriptEdit::SetLayerActive { layer, active } = *edit {
+            area.set_layer_active(layer, active)?;
+            continue;
+        }
+
+        let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
+        match *edit {
+            ScriptEdit::DeleteObject { instance_id } => {
+                let (layer, _) = find_object(layers, instance_id)?;
+                layers[layer].objects.as_mut_vec().retain(|obj| obj.instance_id != instance_id);
+            },
+            ScriptEdit::MoveObject { instance_id, layer: new_layer } => {
+                let new_layer = check_layer(layers, new_layer)?;
+                let (layer, _) = find_object(layers, instance_id)?;
+                let objects = layers[layer].objects.as_mut_vec();
+                let idx = objects.iter().position(|obj| obj.instance_id == instance_id).unwrap();
+                let obj = objects.remove(idx);
+                let asset_ids = referenced_asset_ids(&obj);
+                layers[new_layer].objects.as_mut_vec().push(obj);
+                area.copy_dependencies(layer, new_layer, &asset_ids);
+            },
+            ScriptEdit::AddConnection { instance_id, ref state, ref message, target } => {
+                let conn = parse_connection(state, message, target)?;
+                let (_, obj) = find_object(layers, instance_id)?;
+                obj.connections.as_mut_vec().push(conn);
+            },
+            ScriptEdit::RemoveConnection { instance_id, ref state, ref message, target } => {
+                let conn = parse_connection(state, message, target)?;
+                let (_, obj) = find_object(layers, instance_id)?;
+                obj.connections.as_mut_vec().retain(|c| {
+                    !(c.state == conn.state && c.message == conn.message
+                        && c.target_object_id == conn.target_object_id)
+                });
+            },
+            ScriptEdit::CloneObject { instance_id, position, layer } => {
+                let (src_layer, obj) = find_object(layers, instance_id)?;
+                let mut obj = obj.clone();
+                let layer = match layer {
+                    Some(layer) => check_layer(layers, layer)?,
+                    None => src_layer,
+                };
+                obj.instance_id = ps.fresh_instance_id(layer as u32);
+                obj.connections = vec![].into();
+                if let Some(position) = position {
+                    if obj.property_type("Position").is_ok() {
+                        obj.set_property("Position", structs::PropertyValue::Vec3(position))?;
+                    } else {
+                        eprintln!(
+                            "Warning: ignoring the position of the clone of 0x{:08X}, which \
+                             has no Position property",
+                            instance_id,
+                        );
+                    }
+                }
+                let asset_ids = referenced_asset_ids(&obj);
+                layers[layer].objects.as_mut_vec().push(obj);
+                area.copy_dependencies(src_layer, layer, &asset_ids);
+            },
+            ScriptEdit::SetProperty { instance_id, ref property, ref value } => {
+                let (_, obj) = find_object(layers, instance_id)?;
+                let value = json_to_property_value(obj.property_type(property)?, value)?;
+                obj.set_property(property, value)?;
+            },
+            ScriptEdit::SetLayerActive { .. } => unreachable!(),
+        }
+    }
+    Ok(())
+}
+
+fn patch_enemy_stats(
+    area: &mut mlvl_wrapper::MlvlArea,
+    config: &EnemyStatsConfig,
+    seed: u64,
+) -> Result<(), String>
+{
+    // Seed per room so the result doesn't depend on the order the rooms are patched in
+    let mut rng = StdRng::seed_from_u64(seed ^ area.mrea_file_id() as u64);
+    let mut roll = |range: Option<[f32; 2]>| match range {
+        Some([min, max]) if min < max => rng.gen_range(min, max),
+        Some([min, _]) => min,
+        None => 1.0,
+    };
+
+    let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
+    for obj in layers.iter_mut().flat_map(|layer| layer.objects.as_mut_vec()) {
+        if !obj.property_data.is_patterned_ai() {
+            continue;
+        }
+        let overrides = structs::scly_schema().object_name(obj.property_data.object_type())
+            .and_then(|name| config.overrides.get(name));
+        let mut health = overrides.and_then(|o| o.health_multiplier)
+            .unwrap_or(config.health_multiplier);
+        let mut damage = overrides.and_then(|o| o.damage_multiplier)
+            .unwrap_or(config.damage_multiplier);
+        let mut size = 1.0;
+        if let Some(randomize) = &config.randomize {
+            health *= roll(randomize.health);
+            damage *= roll(randomize.damage);
+            size *= roll(randomize.size);
+        }
+
+        // Enemies whose properties can't be decoded are left alone
+        if size != 1.0 {
+            if let Some(scale) = obj.property_data.patterned_scale_mut() {
+                for component in scale.iter_mut() {
+                    *component *= size;
+                }
+            }
+        }
+
+        // Only the contact damage is part of the PatternedInfo; attacks specific to each
+        // enemy are left alone.
+        if let Some(patterned_info) = obj.property_data.patterned_info_mut() {
+            patterned_info.health_info.health *= health;
+            patterned_info.contact_damage.damage *= damage;
+            patterned_info.half_extent *= size;
+            patterned_info.height *= size;
+        }
+    }
+    Ok(())
+}
+
+fn patch_enemy_weaknesses(
+    area: &mut mlvl_wrapper::MlvlArea,
+    weaknesses: &[EnemyWeaknesses],
+) -> Result<(), String>
+{
+    let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
+    for obj in layers.iter_mut().flat_map(|layer| layer.objects.as_mut_vec()) {
+        let object_type = obj.property_data.object_type();
+        let weakness = weaknesses.iter().find(|w| w.object_type == object_type);
+        if let Some(weakness) = weakness {
+            // Enemies whose properties can't be decoded are left alone
+            if let Some(patterned_info) = obj.property_data.patterned_info_mut() {
+                weakness.apply(&mut patterned_info.damage_vulnerability);
+            }
+        }
+    }
+    Ok(())
+}
+
 fn patch_landing_site_cutscene_triggers(
     ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -649,7 +1250,7 @@ fn patch_landing_site_cutscene_triggers(
     //     the memory relay, but I couldn't figure out how to make the memory
     //     relay default to on/enabled.
     let layer = area.mrea().scly_section_mut().layers.iter_mut().next().unwrap();
-    let timer_id = ps.fresh_instance_id_range.next().unwrap();
+    let timer_id = ps.fresh_instance_id(0);
     for obj in layer.objects.iter_mut() {
         if obj.instance_id == 427 {
             obj.connections.as_mut_vec().push(structs::Connection {
@@ -738,6 +1339,7 @@ fn fix_artifact_of_truth_requirements(
     ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
     pickup_layout: &[PickupType],
+    artifact_layers: &[ArtifactTempleLayer; 12],
 ) -> Result<(), String>
 {
     let truth_req_layer_id = area.layer_flags.layer_count;
@@ -747,24 +1349,24 @@ fn fix_artifact_of_truth_requirements(
     area.add_layer(b"Randomizer - Got Artifact 1\0".as_cstr());
 
     let at_pickup_kind = pickup_layout[63].pickup_data().kind;
-    for i in 0..12 {
-        let layer_number = if i == 0 {
-            truth_req_layer_id
-        } else {
-            i + 1
+    for (i, layer) in artifact_layers.iter().enumerate() {
+        let layer_number = artifact_temple_layer_number(i);
+        let kind = i as u32 + 29;
+        let inactive = match layer {
+            // If the artifact is needed, but is not the artifact at the Artifact Temple, mark
+            // this layer as inactive. It will be activated when the item is collected.
+            ArtifactTempleLayer::Artifact => at_pickup_kind != kind,
+            // The layer will be activated when the boss is defeated
+            ArtifactTempleLayer::Boss(_) => true,
+            // Either the artifact isn't needed or it is in the Artifact Temple, so mark this
+            // layer as active. In the former case, it needs to always be active since it will
+            // never be collected and in the latter case it needs to be active so the Ridley
+            // fight can start immediately if its the last artifact collected.
+            ArtifactTempleLayer::Active => false,
         };
-        let kind = i + 29;
-        let exists = pickup_layout.iter()
-            .any(|pt| kind == pt.pickup_data().kind);
-        if exists && at_pickup_kind != kind {
-            // If the artifact exsts, but is not the artifact at the Artifact Temple, mark this
-            // layer as inactive. It will be activated when the item is collected.
+        if inactive {
             area.layer_flags.flags &= !(1 << layer_number);
         } else {
-            // Either the artifact doesn't exist or it does and it is in the Artifact Temple, so
-            // mark this layer as active. In the former case, it needs to always be active since it
-            // will never be collect and in the latter case it needs to be active so the Ridley
-            // fight can start immediately if its the last artifact collected.
             area.layer_flags.flags |= 1 << layer_number;
         }
     }
@@ -772,7 +1374,7 @@ fn fix_artifact_of_truth_requirements(
     let scly = area.mrea().scly_section_mut();
 
     // A relay on the new layer is created and connected to "Relay Show Progress 1"
-    let new_relay_instance_id = ps.fresh_instance_id_range.next().unwrap();
+    let new_relay_instance_id = ps.fresh_instance_id(truth_req_layer_id);
     let new_relay = structs::SclyObject {
         instance_id: new_relay_instance_id,
         connections: vec![
@@ -802,6 +1404,22 @@ fn fix_artifact_of_truth_requirements(
     Ok(())
 }
 
+// The relays in the Artifact Temple that activate the artifact hints
+const HINT_RELAY_OBJS: &[u32] = &[
+    68157732,
+    68157735,
+    68157738,
+    68157741,
+    68157744,
+    68157747,
+    68157750,
+    68157753,
+    68157756,
+    68157759,
+    68157762,
+    68157765,
+];
+
 fn patch_artifact_hint_availability(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea,
@@ -809,20 +1427,6 @@ fn patch_artifact_hint_availability(
 ) -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
-    const HINT_RELAY_OBJS: &[u32] = &[
-        68157732,
-        68157735,
-        68157738,
-        68157741,
-        68157744,
-        68157747,
-        68157750,
-        68157753,
-        68157756,
-        68157759,
-        68157762,
-        68157765,
-    ];
     match hint_behavior {
         ArtifactHintBehavior::Default => (),
         ArtifactHintBehavior::All => {
@@ -848,6 +1452,145 @@ fn patch_artifact_hint_availability(
     Ok(())
 }
 
+fn temple_boss_room(boss: TempleBoss) -> (&'static str, &'static str, fn(&structs::SclyProperty) -> bool)
+{
+    match boss {
+        TempleBoss::Flaahgra =>
+            ("Metroid2.pak", "Sunchamber", |obj| obj.is_flaahgra()),
+        TempleBoss::Thardus =>
+            ("Metroid3.pak", "Quarantine Cave", |obj| obj.is_thardus()),
+        TempleBoss::PhazonElite =>
+            ("metroid5.pak", "Elite Research", |obj| obj.is_elite_pirate()),
+        TempleBoss::OmegaPirate =>
+            ("metroid5.pak", "Elite Quarters", |obj| obj.is_omega_pirate()),
+    }
+}
+
+fn patch_temple_boss_requirement(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+    is_boss: fn(&structs::SclyProperty) -> bool,
+    artifact_idx: usize,
+) -> Result<(), String>
+{
+    let layer_number = artifact_temple_layer_number(artifact_idx);
+    let scly = area.mrea().scly_section_mut();
+    let mut found = false;
+    for (layer_idx, layer) in scly.layers.as_mut_vec().iter_mut().enumerate() {
+        let boss_ids: Vec<u32> = layer.objects.iter()
+            .filter(|obj| is_boss(&obj.property_data))
+            .map(|obj| obj.instance_id)
+            .collect();
+        for boss_id in boss_ids {
+            // Activate the boss's layer in the Artifact Temple when it dies
+            let layer_change_id = ps.fresh_instance_id(layer_idx as u32);
+            layer.objects.as_mut_vec().push(structs::SclyObject {
+                instance_id: layer_change_id,
+                connections: vec![].into(),
+                property_data: structs::SpecialFunction::layer_change_fn(
+                    b"Artifact Temple Boss Layer Switch\0".as_cstr(),
+                    0xCD2B0EA2,
+                    layer_number,
+                ).into(),
+            });
+            let boss = layer.objects.iter_mut()
+                .find(|obj| obj.instance_id == boss_id)
+                .unwrap();
+            boss.connections.as_mut_vec().push(structs::Connection {
+                state: structs::ConnectionState::DEAD,
+                message: structs::ConnectionMsg::INCREMENT,
+                target_object_id: layer_change_id,
+            });
+            found = true;
+        }
+    }
+    if !found {
+        Err(format!("No boss found in area 0x{:08X}", area.mlvl_area.mrea.to_u32()))?
+    }
+    Ok(())
+}
+
+fn patch_artifact_temple_skip_ridley(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+) -> Result<(), String>
+{
+    let scly = area.mrea().scly_section_mut();
+    let layers = scly.layers.as_mut_vec();
+    let mut found = false;
+    for layer_idx in 0..layers.len() {
+        while let Some(pos) = layers[layer_idx].objects.iter()
+            .position(|obj| obj.property_data.is_ridley())
+        {
+            let ridley = layers[layer_idx].objects.as_mut_vec().remove(pos);
+
+            // Whatever would have started the fight instead does what Ridley's death would have
+            let dead_connections: Vec<_> = ridley.connections.iter()
+                .filter(|conn| conn.state == structs::ConnectionState::DEAD)
+                .map(|conn| structs::Connection {
+                    state: structs::ConnectionState::ZERO,
+                    ..conn.into_owned()
+                })
+                .collect();
+            let relay_id = ps.fresh_instance_id(layer_idx as u32);
+            layers[layer_idx].objects.as_mut_vec().push(structs::SclyObject {
+                instance_id: relay_id,
+                connections: dead_connections.into(),
+                property_data: structs::Relay {
+                    name: b"Relay Ridley Skipped\0".as_cstr(),
+                    active: 1,
+                }.into(),
+            });
+
+            let mut redirected = false;
+            for obj in layers.iter_mut().flat_map(|layer| layer.objects.iter_mut()) {
+                let connections = obj.connections.as_mut_vec();
+                for conn in connections.iter_mut() {
+                    if conn.target_object_id == ridley.instance_id &&
+                        conn.message == structs::ConnectionMsg::ACTIVATE
+                    {
+                        conn.target_object_id = relay_id;
+                        conn.message = structs::ConnectionMsg::SET_TO_ZERO;
+                        redirected = true;
+                    }
+                }
+                connections.retain(|conn| conn.target_object_id != ridley.instance_id);
+            }
+            if !redirected {
+                Err(format!("Nothing activates Ridley 0x{:08X}", ridley.instance_id))?
+            }
+            found = true;
+        }
+    }
+    if !found {
+        Err("Ridley not found in the Artifact Temple".to_string())?
+    }
+    Ok(())
+}
+
+fn patch_artifact_temple_skip_impact_crater(
+    _ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea,
+) -> Result<(), String>
+{
+    let credits = SpawnRoom::EndingCinematic.spawn_room_data();
+    let scly = area.mrea().scly_section_mut();
+    let mut found = false;
+    let wts = scly.layers.iter_mut()
+        .flat_map(|layer| layer.objects.iter_mut())
+        .filter_map(|obj| obj.property_data.as_world_transporter_mut())
+        .filter(|wt| wt.mlvl.to_u32() == World::ImpactCrater.mlvl());
+    for wt in wts {
+        wt.mlvl = ResId::new(credits.mlvl);
+        wt.mrea = ResId::new(credits.mrea);
+        found = true;
+    }
+    if !found {
+        Err("The Impact Crater portal wasn't found in the Artifact Temple".to_string())?
+    }
+    Ok(())
+}
+
 fn patch_sun_tower_prevent_wild_before_flaahgra(
     _ps: &mut PatcherState,
     area: &mut mlvl_wrapper::MlvlArea
@@ -869,7 +1612,7 @@ fn patch_sunchamber_prevent_wild_before_flaahgra(
 ) -> Result<(), String>
 {
     let scly = area.mrea().scly_section_mut();
-    let enable_sun_tower_layer_id = ps.fresh_instance_id_range.next().unwrap();
+    let enable_sun_tower_layer_id = ps.fresh_instance_id(1);
     scly.layers.as_mut_vec()[1].objects.as_mut_vec().push(structs::SclyObject {
         instance_id: enable_sun_tower_layer_id,
         connections: vec![].into(),
@@ -1026,16 +1769,41 @@ fn patch_mqa_cinematic(_ps: &mut PatcherState, area: &mut mlvl_wrapper::MlvlArea
     Ok(())
 }
 
-fn make_elite_research_fight_prereq_patches(patcher: &mut PrimePatcher)
+/// Records the objects `pickup_meta` lists for each room, which the "remove_room_objects" and
+/// "pickups" patches change
+pub(crate) fn add_room_info_touched_objects(patcher: &mut PrimePatcher)
 {
-    patcher.add_scly_patch(resource_info!("03_mines.MREA").into(), |_ps, area| {
+    for (_, rooms) in pickup_meta::ROOM_INFO.iter() {
+        for room_info in rooms.iter() {
+            let room_id = room_info.room_id.to_u32();
+            patcher.add_touched_objects(
+                "remove_room_objects",
+                room_id,
+                room_info.objects_to_remove.iter().flat_map(|otr| otr.instance_ids.iter().copied()),
+            );
+            patcher.add_touched_objects(
+                "pickups",
+                room_id,
+                room_info.pickup_locations.iter().flat_map(|location| {
+                    iter::once(location.location.instance_id)
+                        .chain(iter::once(location.attainment_audio.instance_id))
+                        .chain(iter::once(location.hudmemo.instance_id))
+                }),
+            );
+        }
+    }
+}
+
+fn make_elite_research_fight_prereq_patches(patcher: &mut PrimePatcher)
+{
+    patcher.add_scly_patch("elite_research_fight_prereqs", resource_info!("03_mines.MREA").into(), |_ps, area| {
         let flags = &mut area.layer_flags.flags;
         *flags |= 1 << 1; // Turn on "3rd pass elite bustout"
         *flags &= !(1 << 5); // Turn off the "dummy elite"
         Ok(())
     });
 
-    patcher.add_scly_patch(resource_info!("07_mines_electric.MREA").into(), |_ps, area| {
+    patcher.add_scly_patch("elite_research_fight_prereqs", resource_info!("07_mines_electric.MREA").into(), |_ps, area| {
         let scly = area.mrea().scly_section_mut();
         scly.layers.as_mut_vec()[0].objects.as_mut_vec()
             .retain(|obj| obj.instance_id != 0x1B0525 && obj.instance_id != 0x1B0522);
@@ -1089,7 +1857,7 @@ fn patch_research_lab_aether_exploding_wall<'r>(
     let scly = area.mrea().scly_section_mut();
     let layer = &mut scly.layers.as_mut_vec()[0];
 
-    let id = ps.fresh_instance_id_range.next().unwrap();
+    let id = ps.fresh_instance_id(0);
     let obj = layer.objects.as_mut_vec().iter_mut()
         .find(|obj| obj.instance_id == 2622568)
         .unwrap();
@@ -1250,7 +2018,7 @@ fn patch_main_ventilation_shaft_section_b_door<'r>(
     let layer = &mut scly.layers.as_mut_vec()[0];
 
     layer.objects.as_mut_vec().push(structs::SclyObject {
-        instance_id: ps.fresh_instance_id_range.next().unwrap(),
+        instance_id: ps.fresh_instance_id(0),
         property_data: structs::Trigger {
             name: b"Trigger_DoorOpen-component\0".as_cstr(),
             position: [31.232622, 442.69165, -64.20529].into(),
@@ -1949,7 +2717,7 @@ fn patch_heat_damage_per_sec<'a>(patcher: &mut PrimePatcher<'_, 'a>, heat_damage
     ];
 
     for heated_room in HEATED_ROOMS.iter() {
-        patcher.add_scly_patch((*heated_room).into(), move |_ps, area| {
+        patcher.add_scly_patch("heat_damage_per_sec", (*heated_room).into(), move |_ps, area| {
             let scly = area.mrea().scly_section_mut();
             let layer = &mut scly.layers.as_mut_vec()[0];
             layer.objects.iter_mut()
@@ -2127,17 +2895,306 @@ fn patch_credits(
     Ok(())
 }
 
+/// Where the starting spawn point is placed for a configured position: the position itself if
+/// the player can stand there, or else the closest place to it that the player can stand at
+/// and walk to from the room's doors and spawn points.
+fn navigable_spawn_position(area: &mut mlvl_wrapper::MlvlArea, position: [f32; 3])
+    -> Result<[f32; 3], String>
+{
+    const PLAYER_HEIGHT: f32 = 2.7;
+    if !position.iter().all(|x| x.is_finite()) {
+        Err(format!("The starting spawn point {:?} isn't a valid position", position))?;
+    }
+    let mrea = area.mrea();
+    let mesh = mrea.collision_section().indices.to_mesh();
+    // The floor is looked for from a little above each point, as they're often right on it
+    let raise = |p: [f32; 3]| [p[0], p[1], p[2] + 0.5];
+    if mesh.is_standable(raise(position), PLAYER_HEIGHT) {
+        return Ok(position);
+    }
+
+    let scly = mrea.scly_section();
+    let entrances: Vec<[f32; 3]> = scly.layers.iter()
+        .flat_map(|layer| {
+            layer.objects.iter()
+                .filter_map(|obj| {
+                    let data = &obj.property_data;
+                    let p = match (data.as_spawn_point(), data.as_door()) {
+                        (Some(spawn_point), _) => spawn_point.position,
+                        (_, Some(door)) => door.position,
+                        _ => return None,
+                    };
+                    Some([p[0], p[1], p[2]])
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let distance = |p: &[f32; 3]| (0..3).map(|i| (p[i] - position[i]).powi(2)).sum::<f32>();
+    let closest = entrances.iter()
+        .flat_map(|entrance| mesh.navigable_spawn_points(raise(*entrance), PLAYER_HEIGHT))
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+    match closest {
+        Some(closest) => {
+            eprintln!(
+                "Warning: moving the starting spawn point from {:?} to {:?}, as the player can't \
+                 stand at the former",
+                position,
+                closest,
+            );
+            Ok(closest)
+        },
+        None => Err(format!(
+            "The starting spawn point {:?} isn't somewhere the player can stand",
+            position,
+        )),
+    }
+}
+
+/// Adds a spawn point at an exact position and facing, and makes it the one the game starts at.
+/// The room's other spawn points are left active so it can still be entered normally.
+fn patch_starting_spawn_point<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    spawn_point_config: SpawnPointConfig,
+    starting_items: &StartingItems,
+) -> Result<(), String>
+{
+    let position = navigable_spawn_position(area, spawn_point_config.position)?;
+
+    let scly = area.mrea().scly_section_mut();
+    let layers = scly.layers.as_mut_vec();
+    for obj in layers.iter_mut().flat_map(|layer| layer.objects.iter_mut()) {
+        if let Some(spawn_point) = obj.property_data.as_spawn_point_mut() {
+            spawn_point.default_spawn = 0;
+        }
+    }
+
+    let mut spawn_point = structs::SpawnPoint {
+        name: b"Randomizer - Starting Spawn Point\0".as_cstr(),
+
+        position: position.into(),
+        rotation: [0.0, 0.0, spawn_point_config.yaw].into(),
+
+        power: 1,
+        ice: 0,
+        wave: 0,
+        plasma: 0,
+
+        missiles: 0,
+        scan_visor: 0,
+        bombs: 0,
+        power_bombs: 0,
+        flamethrower: 0,
+        thermal_visor: 0,
+        charge: 0,
+        super_missile: 0,
+        grapple: 0,
+        xray: 0,
+        ice_spreader: 0,
+        space_jump: 0,
+        morph_ball: 0,
+        combat_visor: 1,
+        boost_ball: 0,
+        spider_ball: 0,
+        power_suit: 1,
+        gravity_suit: 0,
+        varia_suit: 0,
+        phazon_suit: 0,
+        energy_tanks: 0,
+        unknown0: 0,
+        health_refill: 0,
+        unknown1: 0,
+        wavebuster: 0,
+
+        default_spawn: 1,
+        active: 1,
+        morphed: 0,
+    };
+    starting_items.update_spawn_point(&mut spawn_point);
+    layers[0].objects.as_mut_vec().push(structs::SclyObject {
+        instance_id: ps.fresh_instance_id(0),
+        connections: vec![].into(),
+        property_data: spawn_point.into(),
+    });
+    Ok(())
+}
+
+/// The items in `starting_items` that aren't in `item_loss_items`, and how many of each
+fn lost_items(starting_items: &StartingItems, item_loss_items: &StartingItems)
+    -> Vec<(PickupType, u32)>
+{
+    let kept_items = item_loss_items.item_counts();
+    starting_items.item_counts().into_iter()
+        .filter_map(|(pt, count)| {
+            let kept = kept_items.iter()
+                .find(|(other, _)| *other == pt)
+                .map(|(_, kept)| *kept)
+                .unwrap_or(0);
+            count.checked_sub(kept)
+                .filter(|lost| *lost > 0)
+                .map(|lost| (pt, lost))
+        })
+        .collect()
+}
+
+/// Adds a pickup for each lost item in a row in front of the room's spawn point
+fn patch_replace_lost_items<'r>(
+    ps: &mut PatcherState,
+    area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
+    lost_items: &[(PickupType, u32)],
+    game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
+) -> Result<(), String>
+{
+    let room_id = area.mlvl_area.internal_id;
+
+    let spawn_points: Vec<_> = area.mrea().scly_section_mut().layers.as_mut_vec().iter()
+        .flat_map(|layer| layer.objects.iter())
+        .filter_map(|obj| {
+            let spawn_point = obj.property_data.as_spawn_point()?;
+            Some((spawn_point.default_spawn, spawn_point.position, spawn_point.rotation))
+        })
+        .collect();
+    let &(_, position, rotation) = spawn_points.iter()
+        .find(|(default_spawn, _, _)| *default_spawn != 0)
+        .or_else(|| spawn_points.first())
+        .ok_or("Cannot place lost items in a room without a spawn point")?;
+
+    // Samus faces along +Y, rotated about Z
+    let yaw = rotation[2].to_radians();
+    let forward = [-yaw.sin(), yaw.cos()];
+    let right = [yaw.cos(), yaw.sin()];
+    for (i, &(pickup_type, amount)) in lost_items.iter().enumerate() {
+        // Each item gets its own layer, which is turned off once the item is collected so it
+        // doesn't come back the next time the room is loaded
+        let name = CString::new(format!("Randomizer - Lost Item {} ({})", i, pickup_type.name()))
+            .unwrap();
+        area.add_layer(Cow::Owned(name));
+        let layer_idx = area.layer_flags.layer_count - 1;
+
+        let deps_iter = pickup_type.dependencies().iter()
+            .map(|&(file_id, fourcc)| structs::Dependency {
+                asset_id: file_id,
+                asset_type: fourcc,
+            })
+            .chain(iter::once(pickup_type.skip_hudmemos_strg().into()));
+        area.add_dependencies(game_resources, layer_idx as usize, deps_iter);
+
+        let hudmemo_id = ps.fresh_instance_id(layer_idx);
+        let attainment_audio_id = ps.fresh_instance_id(layer_idx);
+        let layer_change_id = ps.fresh_instance_id(layer_idx);
+        let pickup_id = ps.fresh_instance_id(layer_idx);
+
+        let offset = (i as f32 - (lost_items.len() - 1) as f32 / 2.0) * 2.0;
+        let pickup = structs::Pickup {
+            name: b"Randomizer - Lost Item\0".as_cstr(),
+            position: [
+                position[0] + forward[0] * 4.0 + right[0] * offset,
+                position[1] + forward[1] * 4.0 + right[1] * offset,
+                position[2] + 1.0,
+            ].into(),
+            curr_increase: amount,
+            max_increase: amount,
+            active: 1,
+            ..pickup_type.pickup_data().clone()
+        };
+        let connections = vec![
+            structs::Connection {
+                state: structs::ConnectionState::ARRIVED,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: hudmemo_id,
+            },
+            structs::Connection {
+                state: structs::ConnectionState::ARRIVED,
+                message: structs::ConnectionMsg::PLAY,
+                target_object_id: attainment_audio_id,
+            },
+            structs::Connection {
+                state: structs::ConnectionState::ARRIVED,
+                message: structs::ConnectionMsg::DECREMENT,
+                target_object_id: layer_change_id,
+            },
+        ];
+
+        let scly = area.mrea().scly_section_mut();
+        scly.layers.as_mut_vec()[layer_idx as usize].objects.as_mut_vec().extend_from_slice(&[
+            structs::SclyObject {
+                instance_id: pickup_id,
+                connections: connections.into(),
+                property_data: pickup.into(),
+            },
+            structs::SclyObject {
+                instance_id: hudmemo_id,
+                connections: vec![].into(),
+                property_data: structs::HudMemo {
+                    name: b"Randomizer - Lost Item hudmemo\0".as_cstr(),
+
+                    first_message_timer: 5.,
+                    unknown: 1,
+                    memo_type: 0,
+                    strg: pickup_type.skip_hudmemos_strg(),
+                    active: 1,
+                }.into(),
+            },
+            structs::SclyObject {
+                instance_id: attainment_audio_id,
+                connections: vec![].into(),
+                property_data: structs::StreamedAudio {
+                    name: b"Randomizer - Lost Item attainment audio\0".as_cstr(),
+
+                    active: 1,
+                    audio_file_name: pickup_type.attainment_audio_file_name().as_bytes()
+                        .as_cstr(),
+                    no_stop_on_deactivate: 0,
+                    fade_in_time: 0.,
+                    fade_out_time: 0.,
+                    volume: 127,
+                    oneshot: 1,
+                    is_music: 1,
+                }.into(),
+            },
+            structs::SclyObject {
+                instance_id: layer_change_id,
+                connections: vec![].into(),
+                property_data: structs::SpecialFunction::layer_change_fn(
+                    b"Randomizer - Disable Lost Item Layer\0".as_cstr(),
+                    room_id,
+                    layer_idx,
+                ).into(),
+            },
+        ]);
+    }
+    Ok(())
+}
+
 fn patch_starting_pickups<'r>(
     area: &mut mlvl_wrapper::MlvlArea<'r, '_, '_, '_>,
     starting_items: &StartingItems,
     show_starting_memo: bool,
+    etank_capacity: u32,
     game_resources: &HashMap<(u32, FourCC), structs::Resource<'r>>,
 ) -> Result<(), String>
 {
+    // CPlayerState::EItemType::HealthRefill
+    const HEALTH_REFILL_ITEM: u32 = 26;
+
     let room_id = area.mlvl_area.internal_id;
     let layer_count = area.mrea().scly_section_mut().layers.as_mut_vec().len() as u32;
 
-    if show_starting_memo {
+    // Spawn points can't give artifacts or set the player's energy, so those are changed once
+    // the player has spawned. Negative amounts take away from what the player has.
+    let mut player_state_changes: Vec<(u32, i32)> = starting_items.artifacts.iter()
+        .map(|pt| (pt.pickup_data().kind, 1))
+        .collect();
+    if let Some(health) = starting_items.health {
+        let full_health = etank_capacity - 1 + etank_capacity * starting_items.energy_tanks;
+        if health == 0 || health > full_health {
+            Err(format!("Starting health must be between 1 and {}", full_health))?
+        }
+        player_state_changes.push((HEALTH_REFILL_ITEM, health as i32 - full_health as i32));
+    }
+
+    let use_popup_layer = show_starting_memo || !player_state_changes.is_empty();
+    if use_popup_layer {
         // Turn on "Randomizer - Starting Items popup Layer"
         area.layer_flags.flags |= 1 << layer_count;
         area.add_layer(b"Randomizer - Starting Items popup Layer\0".as_cstr());
@@ -2156,6 +3213,9 @@ fn patch_starting_pickups<'r>(
     let timer_starting_items_popup_id = (next_object_id + 1) + (layer_count << 26);
     let hud_memo_starting_items_popup_id = (next_object_id + 2) + (layer_count << 26);
     let special_function_starting_items_popup_id = (next_object_id + 3) + (layer_count << 26);
+    let player_state_change_ids: Vec<u32> = (0..player_state_changes.len() as u32)
+        .map(|i| (next_object_id + 4 + i) + (layer_count << 26))
+        .collect();
 
     for layer in scly.layers.iter_mut() {
         for obj in layer.objects.iter_mut() {
@@ -2165,8 +3225,31 @@ fn patch_starting_pickups<'r>(
         }
     }
 
-    if show_starting_memo {
-        scly.layers.as_mut_vec()[layer_count as usize].objects.as_mut_vec().extend_from_slice(
+    if use_popup_layer {
+        let mut timer_connections = vec![
+            structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::DECREMENT,
+                target_object_id: special_function_starting_items_popup_id,
+            },
+        ];
+        if show_starting_memo {
+            timer_connections.push(structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: hud_memo_starting_items_popup_id,
+            });
+        }
+        timer_connections.extend(player_state_change_ids.iter().map(|id| {
+            structs::Connection {
+                state: structs::ConnectionState::ZERO,
+                message: structs::ConnectionMsg::SET_TO_ZERO,
+                target_object_id: *id,
+            }
+        }));
+
+        let objects = scly.layers.as_mut_vec()[layer_count as usize].objects.as_mut_vec();
+        objects.extend_from_slice(
             &[
                 structs::SclyObject {
                     instance_id: timer_starting_items_popup_id,
@@ -2179,31 +3262,7 @@ fn patch_starting_pickups<'r>(
                         start_immediately: 1,
                         active: 1,
                     }.into(),
-                    connections: vec![
-                        structs::Connection {
-                            state: structs::ConnectionState::ZERO,
-                            message: structs::ConnectionMsg::SET_TO_ZERO,
-                            target_object_id: hud_memo_starting_items_popup_id,
-                        },
-                        structs::Connection {
-                            state: structs::ConnectionState::ZERO,
-                            message: structs::ConnectionMsg::DECREMENT,
-                            target_object_id: special_function_starting_items_popup_id,
-                        },
-                    ].into(),
-                },
-                structs::SclyObject {
-                    instance_id: hud_memo_starting_items_popup_id,
-                    connections: vec![].into(),
-                    property_data: structs::HudMemo {
-                        name: b"Starting Items popup hudmemo\0".as_cstr(),
-
-                        first_message_timer: 0.5,
-                        unknown: 1,
-                        memo_type: 1,
-                        strg: custom_asset_ids::STARTING_ITEMS_HUDMEMO_STRG,
-                        active: 1,
-                    }.into(),
+                    connections: timer_connections.into(),
                 },
                 structs::SclyObject {
                     instance_id: special_function_starting_items_popup_id,
@@ -2217,6 +3276,43 @@ fn patch_starting_pickups<'r>(
             ]
         );
 
+        objects.extend(player_state_changes.iter().zip(player_state_change_ids.iter())
+            .map(|(&(item, amount), id)| {
+                structs::SclyObject {
+                    instance_id: *id,
+                    connections: vec![].into(),
+                    property_data: structs::PlayerStateChange {
+                        name: b"Starting Items player state change\0".as_cstr(),
+
+                        active: 1,
+                        item,
+                        amount: amount as u32,
+                        capacity: amount.max(0) as u32,
+                        control: 0,
+                        control_command: 0,
+                    }.into(),
+                }
+            })
+        );
+    }
+
+    if show_starting_memo {
+        scly.layers.as_mut_vec()[layer_count as usize].objects.as_mut_vec().push(
+            structs::SclyObject {
+                instance_id: hud_memo_starting_items_popup_id,
+                connections: vec![].into(),
+                property_data: structs::HudMemo {
+                    name: b"Starting Items popup hudmemo\0".as_cstr(),
+
+                    first_message_timer: 0.5,
+                    unknown: 1,
+                    memo_type: 1,
+                    strg: custom_asset_ids::STARTING_ITEMS_HUDMEMO_STRG,
+                    active: 1,
+                }.into(),
+            }
+        );
+
         area.add_dependencies(
             &game_resources,
             0,
@@ -2226,21 +3322,100 @@ fn patch_starting_pickups<'r>(
     Ok(())
 }
 
+/// Makes every room that a world's map station would reveal visible on its map from the start
+fn patch_starting_maps(file: &mut structs::FstEntryFile) -> Result<(), String>
+{
+    // CMapArea::EVisMode
+    const VISIBILITY_ALWAYS: u32 = 0;
+    const VISIBILITY_MAP_STATION_OR_VISIT: u32 = 1;
+
+    let pak = match file {
+        structs::FstEntryFile::Pak(pak) => pak,
+        _ => Err("Expected a PAK")?,
+    };
+    let mut cursor = pak.resources.cursor();
+    while cursor.peek().is_some() {
+        let mut cursor = cursor.cursor_advancer();
+        if cursor.peek().unwrap().fourcc() != b"MAPA".into() {
+            continue;
+        }
+        let mapa = cursor.value().unwrap().kind.as_mapa_mut().unwrap();
+        if mapa.visibility_mode == VISIBILITY_MAP_STATION_OR_VISIT {
+            mapa.visibility_mode = VISIBILITY_ALWAYS;
+        }
+    }
+    Ok(())
+}
+
+/// The scans that make up the given worlds' parts of the logbook, as listed in their SAVWs. The
+/// REL marks these as scanned, as big-endian SCAN asset ids.
+fn create_starting_scans_file(gc_disc: &structs::GcDisc, worlds: &[World]) -> Vec<u8>
+{
+    let mut buf = vec![];
+    for (pak_name, _) in pickup_meta::ROOM_INFO.iter() {
+        let world = World::from_pak(pak_name).unwrap();
+        if !worlds.contains(&world) {
+            continue;
+        }
+        let file_entry = gc_disc.find_file(pak_name).unwrap();
+        let pak = match *file_entry.file().unwrap() {
+            structs::FstEntryFile::Pak(ref pak) => Cow::Borrowed(pak),
+            structs::FstEntryFile::Unknown(ref reader) => Cow::Owned(reader.clone().read(())),
+            _ => panic!(),
+        };
+        let savw = pak.resources.iter()
+            .find(|res| res.file_id == world.savw() && res.fourcc() == b"SAVW".into())
+            .unwrap();
+        for scan in savw.kind.as_savw().unwrap().scan_array.iter() {
+            buf.extend_from_slice(&scan.scan.to_u32().to_be_bytes());
+        }
+    }
+    buf
+}
+
 include!("../compile_to_ppc/patches_config.rs");
 fn create_rel_config_file(
     spawn_room: SpawnRoomData,
     quickplay: bool,
+    item_tracker: bool,
+    multiworld: bool,
+    practice_mode: bool,
 ) -> Vec<u8>
 {
     let config = RelConfig {
         quickplay_mlvl: if quickplay { spawn_room.mlvl } else { 0xFFFFFFFF },
         quickplay_mrea: if quickplay { spawn_room.mrea } else { 0xFFFFFFFF },
+        item_tracker,
+        multiworld,
+        practice_mode,
     };
     let mut buf = vec![0; mem::size_of::<RelConfig>()];
     ssmarshal::serialize(&mut buf, &config).unwrap();
     buf
 }
 
+/// The rooms in the practice mode's warp menu. Every entry is the room's MLVL and MREA as
+/// big-endian u32s, followed by the length of its name as a u8 and then the name in ASCII.
+fn create_practice_warps_file(include_frigate: bool) -> Vec<u8>
+{
+    let mut buf = vec![];
+    for (pak_name, rooms) in pickup_meta::ROOM_INFO.iter() {
+        let world = World::from_pak(pak_name).unwrap();
+        if world == World::FrigateOrpheon && !include_frigate {
+            continue;
+        }
+        for room_info in rooms.iter() {
+            let name = format!("{}: {}", world.to_str(), room_info.name);
+            let name: Vec<u8> = name.bytes().filter(u8::is_ascii).take(u8::MAX as usize).collect();
+            buf.extend_from_slice(&world.mlvl().to_be_bytes());
+            buf.extend_from_slice(&room_info.room_id.to_u32().to_be_bytes());
+            buf.push(name.len() as u8);
+            buf.extend_from_slice(&name);
+        }
+    }
+    buf
+}
+
 fn patch_dol<'r>(
     file: &mut structs::FstEntryFile,
     spawn_room: SpawnRoomData,
@@ -2499,6 +3674,19 @@ fn patch_dol<'r>(
     });
     dol_patcher.ppcasm_patch(&power_bomb_capacity_patch)?;
 
+    if config.multiworld.is_some() {
+        // The amounts of these items can't otherwise go above 1
+        for &item in &[multiworld::MULTIWORLD_RECEIVED_COUNTER_ITEM, multiworld::MULTIWORLD_SEND_ITEM] {
+            let max_value_patch = ppcasm!(
+                symbol_addr!("CPlayerState_PowerUpMaxValues", version) + item as u32 * 4,
+                {
+                    .long 255;
+                }
+            );
+            dol_patcher.ppcasm_patch(&max_value_patch)?;
+        }
+    }
+
     // set etank capacity and base health
     let etank_capacity = config.etank_capacity as f32;
     let base_health = etank_capacity - 1.0;
@@ -2640,7 +3828,7 @@ fn patch_bnr(
 }
 
 #[derive(PartialEq, Copy, Clone)]
-enum Version
+pub enum Version
 {
     NtscU0_00,
     NtscU0_01,
@@ -2669,9 +3857,61 @@ impl fmt::Display for Version
     }
 }
 
+/// Fails if the layout can't be finished under the normal difficulty's requirements, listing
+/// the items that can't be reached
+fn check_layout_solvability(config: &PatchConfig) -> Result<(), String>
+{
+    let vanilla = logic::check_vanilla_connections(
+        config.layout.starting_location,
+        &config.layout.elevators,
+    );
+    if let Err(e) = vanilla {
+        eprintln!("Warning: skipping the solvability check. {}", e);
+        return Ok(());
+    }
+
+    let difficulty = normal_difficulty::difficulty();
+    let goal = difficulty.goal(&config.layout.pickups, &config.artifact_temple);
+    let report = logic::check_solvability(
+        &difficulty,
+        &config.layout,
+        &config.starting_items,
+        &config.progressive_items,
+        &goal,
+    )?;
+    if !report.completable {
+        let unreachable: Vec<_> = pickup_meta::pickup_location_room_names()
+            .zip(config.layout.pickups.iter())
+            .enumerate()
+            .filter(|(i, (_, pt))| {
+                **pt != PickupType::Nothing && report.unreachable_locations.contains(i)
+            })
+            .map(|(_, (room_name, pt))| format!("{} ({})", pt.name(), room_name))
+            .collect();
+        Err(format!(
+            "The layout can't be completed. Unreachable items: {}",
+            unreachable.join(", "),
+        ))?
+    }
+    Ok(())
+}
+
 pub fn patch_iso<T>(config: PatchConfig, mut pn: T) -> Result<(), String>
     where T: structs::ProgressNotifier
 {
+    if config.check_solvability {
+        check_layout_solvability(&config)?;
+    }
+    if let Some(spoiler_path) = &config.spoiler_path {
+        spoiler::write_spoiler_log(
+            spoiler_path,
+            &config.layout,
+            &config.starting_items,
+            &config.progressive_items,
+            &config.artifact_temple,
+        )?;
+    }
+
     let mut ct = Vec::new();
     writeln!(ct, "Created by randomprime version {}", env!("CARGO_PKG_VERSION")).unwrap();
     writeln!(ct).unwrap();
@@ -2687,6 +3927,24 @@ pub fn patch_iso<T>(config: PatchConfig, mut pn: T) -> Result<(), String>
     writeln!(ct, "map default state: {}", config.map_default_state.to_string().to_lowercase()).unwrap();
     writeln!(ct, "missile capacity: {}", config.missile_capacity).unwrap();
     writeln!(ct, "power bomb capacity: {}", config.power_bomb_capacity).unwrap();
+
+    let enemy_weaknesses = config.enemy_vulnerabilities.as_ref()
+        .map(|vulnerability_config| enemy_weaknesses::randomize_enemy_weaknesses(
+            config.layout.seed,
+            vulnerability_config,
+            &config.starting_items,
+            &config.layout.pickups,
+        ))
+        .unwrap_or_default();
+    if !enemy_weaknesses.is_empty() {
+        writeln!(ct, "enemy weaknesses:").unwrap();
+        for weaknesses in &enemy_weaknesses {
+            writeln!(ct, "    {}", weaknesses).unwrap();
+        }
+    }
+    if !config.disabled_patches.is_empty() {
+        writeln!(ct, "disabled patches: {}", config.disabled_patches.join(", ")).unwrap();
+    }
     writeln!(ct, "{}", config.comment).unwrap();
 
     let mut reader = Reader::new(&config.input_iso[..]);
@@ -2715,8 +3973,30 @@ pub fn patch_iso<T>(config: PatchConfig, mut pn: T) -> Result<(), String>
     if version == Version::NtscU0_01 {
         Err("The NTSC 0-01 version of Metroid Prime is not current supported.")?;
     }
+    if config.practice_mode && version != Version::NtscU0_00 {
+        Err("Practice mode is only supported on the NTSC 0-00 version of Metroid Prime.")?;
+    }
+    if config.item_tracker && version != Version::NtscU0_00 && version != Version::NtscU0_02 {
+        Err(concat!("The item tracker is only supported on the NTSC 0-00 and 0-02 versions ",
+                    "of Metroid Prime."))?;
+    }
+    if config.multiworld.is_some() && version != Version::NtscU0_00 && version != Version::NtscU0_02 {
+        Err(concat!("Multiworld is only supported on the NTSC 0-00 and 0-02 versions ",
+                    "of Metroid Prime."))?;
+    }
+    if !config.starting_items.scans.is_empty()
+        && version != Version::NtscU0_00 && version != Version::NtscU0_02
+    {
+        Err(concat!("Starting scan data is only supported on the NTSC 0-00 and 0-02 versions ",
+                    "of Metroid Prime."))?;
+    }
 
-    build_and_run_patches(&mut gc_disc, &config, version)?;
+    let applied_patches = build_and_run_patches(&mut gc_disc, &config, &enemy_weaknesses, version)?;
+    writeln!(ct).unwrap();
+    writeln!(ct, "Patches applied:").unwrap();
+    for name in applied_patches {
+        writeln!(ct, "    {}", name).unwrap();
+    }
 
     gc_disc.add_file("randomprime.txt", structs::FstEntryFile::Unknown(Reader::new(&ct)))?;
 
@@ -2765,8 +4045,424 @@ pub fn patch_iso<T>(config: PatchConfig, mut pn: T) -> Result<(), String>
     Ok(())
 }
 
-fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, version: Version)
-    -> Result<(), String>
+// The versions that have the fixes made for the PAL release
+const PAL_BASED_VERSIONS: &[Version] = &[
+    Version::NtscJ,
+    Version::Pal,
+    Version::NtscUTrilogy,
+    Version::NtscJTrilogy,
+    Version::PalTrilogy,
+];
+
+/// Every patch `build_and_run_patches` can apply, in the order they're added. Their names are
+/// listed in randomprime.txt when they're applied and can be used to disable them in the config.
+pub(crate) const PATCHES: &[PatchInfo] = &[
+    PatchInfo {
+        name: "game_banner",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "remove_attract_mode_fmvs",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "flaahgra_music",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "select_game_fmvs",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "remove_room_objects",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "pickups",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "room_layers",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "enemy_stats",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "enemy_weaknesses",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "elevators",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "dol",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "starting_maps",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "remove_frigate",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "frigate_teleporter",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "artifact_totem_scans",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "main_menu_message",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "main_menu",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "credits",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "mines_savw_for_phazon_suit_scan",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "artifact_of_truth_requirements",
+        versions: &[],
+        objects: &[(resource_info!("07_stonehenge.MREA").res_id, &[68158836])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "temple_boss_requirements",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "artifact_temple_skip_ridley",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "artifact_temple_skip_impact_crater",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "artifact_hint_availability",
+        versions: &[],
+        objects: &[
+            (resource_info!("07_stonehenge.MREA").res_id, &[1048956]),
+            (resource_info!("07_stonehenge.MREA").res_id, HINT_RELAY_OBJS),
+        ],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "save_banner",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "starting_spawn_point",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "starting_pickups",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "post_escape_pickups",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "replace_lost_items",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "morphball_hud",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "elite_research_fight_prereqs",
+        versions: &[],
+        objects: &[(resource_info!("07_mines_electric.MREA").res_id, &[0x1B0525, 0x1B0522])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "heat_damage_per_sec",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "sunchamber_prevent_wild_before_flaahgra",
+        versions: &[],
+        objects: &[(resource_info!("22_Flaahgra.MREA").res_id, &[0x42500D4])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "sun_tower_prevent_wild_before_flaahgra",
+        versions: &[],
+        objects: &[(resource_info!("0v_connect_tunnel.MREA").res_id, &[0x001d015b])],
+        // The trigger is moved to a layer only the Sunchamber patch activates
+        requires: &["sunchamber_prevent_wild_before_flaahgra"],
+    },
+    PatchInfo {
+        name: "temple_security_station_cutscene_trigger",
+        versions: &[],
+        objects: &[(resource_info!("00j_over_hall.MREA").res_id, &[0x70067])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "ridley_phendrana_shorelines_cinematic",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "mqa_cinematic",
+        versions: &[],
+        objects: &[(resource_info!("08_mines.MREA").res_id, &[0x2000CF, 0x2006DE])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "main_ventilation_shaft_section_b_door",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "research_lab_hydra_barrier",
+        versions: &[],
+        objects: &[(resource_info!("10_ice_research_a.MREA").res_id, &[202965810])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "lab_aether_cutscene_trigger",
+        versions: &[],
+        objects: &[(resource_info!("12_ice_research_b.MREA").res_id, &[0x330317])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "research_lab_aether_exploding_wall",
+        versions: &[],
+        objects: &[(resource_info!("13_ice_vault.MREA").res_id, &[2622568])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "observatory_2nd_pass_solvablility",
+        versions: &[],
+        objects: &[(resource_info!("11_ice_observatory.MREA").res_id, &[0x81E0460, 0x81E0461])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "observatory_1st_pass_softlock",
+        versions: &[],
+        objects: &[(resource_info!("11_ice_observatory.MREA").res_id, &[0x041E0381])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "mines_security_station_soft_lock",
+        versions: &[],
+        objects: &[(resource_info!("02_mines_shotemup.MREA").res_id, &[460074])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "gravity_chamber_stalactite_grapple_point",
+        versions: &[],
+        objects: &[(resource_info!("18_ice_gravity_chamber.MREA").res_id, &[3473722])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "main_quarry_barrier",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "research_core_access_soft_lock",
+        versions: &[Version::NtscU0_00],
+        objects: &[(
+            resource_info!("00n_ice_connect.MREA").res_id,
+            &[0x082C00CD, 0x082C006C, 0x082C0124],
+        )],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "arboretum_invisible_wall",
+        versions: &[
+            Version::NtscU0_01,
+            Version::NtscU0_02,
+            Version::NtscJ,
+            Version::Pal,
+            Version::NtscUTrilogy,
+            Version::NtscJTrilogy,
+            Version::PalTrilogy,
+        ],
+        objects: &[(resource_info!("08_courtyard.MREA").res_id, &[0x1302AA])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "ruined_courtyard_thermal_conduits",
+        versions: &[
+            Version::NtscU0_02,
+            Version::NtscJ,
+            Version::Pal,
+            Version::NtscUTrilogy,
+            Version::NtscJTrilogy,
+            Version::PalTrilogy,
+        ],
+        objects: &[(resource_info!("05_ice_shorelines.MREA").res_id, &[0xF01C8, 0xF01C7, 0xF01DD])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "main_quarry_door_lock_0_02",
+        versions: &[Version::NtscU0_02],
+        objects: &[(resource_info!("01_mines_mainplaza.MREA").res_id, &[132563])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "geothermal_core_door_lock_0_02",
+        versions: &[Version::NtscU0_02],
+        objects: &[(resource_info!("13_over_burningeffigy.MREA").res_id, &[1311646])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "hive_totem_boss_trigger_0_02",
+        versions: &[Version::NtscU0_02],
+        objects: &[(resource_info!("19_hive_totem.MREA").res_id, &[0x4240140])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "ore_processing_destructible_rock_pal",
+        versions: PAL_BASED_VERSIONS,
+        objects: &[(resource_info!("04_mines_pillar.MREA").res_id, &[0x60372, 0x60378, 0x60379])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "geothermal_core_destructible_rock_pal",
+        versions: PAL_BASED_VERSIONS,
+        objects: &[(
+            resource_info!("13_over_burningeffigy.MREA").res_id,
+            &[0x1403AE, 0x1403B4, 0x1403B5],
+        )],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "main_quarry_door_lock_pal",
+        versions: &[Version::Pal],
+        objects: &[(resource_info!("01_mines_mainplaza.MREA").res_id, &[0x1c0205db])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "landing_site_cutscene_triggers",
+        versions: &[],
+        objects: &[(resource_info!("01_over_mainplaza.MREA").res_id, &[427, 221])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "ending_scene_straight_to_credits",
+        versions: &[],
+        objects: &[(resource_info!("01_endcinema.MREA").res_id, &[1103])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "essence_cinematic_skip_whitescreen",
+        versions: &[Version::NtscU0_00],
+        objects: &[(resource_info!("03f_crater.MREA").res_id, &[0xB01DC])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "essence_cinematic_skip_nomusic",
+        versions: &[Version::NtscU0_00, Version::NtscU0_02, Version::Pal],
+        objects: &[(resource_info!("03f_crater.MREA").res_id, &[0xB01DC])],
+        // Both add connections to the cinematic skip
+        requires: &["essence_cinematic_skip_whitescreen"],
+    },
+    PatchInfo {
+        name: "vault_ledge_door",
+        versions: &[],
+        objects: &[(resource_info!("01_mainplaza.MREA").res_id, &[0x202F4, 0x20060, 0x202B8])],
+        requires: &[],
+    },
+    PatchInfo {
+        name: "suit_hue_rotation",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },    PatchInfo {
+        name: "script_edits",
+        versions: &[],
+        objects: &[],
+        requires: &[],
+    },
+];
+
+fn build_and_run_patches(
+    gc_disc: &mut structs::GcDisc,
+    config: &PatchConfig,
+    enemy_weaknesses: &[EnemyWeaknesses],
+    version: Version,
+)
+    -> Result<Vec<&'static str>, String>
 {
     let pickup_layout = &config.layout.pickups[..];
 
@@ -2787,10 +4483,42 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
     assert!(frigate_done_room.mlvl != World::FrigateOrpheon.mlvl()); // panic if the frigate level gets you stuck in a loop
 
     let mut rng = StdRng::seed_from_u64(config.layout.seed);
-    let artifact_totem_strings = build_artifact_temple_totem_scan_strings(pickup_layout, &mut rng, config.artifact_hints.clone());
+    let artifact_layers = plan_artifact_temple_layers(
+        pickup_layout,
+        &config.starting_items.artifacts,
+        &config.artifact_temple,
+    )?;
+    let artifact_layers = &artifact_layers;
+    let artifact_totem_strings = build_artifact_temple_totem_scan_strings(
+        pickup_layout,
+        &mut rng,
+        config.artifact_hints.clone(),
+        artifact_layers,
+    );
 
     let show_starting_memo = config.starting_memo.is_some();
 
+    // The items the player has after escaping the Frigate. When starting there, the player's
+    // items are exactly the starting items, so what's lost can be worked out.
+    let starts_on_frigate = starting_room.mlvl == World::FrigateOrpheon.mlvl();
+    let post_escape_items = if starts_on_frigate && config.item_loss.skip_item_loss {
+        // Artifacts and health aren't touched by spawn points, so they carry over by themselves
+        StartingItems {
+            artifacts: vec![],
+            health: None,
+            ..config.starting_items.clone()
+        }
+    } else {
+        config.item_loss_items.clone()
+    };
+    let post_escape_items = &post_escape_items;
+    let lost_items = if starts_on_frigate && config.item_loss.replace_lost_items {
+        lost_items(&config.starting_items, &config.item_loss_items)
+    } else {
+        vec![]
+    };
+    let lost_items = &lost_items;
+
     let starting_memo = {
         if config.starting_memo.is_some() {
             Some(config.starting_memo.as_ref().unwrap().as_str())
@@ -2799,7 +4527,13 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
         }
     };
 
-    let game_resources = collect_game_resources(gc_disc, starting_memo);
+    let generated_hudmemos = generated_hudmemos(config);
+    let generated_hudmemos = &generated_hudmemos;
+    let game_resources = collect_game_resources(
+        gc_disc,
+        starting_memo,
+        generated_hudmemos,
+    );
     let game_resources = &game_resources;
 
     // XXX These values need to out live the patcher
@@ -2809,8 +4543,8 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
     let n = format!("Video/04_fileselect_playgame_{}.thp", select_game_fmv_suffix);
     let file_select_play_game_fmv = gc_disc.find_file(&n).unwrap().file().unwrap().clone();
 
-    let mut patcher = PrimePatcher::new();
-    patcher.add_file_patch(b"opening.bnr", |file| patch_bnr(file, &config.game_banner));
+    let mut patcher = PrimePatcher::new(PATCHES, version, &config.disabled_patches);
+    patcher.add_file_patch("game_banner", b"opening.bnr", |file| patch_bnr(file, &config.game_banner));
     if !config.keep_fmvs {
         // Replace the attract mode FMVs with empty files to reduce the amount of data we need to
         // copy and to make compressed ISOs smaller.
@@ -2829,7 +4563,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
         ];
         const FMV: &[u8] = include_bytes!("../extra_assets/attract_mode.thp");
         for name in FMV_NAMES {
-            patcher.add_file_patch(name, |file| {
+            patcher.add_file_patch("remove_attract_mode_fmvs", name, |file| {
                 *file = structs::FstEntryFile::ExternalFile(Box::new(FMV));
                 Ok(())
             });
@@ -2842,7 +4576,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
             b"Audio/rui_flaaghraL.dsp",
         ];
         for (file_name, music_file) in MUSIC_FILE_NAME.iter().zip(flaahgra_music_files.iter()) {
-            patcher.add_file_patch(file_name, move |file| {
+            patcher.add_file_patch("flaahgra_music", file_name, move |file| {
                 *file = structs::FstEntryFile::ExternalFile(Box::new(music_file.clone()));
                 Ok(())
             });
@@ -2864,17 +4598,20 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
         } else {
             &file_select_play_game_fmv
         };
-        patcher.add_file_patch(fmv_name, move |file| {
+        patcher.add_file_patch("select_game_fmvs", fmv_name, move |file| {
             *file = fmv_ref.clone();
             Ok(())
         });
     }
 
+    add_room_info_touched_objects(&mut patcher);
+
     // Patch pickups
-    let mut layout_iterator = pickup_layout.iter();
+    let pickup_patch_config = PickupPatchConfig::new(config);
+    let mut layout_iterator = pickup_layout.iter().enumerate();
     for (name, rooms) in pickup_meta::ROOM_INFO.iter() {
         for room_info in rooms.iter() {
-             patcher.add_scly_patch((name.as_bytes(), room_info.room_id.to_u32()), move |_, area| {
+             patcher.add_scly_patch("remove_room_objects", (name.as_bytes(), room_info.room_id.to_u32()), move |_, area| {
                 // Remove objects
                 let layers = area.mrea().scly_section_mut().layers.as_mut_vec();
                 for otr in room_info.objects_to_remove {
@@ -2884,7 +4621,14 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
                 Ok(())
             });
             let iter = room_info.pickup_locations.iter().zip(&mut layout_iterator);
-            for (&pickup_location, &pickup_type) in iter {
+            for (&pickup_location, (location_idx, &pickup_type)) in iter {
+                let expansion_amount = config.expansion_amount(location_idx, pickup_type);
+                let remote_pickup = config.remote_pickup(location_idx)
+                    .map(|remote_pickup| (location_idx, remote_pickup));
+                let pickup_type = match remote_pickup {
+                    Some((_, remote_pickup)) => remote_pickup.pickup,
+                    None => pickup_type,
+                };
                 // 1 in 1024 chance of a missile being shiny means a player is likely to see a
                 // shiny missile every 40ish games (assuming most players collect about half of the
                 // missiles)
@@ -2894,6 +4638,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
                     pickup_type
                 };
                 patcher.add_scly_patch(
+                    "pickups",
                     (name.as_bytes(), room_info.room_id.to_u32()),
                     move |ps, area| modify_pickups_in_mrea(
                             ps,
@@ -2901,13 +4646,111 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
                             pickup_type,
                             pickup_location,
                             game_resources,
-                            config
+                            pickup_patch_config,
+                            artifact_layers,
+                            expansion_amount,
+                            generated_hudmemos,
+                            remote_pickup,
                         )
                 );
             }
         }
     }
 
+    // Apply declarative script edits and layer overrides from the room configs. The script
+    // edits are added last, see below.
+    let mut script_edits = vec![];
+    for (name, rooms) in pickup_meta::ROOM_INFO.iter() {
+        let world = World::from_pak(name).unwrap();
+        let level = match config.level_data.get(world.to_json_key()) {
+            Some(level) => level,
+            None => continue,
+        };
+        for room_key in level.rooms.keys() {
+            let matches = rooms.iter()
+                .filter(|room_info| room_key_matches(room_key, room_info))
+                .count();
+            if matches == 0 {
+                Err(format!("Unknown room \"{}\" in level \"{}\"", room_key, world.to_json_key()))?;
+            } else if matches > 1 {
+                Err(format!(
+                    "More than one room in level \"{}\" is named \"{}\"; use its MREA id \
+                     (e.g. \"0x{:08X}\") instead",
+                    world.to_json_key(),
+                    room_key,
+                    rooms.iter().find(|r| r.name == room_key).unwrap().room_id.to_u32(),
+                ))?;
+            }
+        }
+        let mut layer_toggles = vec![];
+        // The rooms are listed in the same order as the MLVL's areas
+        for (area_idx, room_info) in rooms.iter().enumerate() {
+            let mut configs = level.rooms.iter()
+                .filter(|(key, _)| room_key_matches(key, room_info));
+            let room = match (configs.next(), configs.next()) {
+                (Some((_, room)), None) => room,
+                (Some(_), Some(_)) => Err(format!(
+                    "Room \"{}\" (0x{:08X}) in level \"{}\" is configured more than once",
+                    room_info.name,
+                    room_info.room_id.to_u32(),
+                    world.to_json_key(),
+                ))?,
+                (None, _) => continue,
+            };
+            if !room.script_edits.is_empty() {
+                script_edits.push((name, room_info.room_id.to_u32(), &room.script_edits[..]));
+            }
+            if !room.layers.is_empty() {
+                let layers = &room.layers;
+                patcher.add_scly_patch(
+                    "room_layers",
+                    (name.as_bytes(), room_info.room_id.to_u32()),
+                    move |_ps, area| patch_room_layers(area, layers)
+                );
+                let mut layer_indices: Vec<u32> = layers.keys().copied().collect();
+                layer_indices.sort();
+                layer_toggles.extend(layer_indices.into_iter().map(|layer_index| {
+                    structs::LayerToggle {
+                        area_id: area_idx as u32,
+                        layer_index,
+                    }
+                }));
+            }
+        }
+        if !layer_toggles.is_empty() {
+            patcher.add_resource_patch(
+                "room_layers",
+                (&[name.as_bytes()], world.savw(), b"SAVW".into()),
+                move |res| patch_savw_layer_toggles(res, &layer_toggles)
+            );
+        }
+    }
+
+    if let Some(enemy_stats) = &config.enemy_stats {
+        let seed = config.layout.seed;
+        for (name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            for room_info in rooms.iter() {
+                patcher.add_scly_patch(
+                    "enemy_stats",
+                    (name.as_bytes(), room_info.room_id.to_u32()),
+                    move |_ps, area| patch_enemy_stats(area, enemy_stats, seed)
+                );
+            }
+        }
+    }
+
+    if !enemy_weaknesses.is_empty() {
+        for (name, rooms) in pickup_meta::ROOM_INFO.iter() {
+            for room_info in rooms.iter() {
+                patcher.add_scly_patch(
+                    "enemy_weaknesses",
+                    (name.as_bytes(), room_info.room_id.to_u32()),
+                    move |_ps, area| patch_enemy_weaknesses(area, enemy_weaknesses)
+                );
+            }
+        }
+    }
+
     let (skip_frigate, skip_ending_cinematic) = make_elevators_patch(
         &mut patcher,
         &config.level_data,
@@ -2916,6 +4759,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
 
     // set save spawn room
     patcher.add_file_patch(
+        "dol",
         b"default.dol",
         |file| patch_dol(
             file,
@@ -2925,14 +4769,29 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
         )
     );
 
-    let rel_config = create_rel_config_file(starting_room, config.quickplay);
+    let rel_config = create_rel_config_file(
+        starting_room,
+        config.quickplay,
+        config.item_tracker,
+        config.multiworld.is_some(),
+        config.practice_mode,
+    );
+
+    // Registered before the frigate is (possibly) emptied, which replaces this patch for it
+    for (name, _) in pickup_meta::ROOM_INFO.iter() {
+        let world = World::from_pak(name).unwrap();
+        if config.starting_items.maps.contains(&world) {
+            patcher.add_file_patch("starting_maps", name.as_bytes(), patch_starting_maps);
+        }
+    }
 
     if skip_frigate && starting_room.mlvl != World::FrigateOrpheon.mlvl(){
         // remove frigate data to save time/space
-        patcher.add_file_patch(b"Metroid1.pak", empty_frigate_pak);
+        patcher.add_file_patch("remove_frigate", b"Metroid1.pak", empty_frigate_pak);
     } else {
         // redirect end of frigate cutscene to room specified in layout
         patcher.add_scly_patch(
+            "frigate_teleporter",
             resource_info!("01_intro_hanger.MREA").into(),
             move |_ps, area| patch_frigate_teleporter(area, frigate_done_room),
         );
@@ -2943,6 +4802,22 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
         structs::FstEntryFile::ExternalFile(Box::new(rel_config)),
     )?;
 
+    if !config.starting_items.scans.is_empty() {
+        let starting_scans = create_starting_scans_file(gc_disc, &config.starting_items.scans);
+        gc_disc.add_file(
+            "starting_scans.bin",
+            structs::FstEntryFile::ExternalFile(Box::new(starting_scans)),
+        )?;
+    }
+
+    if config.practice_mode {
+        let frigate_removed = skip_frigate && starting_room.mlvl != World::FrigateOrpheon.mlvl();
+        gc_disc.add_file(
+            "practice_warps.bin",
+            structs::FstEntryFile::ExternalFile(Box::new(create_practice_warps_file(!frigate_removed))),
+        )?;
+    }
+
     const ARTIFACT_TOTEM_SCAN_STRGS: &[ResourceInfo] = &[
         resource_info!("07_Over_Stonehenge Totem 5.STRG"), // Lifegiver
         resource_info!("07_Over_Stonehenge Totem 4.STRG"), // Wild
@@ -2959,221 +4834,306 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
     ];
     for (res_info, strg_text) in ARTIFACT_TOTEM_SCAN_STRGS.iter().zip(artifact_totem_strings.iter()) {
         patcher.add_resource_patch(
+            "artifact_totem_scans",
             (*res_info).into(),
             move |res| patch_artifact_totem_scan_strg(res, &strg_text),
         );
     }
 
+    let main_menu_message = &config.main_menu_message;
     patcher.add_resource_patch(
+        "main_menu_message",
         resource_info!("STRG_Main.STRG").into(),// 0x0552a456
-        |res| patch_main_strg(res, &config.main_menu_message)
+        move |res| patch_main_strg(res, main_menu_message)
     );
     patcher.add_resource_patch(
+        "main_menu",
         resource_info!("FRME_NewFileSelect.FRME").into(),
         patch_main_menu
     );
 
+    let credits_string = &config.credits_string;
     patcher.add_resource_patch(
+        "credits",
         resource_info!("STRG_Credits.STRG").into(),
-        |res| patch_credits(res, &pickup_layout, config.credits_string.clone())
+        move |res| patch_credits(res, &pickup_layout, credits_string.clone())
     );
 
     patcher.add_resource_patch(
+        "mines_savw_for_phazon_suit_scan",
         resource_info!("!MinesWorld_Master.SAVW").into(),
         patch_mines_savw_for_phazon_suit_scan
     );
     patcher.add_scly_patch(
+        "artifact_of_truth_requirements",
         resource_info!("07_stonehenge.MREA").into(),
-        |ps, area| fix_artifact_of_truth_requirements(ps, area, &pickup_layout)
+        move |ps, area| fix_artifact_of_truth_requirements(ps, area, &pickup_layout, artifact_layers)
     );
+    for (i, layer) in artifact_layers.iter().enumerate() {
+        if let ArtifactTempleLayer::Boss(boss) = *layer {
+            let (pak_name, room_name, is_boss) = temple_boss_room(boss);
+            let room_id = pickup_meta::ROOM_INFO.iter()
+                .find(|(name, _)| *name == pak_name)
+                .and_then(|(_, rooms)| rooms.iter().find(|room_info| room_info.name == room_name))
+                .unwrap()
+                .room_id;
+            patcher.add_scly_patch(
+                "temple_boss_requirements",
+                (pak_name.as_bytes(), room_id.to_u32()),
+                move |ps, area| patch_temple_boss_requirement(ps, area, is_boss, i)
+            );
+        }
+    }
+    if config.artifact_temple.skip_ridley {
+        patcher.add_scly_patch(
+            "artifact_temple_skip_ridley",
+            resource_info!("07_stonehenge.MREA").into(),
+            patch_artifact_temple_skip_ridley
+        );
+    }
+    if config.artifact_temple.skip_impact_crater {
+        patcher.add_scly_patch(
+            "artifact_temple_skip_impact_crater",
+            resource_info!("07_stonehenge.MREA").into(),
+            patch_artifact_temple_skip_impact_crater
+        );
+    }
+    let artifact_hint_behavior = config.artifact_hint_behavior;
     patcher.add_scly_patch(
+        "artifact_hint_availability",
         resource_info!("07_stonehenge.MREA").into(),
-        |ps, area| patch_artifact_hint_availability(ps, area, config.artifact_hint_behavior)
+        move |ps, area| patch_artifact_hint_availability(ps, area, artifact_hint_behavior)
     );
 
     patcher.add_resource_patch(
+        "save_banner",
         resource_info!("TXTR_SaveBanner.TXTR").into(),
         patch_save_banner_txtr
     );
 
+    let starting_items = &config.starting_items;
+    let etank_capacity = config.etank_capacity;
+    if let Some(spawn_point) = config.starting_spawn_point {
+        patcher.add_scly_patch(
+            "starting_spawn_point",
+            (starting_room.pak_name.as_bytes(), starting_room.mrea),
+            move |ps, area| patch_starting_spawn_point(
+                ps,
+                area,
+                spawn_point,
+                starting_items,
+            )
+        );
+    }
+
     patcher.add_scly_patch(
+        "starting_pickups",
         (starting_room.pak_name.as_bytes(), starting_room.mrea),
         move |_ps, area| patch_starting_pickups(
             area,
-            &config.starting_items,
+            starting_items,
             show_starting_memo,
+            etank_capacity,
             &game_resources,
         )
     );
 
     if !skip_frigate {
         patcher.add_scly_patch(
+            "post_escape_pickups",
             resource_info!("02_intro_elevator.MREA").into(),
             move |_ps, area| patch_starting_pickups(
                 area,
-                &config.item_loss_items,
+                post_escape_items,
                 false,
+                etank_capacity,
                 &game_resources,
             )
         );
 
         // TODO: only works for landing site
         patcher.add_scly_patch(
+            "post_escape_pickups",
             (frigate_done_room.pak_name.as_bytes(), frigate_done_room.mrea),
             move |_ps, area| patch_starting_pickups(
                 area,
-                &config.item_loss_items,
+                post_escape_items,
                 false,
+                etank_capacity,
                 &game_resources,
             )
         );
+
+        if !lost_items.is_empty() {
+            patcher.add_scly_patch(
+                "replace_lost_items",
+                (frigate_done_room.pak_name.as_bytes(), frigate_done_room.mrea),
+                move |ps, area| patch_replace_lost_items(ps, area, lost_items, &game_resources)
+            );
+        }
     }
 
-    patcher.add_resource_patch(resource_info!("FRME_BallHud.FRME").into(), patch_morphball_hud);
+    patcher.add_resource_patch(
+        "morphball_hud",
+        resource_info!("FRME_BallHud.FRME").into(),
+        patch_morphball_hud,
+    );
 
     make_elite_research_fight_prereq_patches(&mut patcher);
 
     patch_heat_damage_per_sec(&mut patcher, config.heat_damage_per_sec);
 
     patcher.add_scly_patch(
+        "sunchamber_prevent_wild_before_flaahgra",
         resource_info!("22_Flaahgra.MREA").into(),
         patch_sunchamber_prevent_wild_before_flaahgra
     );
     patcher.add_scly_patch(
+        "sun_tower_prevent_wild_before_flaahgra",
         resource_info!("0v_connect_tunnel.MREA").into(),
         patch_sun_tower_prevent_wild_before_flaahgra
     );
     patcher.add_scly_patch(
+        "temple_security_station_cutscene_trigger",
         resource_info!("00j_over_hall.MREA").into(),
         patch_temple_security_station_cutscene_trigger
     );
     patcher.add_scly_patch(
+        "ridley_phendrana_shorelines_cinematic",
         resource_info!("01_ice_plaza.MREA").into(),
         patch_ridley_phendrana_shorelines_cinematic
     );
     patcher.add_scly_patch(
+        "mqa_cinematic",
         resource_info!("08_mines.MREA").into(),
         patch_mqa_cinematic
     );
     patcher.add_scly_patch(
+        "main_ventilation_shaft_section_b_door",
         resource_info!("08b_under_intro_ventshaft.MREA").into(),
         patch_main_ventilation_shaft_section_b_door
     );
     patcher.add_scly_patch(
+        "research_lab_hydra_barrier",
         resource_info!("10_ice_research_a.MREA").into(),
         patch_research_lab_hydra_barrier
     );
     patcher.add_scly_patch(
+        "lab_aether_cutscene_trigger",
         resource_info!("12_ice_research_b.MREA").into(),
         move |ps, area| patch_lab_aether_cutscene_trigger(ps, area, version)
     );
     patcher.add_scly_patch(
+        "research_lab_aether_exploding_wall",
         resource_info!("13_ice_vault.MREA").into(),
         patch_research_lab_aether_exploding_wall
     );
     patcher.add_scly_patch(
+        "observatory_2nd_pass_solvablility",
         resource_info!("11_ice_observatory.MREA").into(),
         patch_observatory_2nd_pass_solvablility
     );
     patcher.add_scly_patch(
+        "observatory_1st_pass_softlock",
         resource_info!("11_ice_observatory.MREA").into(),
         patch_observatory_1st_pass_softlock
     );
     patcher.add_scly_patch(
+        "mines_security_station_soft_lock",
         resource_info!("02_mines_shotemup.MREA").into(),
         patch_mines_security_station_soft_lock
     );
     patcher.add_scly_patch(
+        "gravity_chamber_stalactite_grapple_point",
         resource_info!("18_ice_gravity_chamber.MREA").into(),
         patch_gravity_chamber_stalactite_grapple_point
     );
     patcher.add_scly_patch(
+        "main_quarry_barrier",
         resource_info!("01_mines_mainplaza.MREA").into(),
         patch_main_quarry_barrier
     );
 
-    if version == Version::NtscU0_00 {
-        patcher.add_scly_patch(
-            resource_info!("00n_ice_connect.MREA").into(),
-            patch_research_core_access_soft_lock
-        );
-    } else {
-        patcher.add_scly_patch(
-            resource_info!("08_courtyard.MREA").into(),
-            patch_arboretum_invisible_wall
-        );
-        if version != Version::NtscU0_01 {
-            patcher.add_scly_patch(
-                resource_info!("05_ice_shorelines.MREA").into(),
-                move |ps, area| patch_ruined_courtyard_thermal_conduits(ps, area, version)
-            );
-        }
-    }
-
-    if version == Version::NtscU0_02 {
-        patcher.add_scly_patch(
-            resource_info!("01_mines_mainplaza.MREA").into(),
-            patch_main_quarry_door_lock_0_02
-        );
-        patcher.add_scly_patch(
-            resource_info!("13_over_burningeffigy.MREA").into(),
-            patch_geothermal_core_door_lock_0_02
-        );
-        patcher.add_scly_patch(
-            resource_info!("19_hive_totem.MREA").into(),
-            patch_hive_totem_boss_trigger_0_02
-        );
-    }
-
-    if version == Version::Pal || version == Version::NtscJ || version == Version::NtscUTrilogy || version == Version::NtscJTrilogy || version == Version::PalTrilogy {
-        patcher.add_scly_patch(
-            resource_info!("04_mines_pillar.MREA").into(),
-            patch_ore_processing_destructible_rock_pal
-        );
-        patcher.add_scly_patch(
-            resource_info!("13_over_burningeffigy.MREA").into(),
-            patch_geothermal_core_destructible_rock_pal
-        );
-
-        if version == Version::Pal {
-            patcher.add_scly_patch(
-                resource_info!("01_mines_mainplaza.MREA").into(),
-                patch_main_quarry_door_lock_pal
-            );
-        }
-    }
+    // These are only applied to the versions listed for them in `PATCHES`
+    patcher.add_scly_patch(
+        "research_core_access_soft_lock",
+        resource_info!("00n_ice_connect.MREA").into(),
+        patch_research_core_access_soft_lock
+    );
+    patcher.add_scly_patch(
+        "arboretum_invisible_wall",
+        resource_info!("08_courtyard.MREA").into(),
+        patch_arboretum_invisible_wall
+    );
+    patcher.add_scly_patch(
+        "ruined_courtyard_thermal_conduits",
+        resource_info!("05_ice_shorelines.MREA").into(),
+        move |ps, area| patch_ruined_courtyard_thermal_conduits(ps, area, version)
+    );
+    patcher.add_scly_patch(
+        "main_quarry_door_lock_0_02",
+        resource_info!("01_mines_mainplaza.MREA").into(),
+        patch_main_quarry_door_lock_0_02
+    );
+    patcher.add_scly_patch(
+        "geothermal_core_door_lock_0_02",
+        resource_info!("13_over_burningeffigy.MREA").into(),
+        patch_geothermal_core_door_lock_0_02
+    );
+    patcher.add_scly_patch(
+        "hive_totem_boss_trigger_0_02",
+        resource_info!("19_hive_totem.MREA").into(),
+        patch_hive_totem_boss_trigger_0_02
+    );
+    patcher.add_scly_patch(
+        "ore_processing_destructible_rock_pal",
+        resource_info!("04_mines_pillar.MREA").into(),
+        patch_ore_processing_destructible_rock_pal
+    );
+    patcher.add_scly_patch(
+        "geothermal_core_destructible_rock_pal",
+        resource_info!("13_over_burningeffigy.MREA").into(),
+        patch_geothermal_core_destructible_rock_pal
+    );
+    patcher.add_scly_patch(
+        "main_quarry_door_lock_pal",
+        resource_info!("01_mines_mainplaza.MREA").into(),
+        patch_main_quarry_door_lock_pal
+    );
 
-    if starting_room.mrea != SpawnRoom::LandingSite.spawn_room_data().mrea {
+    let landing_site_mrea = SpawnRoom::LandingSite.spawn_room_data().mrea;
+    if starting_room.mrea != landing_site_mrea || config.starting_spawn_point.is_some() {
         // If we have a non-default start point, patch the landing site to avoid
         // weirdness with cutscene triggers and the ship spawning.
         patcher.add_scly_patch(
+            "landing_site_cutscene_triggers",
             resource_info!("01_over_mainplaza.MREA").into(),
             patch_landing_site_cutscene_triggers
         );
     }
 
-    if skip_ending_cinematic {
+    if skip_ending_cinematic || config.artifact_temple.skip_impact_crater {
         patcher.add_scly_patch(
+            "ending_scene_straight_to_credits",
             resource_info!("01_endcinema.MREA").into(),
             patch_ending_scene_straight_to_credits
         );
     }
 
-    if version == Version::NtscU0_00 {
-        patcher.add_scly_patch(
-            resource_info!("03f_crater.MREA").into(),
-            patch_essence_cinematic_skip_whitescreen
-        );
-    }
-    if [Version::NtscU0_00, Version::NtscU0_02, Version::Pal].contains(&version) {
-        patcher.add_scly_patch(
-            resource_info!("03f_crater.MREA").into(),
-            patch_essence_cinematic_skip_nomusic
-        );
-    }
+    patcher.add_scly_patch(
+        "essence_cinematic_skip_whitescreen",
+        resource_info!("03f_crater.MREA").into(),
+        patch_essence_cinematic_skip_whitescreen
+    );
+    patcher.add_scly_patch(
+        "essence_cinematic_skip_nomusic",
+        resource_info!("03f_crater.MREA").into(),
+        patch_essence_cinematic_skip_nomusic
+    );
 
     if config.enable_vault_ledge_door {
         patcher.add_scly_patch(
+            "vault_ledge_door",
             resource_info!("01_mainplaza.MREA").into(),
             make_main_plaza_locked_door_two_ways
         );
@@ -3185,7 +5145,7 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
             .chain(crate::txtr_conversions::POWER_SUIT_TEXTURES.iter())
             .chain(crate::txtr_conversions::GRAVITY_SUIT_TEXTURES.iter());
         for varia_texture in iter {
-            patcher.add_resource_patch((*varia_texture).into(), move |res| {
+            patcher.add_resource_patch("suit_hue_rotation", (*varia_texture).into(), move |res| {
                 let res_data = crate::ResourceData::new(res);
                 let data = res_data.decompress();
                 let mut reader = Reader::new(&data[..]);
@@ -3210,7 +5170,161 @@ fn build_and_run_patches(gc_disc: &mut structs::GcDisc, config: &PatchConfig, ve
         }
     }
 
-    patcher.run(gc_disc)?;
-    Ok(())
+    // After every built-in patch, so those see the objects they expect and the edits aren't
+    // undone by them
+    for (name, room_id, edits) in script_edits {
+        patcher.add_touched_objects(
+            "script_edits",
+            room_id,
+            edits.iter().filter_map(script_edit_target),
+        );
+        patcher.add_scly_patch(
+            "script_edits",
+            (name.as_bytes(), room_id),
+            move |ps, area| patch_script_edits(ps, area, edits)
+        );
+    }
+
+    patcher.run(gc_disc)
 }
 
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn artifact_temple_layers_follow_totem_order()
+    {
+        let config = ArtifactTempleConfig {
+            required_artifacts: 2,
+            required_bosses: vec![TempleBoss::Flaahgra],
+            ..ArtifactTempleConfig::default()
+        };
+        let pickup_layout = [
+            PickupType::ArtifactOfLifegiver,
+            PickupType::ArtifactOfStrength,
+            PickupType::ArtifactOfTruth,
+        ];
+        assert_eq!(
+            config.required_artifact_types(&pickup_layout),
+            vec![PickupType::ArtifactOfTruth, PickupType::ArtifactOfStrength],
+        );
+
+        let layers = plan_artifact_temple_layers(&pickup_layout, &[], &config).unwrap();
+        for pt in PickupType::iter().filter(|pt| pt.is_artifact()) {
+            let layer = layers[(pt.pickup_data().kind - 29) as usize];
+            match pt {
+                PickupType::ArtifactOfTruth | PickupType::ArtifactOfStrength =>
+                    assert_eq!(layer, ArtifactTempleLayer::Artifact),
+                _ => assert_ne!(layer, ArtifactTempleLayer::Artifact),
+            }
+        }
+        assert_eq!(layers[11], ArtifactTempleLayer::Boss(TempleBoss::Flaahgra));
+
+        // Starting with an artifact leaves its totem active
+        let layers = plan_artifact_temple_layers(
+            &pickup_layout,
+            &[PickupType::ArtifactOfTruth],
+            &config,
+        ).unwrap();
+        assert_eq!(layers[0], ArtifactTempleLayer::Active);
+    }
+
+    #[test]
+    fn duplicate_room_names_can_be_keyed_by_mrea_id()
+    {
+        let name = "Connection Elevator to Deck Beta";
+        let (_, rooms) = pickup_meta::ROOM_INFO.iter()
+            .find(|(_, rooms)| rooms.iter().any(|room_info| room_info.name == name))
+            .unwrap();
+        let count = |key: &str| rooms.iter()
+            .filter(|room_info| room_key_matches(key, room_info))
+            .count();
+        assert_eq!(count(name), 2);
+        assert_eq!(count("0x6ED3231B"), 1);
+        assert_eq!(count("0x6ed3231b"), 1);
+        assert_eq!(count("0x12345678"), 0);
+    }
+
+    #[test]
+    fn script_edits_are_added_last()
+    {
+        // The registry lists the patches in the order they're added, and script edits must run
+        // after every built-in patch that could touch the same objects
+        assert_eq!(PATCHES.last().unwrap().name, "script_edits");
+    }
+
+    // Runs a progressive pickup's objects the way the game does when the pickup is collected,
+    // returning the items given to a player who has `owned`
+    fn collect_progressive_pickup(items: &[PickupType], owned: &[PickupType]) -> Vec<PickupType>
+    {
+        fn send(
+            objects: &mut [structs::SclyObject],
+            owned: &[PickupType],
+            given: &mut Vec<PickupType>,
+            conn: &structs::Connection,
+        )
+        {
+            let i = objects.iter().position(|obj| obj.instance_id == conn.target_object_id)
+                .unwrap();
+            if let Some(activator) = objects[i].property_data.as_special_function() {
+                assert_eq!(conn.message, structs::ConnectionMsg::ACTION);
+                if owned.iter().any(|pt| pt.pickup_data().kind == activator.item_id) {
+                    let connections = objects[i].connections.as_mut_vec().clone();
+                    for conn in &connections {
+                        assert_eq!(conn.state, structs::ConnectionState::ZERO);
+                        send(objects, owned, given, conn);
+                    }
+                }
+                return;
+            }
+            let state_change = objects[i].property_data.as_player_state_change_mut().unwrap();
+            if conn.message == structs::ConnectionMsg::ACTIVATE {
+                state_change.active = 1;
+            } else if conn.message == structs::ConnectionMsg::DEACTIVATE {
+                state_change.active = 0;
+            } else if state_change.active != 0 {
+                assert_eq!(conn.message, structs::ConnectionMsg::SET_TO_ZERO);
+                let pt = PickupType::iter()
+                    .find(|pt| pt.pickup_data().kind == state_change.item)
+                    .unwrap();
+                given.push(pt);
+            }
+        }
+
+        let mut next_id = 0;
+        let (mut objects, connections) = progressive_item_objects(items, || {
+            next_id += 1;
+            next_id
+        });
+        let mut given = vec![];
+        for conn in &connections {
+            assert_eq!(conn.state, structs::ConnectionState::ARRIVED);
+            send(&mut objects, owned, &mut given, conn);
+        }
+        given
+    }
+
+    #[test]
+    fn progressive_pickups_give_the_first_missing_item()
+    {
+        let beams = [PickupType::WaveBeam, PickupType::IceBeam, PickupType::PlasmaBeam];
+        assert_eq!(collect_progressive_pickup(&beams, &[]), vec![PickupType::WaveBeam]);
+        assert_eq!(
+            collect_progressive_pickup(&beams, &[PickupType::WaveBeam]),
+            vec![PickupType::IceBeam],
+        );
+        // A later item doesn't skip an earlier one
+        assert_eq!(
+            collect_progressive_pickup(&beams, &[PickupType::IceBeam]),
+            vec![PickupType::WaveBeam],
+        );
+        assert_eq!(
+            collect_progressive_pickup(&beams, &[PickupType::WaveBeam, PickupType::PlasmaBeam]),
+            vec![PickupType::IceBeam],
+        );
+        assert_eq!(collect_progressive_pickup(&beams, &beams), vec![]);
+    }
+}
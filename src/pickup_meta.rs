@@ -1,13 +1,13 @@
-use std::mem;
+use std::{iter, mem};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use reader_writer::{FourCC, Reader};
 use structs::{Connection, ConnectionMsg, ConnectionState, Pickup, ResId, res_id};
 
 use crate::custom_assets::custom_asset_ids;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum PickupType
 {
@@ -205,6 +205,17 @@ impl PickupType
         }
     }
 
+    /// Whether the amount this pickup gives can be configured
+    pub fn is_expansion(&self) -> bool
+    {
+        match self {
+            PickupType::Missile =>            true,
+            PickupType::EnergyTank =>         true,
+            PickupType::PowerBombExpansion => true,
+            _ => false,
+        }
+    }
+
     pub fn skip_hudmemos_strg(&self) -> ResId<res_id::STRG>
     {
         let start = custom_asset_ids::SKIP_HUDMEMO_STRG_START.to_u32();
@@ -362,4 +373,12 @@ pub struct ObjectsToRemove
     pub instance_ids: &'static [u32],
 }
 
+/// The name of the room each pickup location is in, in layout order
+pub fn pickup_location_room_names() -> impl Iterator<Item = &'static str>
+{
+    ROOM_INFO.iter()
+        .flat_map(|(_, rooms)| rooms.iter())
+        .flat_map(|room| iter::repeat(room.name).take(room.pickup_locations.len()))
+}
+
 include!("pickup_meta.rs.in");
@@ -0,0 +1,96 @@
+use serde_json::{json, Value};
+
+use crate::{
+    elevators::{Elevator, World},
+    logic,
+    normal_difficulty,
+    patch_config::{ArtifactTempleConfig, Layout, ProgressiveItem},
+    pickup_meta::{self, PickupType},
+    starting_items::StartingItems,
+};
+
+use std::fs;
+
+/// A machine-readable description of everything randomized in a layout. The playthrough is
+/// only included when the layout can be checked against the normal difficulty's requirements.
+pub fn spoiler_log(
+    layout: &Layout,
+    starting_items: &StartingItems,
+    progressive_items: &[ProgressiveItem],
+    artifact_temple: &ArtifactTempleConfig,
+) -> Value
+{
+    let location_names: Vec<_> = pickup_meta::ROOM_INFO.iter()
+        .flat_map(|(pak_name, rooms)| {
+            let world = World::from_pak(pak_name).unwrap();
+            rooms.iter()
+                .flat_map(move |room| {
+                    (0..room.pickup_locations.len()).map(move |_| (world.to_str(), room.name))
+                })
+        })
+        .collect();
+    let location_json = |i: usize| {
+        let (world, room) = location_names[i];
+        json!({
+            "index": i,
+            "world": world,
+            "room": room,
+            "pickup": layout.pickups[i].name(),
+        })
+    };
+
+    // Elevator names have a null between the area and room names
+    let elevator_name = |name: &str| name.replace('\0', " ");
+    let elevators: serde_json::Map<_, _> = Elevator::iter()
+        .map(|elv| (
+            elevator_name(elv.elevator_data().name),
+            json!(elevator_name(layout.elevators[elv].name)),
+        ))
+        .collect();
+
+    let difficulty = normal_difficulty::difficulty();
+    let goal = difficulty.goal(&layout.pickups, artifact_temple);
+    let playthrough = logic::check_solvability(
+        &difficulty,
+        layout,
+        starting_items,
+        progressive_items,
+        &goal,
+    )
+        .ok()
+        .map(|report| {
+            report.spheres.iter()
+                .map(|sphere| {
+                    sphere.iter()
+                        .filter(|i| layout.pickups[**i] != PickupType::Nothing)
+                        .map(|i| location_json(*i))
+                        .collect::<Vec<_>>()
+                })
+                .filter(|sphere| !sphere.is_empty())
+                .collect::<Vec<_>>()
+        });
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "seed": layout.seed,
+        "startingLocation": layout.starting_location.name,
+        "startingItems": starting_items,
+        "elevators": elevators,
+        "locations": (0..layout.pickups.len()).map(location_json).collect::<Vec<_>>(),
+        "playthrough": playthrough,
+    })
+}
+
+pub fn write_spoiler_log(
+    path: &str,
+    layout: &Layout,
+    starting_items: &StartingItems,
+    progressive_items: &[ProgressiveItem],
+    artifact_temple: &ArtifactTempleConfig,
+) -> Result<(), String>
+{
+    let spoiler = spoiler_log(layout, starting_items, progressive_items, artifact_temple);
+    let json = serde_json::to_string_pretty(&spoiler)
+        .map_err(|e| format!("Failed to serialize spoiler log: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
@@ -1,14 +1,19 @@
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
 use std::cmp;
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+use crate::{
+    elevators::World,
+    pickup_meta::PickupType,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartingItems
 {
     pub scan_visor: bool,
-    pub missiles: u8,
-    pub energy_tanks: u8,
-    pub power_bombs: u8,
+    pub missiles: u32,
+    pub energy_tanks: u32,
+    pub power_bombs: u32,
     pub wave: bool,
     pub ice: bool,
     pub plasma: bool,
@@ -28,6 +33,17 @@ pub struct StartingItems
     pub wavebuster: bool,
     pub ice_spreader: bool,
     pub flamethrower: bool,
+    #[serde(default)]
+    pub artifacts: Vec<PickupType>,
+    // Worlds whose map is shown as if their map station had been used
+    #[serde(default)]
+    pub maps: Vec<World>,
+    // Worlds whose logbook entries start out scanned
+    #[serde(default)]
+    pub scans: Vec<World>,
+    // The energy to start with, instead of being fully healed
+    #[serde(default)]
+    pub health: Option<u32>,
 }
 
 impl StartingItems
@@ -37,7 +53,7 @@ impl StartingItems
         let mut fetch_bits = move |bits: u8| {
             let ret = starting_items & ((1 << bits) - 1);
             starting_items >>= bits;
-            ret as u8
+            ret as u32
         };
 
         StartingItems {
@@ -64,15 +80,19 @@ impl StartingItems
             wavebuster:  fetch_bits(1) == 1,
             ice_spreader:  fetch_bits(1) == 1,
             flamethrower:  fetch_bits(1) == 1,
+            artifacts: vec![],
+            maps: vec![],
+            scans: vec![],
+            health: None,
         }
     }
 
     pub fn update_spawn_point(&self, spawn_point: &mut structs::SpawnPoint)
     {
         spawn_point.scan_visor = self.scan_visor as u32;
-        spawn_point.missiles = self.missiles as u32;
-        spawn_point.energy_tanks = self.energy_tanks as u32;
-        spawn_point.power_bombs = self.power_bombs as u32;
+        spawn_point.missiles = self.missiles;
+        spawn_point.energy_tanks = self.energy_tanks;
+        spawn_point.power_bombs = self.power_bombs;
         spawn_point.wave = self.wave as u32;
         spawn_point.ice = self.ice as u32;
         spawn_point.plasma = self.plasma as u32;
@@ -140,9 +160,139 @@ impl StartingItems
             wavebuster: manual_starting_items.wavebuster | random_starting_items.wavebuster,
             ice_spreader: manual_starting_items.ice_spreader | random_starting_items.ice_spreader,
             flamethrower: manual_starting_items.flamethrower | random_starting_items.flamethrower,
+            artifacts: PickupType::iter()
+                .filter(|pt| {
+                    manual_starting_items.artifacts.contains(pt)
+                        || random_starting_items.artifacts.contains(pt)
+                })
+                .collect(),
+            maps: World::iter()
+                .filter(|world| {
+                    manual_starting_items.maps.contains(world)
+                        || random_starting_items.maps.contains(world)
+                })
+                .collect(),
+            scans: World::iter()
+                .filter(|world| {
+                    manual_starting_items.scans.contains(world)
+                        || random_starting_items.scans.contains(world)
+                })
+                .collect(),
+            health: manual_starting_items.health.or(random_starting_items.health),
         }
     }
     
+    /// Whether the player starts with (at least one of) the given item
+    pub fn contains(&self, pickup_type: PickupType) -> bool
+    {
+        match pickup_type {
+            PickupType::ScanVisor => self.scan_visor,
+            PickupType::Missile => self.missiles > 0,
+            PickupType::EnergyTank => self.energy_tanks > 0,
+            PickupType::PowerBomb | PickupType::PowerBombExpansion => self.power_bombs > 0,
+            PickupType::WaveBeam => self.wave,
+            PickupType::IceBeam => self.ice,
+            PickupType::PlasmaBeam => self.plasma,
+            PickupType::ChargeBeam => self.charge,
+            PickupType::MorphBall => self.morph_ball,
+            PickupType::MorphBallBomb => self.bombs,
+            PickupType::SpiderBall => self.spider_ball,
+            PickupType::BoostBall => self.boost_ball,
+            PickupType::VariaSuit => self.varia_suit,
+            PickupType::GravitySuit => self.gravity_suit,
+            PickupType::PhazonSuit => self.phazon_suit,
+            PickupType::ThermalVisor => self.thermal_visor,
+            PickupType::XRayVisor => self.xray,
+            PickupType::SpaceJumpBoots => self.space_jump,
+            PickupType::GrappleBeam => self.grapple,
+            PickupType::SuperMissile => self.super_missile,
+            PickupType::Wavebuster => self.wavebuster,
+            PickupType::IceSpreader => self.ice_spreader,
+            PickupType::Flamethrower => self.flamethrower,
+            pt if pt.is_artifact() => self.artifacts.contains(&pt),
+            _ => false,
+        }
+    }
+
+    /// How many of each item the player starts with. Missiles and power bombs are counted in
+    /// ammo rather than expansions.
+    pub fn item_counts(&self) -> Vec<(PickupType, u32)>
+    {
+        let counts = [
+            (PickupType::ScanVisor, self.scan_visor as u32),
+            (PickupType::Missile, self.missiles),
+            (PickupType::EnergyTank, self.energy_tanks),
+            (PickupType::PowerBomb, self.power_bombs),
+            (PickupType::WaveBeam, self.wave as u32),
+            (PickupType::IceBeam, self.ice as u32),
+            (PickupType::PlasmaBeam, self.plasma as u32),
+            (PickupType::ChargeBeam, self.charge as u32),
+            (PickupType::MorphBall, self.morph_ball as u32),
+            (PickupType::MorphBallBomb, self.bombs as u32),
+            (PickupType::SpiderBall, self.spider_ball as u32),
+            (PickupType::BoostBall, self.boost_ball as u32),
+            (PickupType::VariaSuit, self.varia_suit as u32),
+            (PickupType::GravitySuit, self.gravity_suit as u32),
+            (PickupType::PhazonSuit, self.phazon_suit as u32),
+            (PickupType::ThermalVisor, self.thermal_visor as u32),
+            (PickupType::XRayVisor, self.xray as u32),
+            (PickupType::SpaceJumpBoots, self.space_jump as u32),
+            (PickupType::GrappleBeam, self.grapple as u32),
+            (PickupType::SuperMissile, self.super_missile as u32),
+            (PickupType::Wavebuster, self.wavebuster as u32),
+            (PickupType::IceSpreader, self.ice_spreader as u32),
+            (PickupType::Flamethrower, self.flamethrower as u32),
+        ];
+        counts.iter()
+            .cloned()
+            .chain(self.artifacts.iter().map(|pt| (*pt, 1)))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    /// The inverse of `item_counts`
+    pub fn from_item_counts(counts: &[(PickupType, u32)]) -> Result<Self, String>
+    {
+        let mut starting_items = StartingItems::from_u64(0);
+        for &(pickup_type, count) in counts {
+            let amount = |max: u32| if count <= max {
+                Ok(count)
+            } else {
+                Err(format!("Cannot start with {} {}", count, pickup_type.name()))
+            };
+            match pickup_type {
+                PickupType::ScanVisor => starting_items.scan_visor = amount(1)? == 1,
+                PickupType::Missile => starting_items.missiles = count,
+                PickupType::EnergyTank => starting_items.energy_tanks = count,
+                PickupType::PowerBomb => starting_items.power_bombs = count,
+                PickupType::WaveBeam => starting_items.wave = amount(1)? == 1,
+                PickupType::IceBeam => starting_items.ice = amount(1)? == 1,
+                PickupType::PlasmaBeam => starting_items.plasma = amount(1)? == 1,
+                PickupType::ChargeBeam => starting_items.charge = amount(1)? == 1,
+                PickupType::MorphBall => starting_items.morph_ball = amount(1)? == 1,
+                PickupType::MorphBallBomb => starting_items.bombs = amount(1)? == 1,
+                PickupType::SpiderBall => starting_items.spider_ball = amount(1)? == 1,
+                PickupType::BoostBall => starting_items.boost_ball = amount(1)? == 1,
+                PickupType::VariaSuit => starting_items.varia_suit = amount(1)? == 1,
+                PickupType::GravitySuit => starting_items.gravity_suit = amount(1)? == 1,
+                PickupType::PhazonSuit => starting_items.phazon_suit = amount(1)? == 1,
+                PickupType::ThermalVisor => starting_items.thermal_visor = amount(1)? == 1,
+                PickupType::XRayVisor => starting_items.xray = amount(1)? == 1,
+                PickupType::SpaceJumpBoots => starting_items.space_jump = amount(1)? == 1,
+                PickupType::GrappleBeam => starting_items.grapple = amount(1)? == 1,
+                PickupType::SuperMissile => starting_items.super_missile = amount(1)? == 1,
+                PickupType::Wavebuster => starting_items.wavebuster = amount(1)? == 1,
+                PickupType::IceSpreader => starting_items.ice_spreader = amount(1)? == 1,
+                PickupType::Flamethrower => starting_items.flamethrower = amount(1)? == 1,
+                pt if pt.is_artifact() => if amount(1)? == 1 {
+                    starting_items.artifacts.push(pt);
+                },
+                _ => Err(format!("Cannot start with {}", pickup_type.name()))?,
+            }
+        }
+        Ok(starting_items)
+    }
+
     pub fn is_empty(&self) -> bool
     {
         !self.scan_visor &&
@@ -167,7 +317,11 @@ impl StartingItems
         !self.super_missile &&
         !self.wavebuster &&
         !self.ice_spreader &&
-        !self.flamethrower
+        !self.flamethrower &&
+        self.artifacts.is_empty() &&
+        self.maps.is_empty() &&
+        self.scans.is_empty() &&
+        self.health.is_none()
     }
 }
 
@@ -199,6 +353,10 @@ impl Default for StartingItems
             wavebuster: false,
             ice_spreader: false,
             flamethrower: false,
+            artifacts: vec![],
+            maps: vec![],
+            scans: vec![],
+            health: None,
         }
     }
 }
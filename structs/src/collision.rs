@@ -0,0 +1,468 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::{LazyArray, Readable, RoArray};
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+
+use std::collections::{HashMap, HashSet};
+
+// http://www.metroid2002.com/retromodding/wiki/Area_Collision_(Metroid_Prime)
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaCollision<'r>
+{
+    #[auto_struct(expect = 0x01000000)]
+    unknown: u32,
+
+    #[auto_struct(derive = (40 + octree.len() + indices.size()) as u32)]
+    collision_size: u32,
+
+    #[auto_struct(expect = 0xDEAFBABE)]
+    magic: u32,
+
+    #[auto_struct(expect = 3)]
+    version: u32,
+
+    pub bounding_box: GenericArray<f32, U6>,
+
+    pub root_node_type: u32,
+
+    // We never need to walk the octree ourselves (CollisionMesh does a linear search
+    // instead), so it is kept as raw bytes.
+    #[auto_struct(derive = octree.len() as u32)]
+    octree_size: u32,
+    #[auto_struct(init = (octree_size as usize, ()))]
+    pub octree: RoArray<'r, u8>,
+
+    pub indices: CollisionIndices<'r>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct Dcln<'r>
+{
+    #[auto_struct(derive = meshes.len() as u32)]
+    mesh_count: u32,
+    #[auto_struct(init = (mesh_count as usize, ()))]
+    pub meshes: LazyArray<'r, DclnCollision<'r>>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct DclnCollision<'r>
+{
+    #[auto_struct(expect = 0x01000000)]
+    unknown: u32,
+
+    #[auto_struct(derive = (8 + indices.size() + obb_tree.len()) as u32)]
+    collision_size: u32,
+
+    #[auto_struct(expect = 0xDEAFBABE)]
+    magic: u32,
+
+    pub version: u32,
+
+    pub indices: CollisionIndices<'r>,
+
+    #[auto_struct(init = (collision_size as usize - 8 - indices.size(), ()))]
+    pub obb_tree: RoArray<'r, u8>,
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct CollisionIndices<'r>
+{
+    #[auto_struct(derive = materials.len() as u32)]
+    material_count: u32,
+    #[auto_struct(init = (material_count as usize, ()))]
+    pub materials: RoArray<'r, u32>,
+
+    #[auto_struct(derive = vertex_materials.len() as u32)]
+    vertex_material_count: u32,
+    #[auto_struct(init = (vertex_material_count as usize, ()))]
+    pub vertex_materials: RoArray<'r, u8>,
+
+    #[auto_struct(derive = edge_materials.len() as u32)]
+    edge_material_count: u32,
+    #[auto_struct(init = (edge_material_count as usize, ()))]
+    pub edge_materials: RoArray<'r, u8>,
+
+    #[auto_struct(derive = triangle_materials.len() as u32)]
+    triangle_material_count: u32,
+    #[auto_struct(init = (triangle_material_count as usize, ()))]
+    pub triangle_materials: RoArray<'r, u8>,
+
+    #[auto_struct(derive = edges.len() as u32)]
+    edge_count: u32,
+    #[auto_struct(init = (edge_count as usize, ()))]
+    pub edges: RoArray<'r, CollisionEdge>,
+
+    // Three edge indices per triangle
+    #[auto_struct(derive = triangle_edges.len() as u32)]
+    triangle_edge_count: u32,
+    #[auto_struct(init = (triangle_edge_count as usize, ()))]
+    pub triangle_edges: RoArray<'r, u16>,
+
+    #[auto_struct(derive = vertices.len() as u32)]
+    vertex_count: u32,
+    #[auto_struct(init = (vertex_count as usize, ()))]
+    pub vertices: RoArray<'r, GenericArray<f32, U3>>,
+}
+
+#[auto_struct(Readable, Writable, FixedSize)]
+#[derive(Debug, Clone)]
+pub struct CollisionEdge
+{
+    pub vertex0: u16,
+    pub vertex1: u16,
+}
+
+pub mod collision_material
+{
+    pub const STONE: u32 = 1 << 1;
+    pub const METAL: u32 = 1 << 2;
+    pub const GRASS: u32 = 1 << 3;
+    pub const ICE: u32 = 1 << 4;
+    pub const METAL_GRATING: u32 = 1 << 6;
+    pub const PHAZON: u32 = 1 << 7;
+    pub const DIRT: u32 = 1 << 8;
+    pub const LAVA: u32 = 1 << 9;
+    pub const SNOW: u32 = 1 << 11;
+    pub const SLOW_MUD: u32 = 1 << 12;
+    pub const HALF_PIPE: u32 = 1 << 13;
+    pub const MUD: u32 = 1 << 14;
+    pub const GLASS: u32 = 1 << 15;
+    pub const SHIELD: u32 = 1 << 16;
+    pub const SAND: u32 = 1 << 17;
+    pub const SHOOT_THROUGH: u32 = 1 << 18;
+    pub const SOLID: u32 = 1 << 19;
+    pub const CAMERA_THROUGH: u32 = 1 << 21;
+    pub const WOOD: u32 = 1 << 22;
+    pub const ORGANIC: u32 = 1 << 23;
+    pub const FLIPPED_TRIANGLE: u32 = 1 << 25;
+    pub const SEE_THROUGH: u32 = 1 << 26;
+    pub const SCAN_THROUGH: u32 = 1 << 27;
+    pub const AI_WALK_THROUGH: u32 = 1 << 28;
+    pub const CEILING: u32 = 1 << 29;
+    pub const WALL: u32 = 1 << 30;
+    pub const FLOOR: u32 = 1 << 31;
+}
+
+#[derive(Debug, Clone)]
+pub struct CollisionTriangle
+{
+    pub vertices: [[f32; 3]; 3],
+    pub edges: [u16; 3],
+    pub material: u32,
+}
+
+impl CollisionTriangle
+{
+    pub fn is_floor(&self) -> bool
+    {
+        self.material & collision_material::FLOOR != 0
+    }
+
+    pub fn is_wall(&self) -> bool
+    {
+        self.material & collision_material::WALL != 0
+    }
+
+    pub fn is_ceiling(&self) -> bool
+    {
+        self.material & collision_material::CEILING != 0
+    }
+
+    /// The height at which a vertical line through (x, y) crosses this triangle, if it does.
+    pub fn vertical_intersection(&self, x: f32, y: f32) -> Option<f32>
+    {
+        let [a, b, c] = self.vertices;
+        let det = (b[1] - c[1]) * (a[0] - c[0]) + (c[0] - b[0]) * (a[1] - c[1]);
+        if det.abs() < f32::EPSILON {
+            // The triangle is vertical
+            return None;
+        }
+        let l0 = ((b[1] - c[1]) * (x - c[0]) + (c[0] - b[0]) * (y - c[1])) / det;
+        let l1 = ((c[1] - a[1]) * (x - c[0]) + (a[0] - c[0]) * (y - c[1])) / det;
+        let l2 = 1.0 - l0 - l1;
+        if l0 < 0.0 || l1 < 0.0 || l2 < 0.0 {
+            return None;
+        }
+        Some(l0 * a[2] + l1 * b[2] + l2 * c[2])
+    }
+}
+
+/// A flattened, owned copy of a collision mesh that is convenient for spatial queries.
+#[derive(Debug, Clone)]
+pub struct CollisionMesh
+{
+    pub triangles: Vec<CollisionTriangle>,
+}
+
+impl<'r> CollisionIndices<'r>
+{
+    pub fn to_mesh(&self) -> CollisionMesh
+    {
+        let materials: Vec<u32> = self.materials.iter().collect();
+        let vertices: Vec<[f32; 3]> = self.vertices.iter()
+            .map(|v| [v[0], v[1], v[2]])
+            .collect();
+        let edges: Vec<CollisionEdge> = self.edges.iter().collect();
+        let triangle_edges: Vec<u16> = self.triangle_edges.iter().collect();
+
+        let triangles = triangle_edges.chunks(3)
+            .zip(self.triangle_materials.iter())
+            .map(|(tri_edges, material_idx)| {
+                let material = materials[material_idx as usize];
+                let e0 = &edges[tri_edges[0] as usize];
+                let e1 = &edges[tri_edges[1] as usize];
+                let v2 = if e1.vertex0 != e0.vertex0 && e1.vertex0 != e0.vertex1 {
+                    e1.vertex0
+                } else {
+                    e1.vertex1
+                };
+                let mut idxs = [e0.vertex0, e0.vertex1, v2];
+                if material & collision_material::FLIPPED_TRIANGLE != 0 {
+                    idxs.swap(0, 2);
+                }
+                CollisionTriangle {
+                    vertices: [
+                        vertices[idxs[0] as usize],
+                        vertices[idxs[1] as usize],
+                        vertices[idxs[2] as usize],
+                    ],
+                    edges: [tri_edges[0], tri_edges[1], tri_edges[2]],
+                    material,
+                }
+            })
+            .collect();
+        CollisionMesh { triangles }
+    }
+}
+
+impl CollisionMesh
+{
+    /// [min_x, min_y, min_z, max_x, max_y, max_z]
+    pub fn bounding_box(&self) -> [f32; 6]
+    {
+        let mut bb = [
+            f32::INFINITY, f32::INFINITY, f32::INFINITY,
+            f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY,
+        ];
+        for v in self.triangles.iter().flat_map(|t| t.vertices.iter()) {
+            for i in 0..3 {
+                bb[i] = bb[i].min(v[i]);
+                bb[i + 3] = bb[i + 3].max(v[i]);
+            }
+        }
+        bb
+    }
+
+    /// The index and height of the closest floor directly below the given point.
+    pub fn floor_below(&self, point: [f32; 3]) -> Option<(usize, f32)>
+    {
+        self.triangles.iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_floor())
+            .filter_map(|(i, t)| t.vertical_intersection(point[0], point[1]).map(|z| (i, z)))
+            .filter(|&(_, z)| z <= point[2])
+            .fold(None, |best, (i, z)| match best {
+                Some((_, best_z)) if best_z >= z => best,
+                _ => Some((i, z)),
+            })
+    }
+
+    /// The height of the closest surface of any kind directly above the given point.
+    pub fn surface_above(&self, point: [f32; 3]) -> Option<f32>
+    {
+        self.triangles.iter()
+            .filter_map(|t| t.vertical_intersection(point[0], point[1]))
+            .filter(|&z| z > point[2])
+            .fold(None, |best, z| match best {
+                Some(best_z) if best_z <= z => best,
+                _ => Some(z),
+            })
+    }
+
+    /// Whether the point lies within the mesh's bounds and has a floor beneath it.
+    pub fn contains_point(&self, point: [f32; 3]) -> bool
+    {
+        let bb = self.bounding_box();
+        (0..3).all(|i| bb[i] <= point[i] && point[i] <= bb[i + 3])
+            && self.floor_below(point).is_some()
+    }
+
+    /// Whether something `height` units tall could stand on the floor directly below
+    /// the given point without clipping into whatever is above it.
+    pub fn is_standable(&self, point: [f32; 3], height: f32) -> bool
+    {
+        let (_, floor_z) = match self.floor_below(point) {
+            Some(floor) => floor,
+            None => return false,
+        };
+        match self.surface_above([point[0], point[1], floor_z]) {
+            Some(z) => z - floor_z >= height,
+            None => true,
+        }
+    }
+
+    /// The set of floor triangles that can be walked to from the given floor triangle,
+    /// following shared edges. Empty if `start` isn't a floor triangle.
+    pub fn connected_floor(&self, start: usize) -> HashSet<usize>
+    {
+        if !self.triangles.get(start).is_some_and(|t| t.is_floor()) {
+            return HashSet::new();
+        }
+
+        let mut edge_map: HashMap<u16, Vec<usize>> = HashMap::new();
+        for (i, t) in self.triangles.iter().enumerate().filter(|(_, t)| t.is_floor()) {
+            for e in t.edges.iter() {
+                edge_map.entry(*e).or_default().push(i);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            if !seen.insert(i) {
+                continue;
+            }
+            for e in self.triangles[i].edges.iter() {
+                stack.extend(edge_map[e].iter().filter(|j| !seen.contains(j)));
+            }
+        }
+        seen
+    }
+
+    /// Places something `height` units tall could spawn that can be walked to from the floor
+    /// below `from`: the centre of each such floor triangle with enough room above it.
+    pub fn navigable_spawn_points(&self, from: [f32; 3], height: f32) -> Vec<[f32; 3]>
+    {
+        let start = match self.floor_below(from) {
+            Some((start, _)) => start,
+            None => return vec![],
+        };
+        let mut floor: Vec<usize> = self.connected_floor(start).into_iter().collect();
+        floor.sort_unstable();
+        floor.into_iter()
+            .filter_map(|i| {
+                let [a, b, c] = self.triangles[i].vertices;
+                let centre = [
+                    (a[0] + b[0] + c[0]) / 3.0,
+                    (a[1] + b[1] + c[1]) / 3.0,
+                    (a[2] + b[2] + c[2]) / 3.0,
+                ];
+                // Check from just above the centre, so the triangle itself is the floor below
+                let above = [centre[0], centre[1], centre[2] + 0.01];
+                if self.floor_below(above).map(|(j, _)| j) == Some(i)
+                    && self.is_standable(above, height)
+                {
+                    Some(centre)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the floor below `to` can be walked to from the floor below `from`.
+    pub fn is_reachable(&self, from: [f32; 3], to: [f32; 3]) -> bool
+    {
+        match (self.floor_below(from), self.floor_below(to)) {
+            (Some((from, _)), Some((to, _))) => self.connected_floor(from).contains(&to),
+            _ => false,
+        }
+    }
+}
+
+// Two floor triangles forming a 10x10 square at z = 0
+#[cfg(test)]
+fn test_floor_indices_bytes() -> Vec<u8>
+{
+    let mut data = vec![];
+    let push_u32 = |data: &mut Vec<u8>, i: u32| data.extend_from_slice(&i.to_be_bytes());
+    push_u32(&mut data, 1);
+    push_u32(&mut data, collision_material::FLOOR);
+    for count in &[4, 5, 2] {
+        push_u32(&mut data, *count);
+        data.resize(data.len() + *count as usize, 0);
+    }
+    push_u32(&mut data, 5);
+    for (a, b) in &[(0u16, 1u16), (1, 2), (2, 0), (2, 3), (3, 0)] {
+        data.extend_from_slice(&a.to_be_bytes());
+        data.extend_from_slice(&b.to_be_bytes());
+    }
+    push_u32(&mut data, 6);
+    for e in &[0u16, 1, 2, 2, 3, 4] {
+        data.extend_from_slice(&e.to_be_bytes());
+    }
+    push_u32(&mut data, 4);
+    for v in &[[0f32, 0., 0.], [10., 0., 0.], [10., 10., 0.], [0., 10., 0.]] {
+        for f in v {
+            data.extend_from_slice(&f.to_bits().to_be_bytes());
+        }
+    }
+    data
+}
+
+#[test]
+fn test_collision_mesh_queries()
+{
+    use reader_writer::{Reader, Writable};
+
+    let data = test_floor_indices_bytes();
+    let indices: CollisionIndices = Reader::new(&data).read(());
+    assert_eq!(indices.size(), data.len());
+    let mut written = vec![];
+    indices.write_to(&mut written).unwrap();
+    assert_eq!(written, data);
+
+    let mesh = indices.to_mesh();
+    assert_eq!(mesh.triangles.len(), 2);
+    assert!(mesh.contains_point([5., 5., 0.]));
+    assert!(!mesh.contains_point([5., 15., 0.]));
+    assert!(mesh.is_standable([2., 8., 1.], 2.7));
+    assert!(mesh.is_reachable([9., 1., 1.], [1., 9., 1.]));
+    assert!(mesh.connected_floor(2).is_empty());
+
+    let spawn_points = mesh.navigable_spawn_points([9., 1., 1.], 2.7);
+    assert_eq!(spawn_points.len(), 2);
+    assert!(spawn_points.iter().all(|p| mesh.is_standable([p[0], p[1], p[2] + 0.01], 2.7)));
+    assert!(mesh.navigable_spawn_points([5., 15., 1.], 2.7).is_empty());
+}
+
+#[test]
+fn test_dcln_round_trip()
+{
+    use reader_writer::{Reader, Writable};
+
+    let indices = test_floor_indices_bytes();
+    let obb_tree = [0xAAu8; 12];
+    let mut data = vec![];
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(&0x01000000u32.to_be_bytes());
+    data.extend_from_slice(&((8 + indices.len() + obb_tree.len()) as u32).to_be_bytes());
+    data.extend_from_slice(&0xDEAFBABEu32.to_be_bytes());
+    data.extend_from_slice(&2u32.to_be_bytes());
+    data.extend_from_slice(&indices);
+    data.extend_from_slice(&obb_tree);
+    data.resize((data.len() + 31) & !31, 0);
+
+    let dcln: Dcln = Reader::new(&data).read(());
+    assert_eq!(dcln.size(), data.len());
+    let mut written = vec![];
+    dcln.write_to(&mut written).unwrap();
+    assert_eq!(written, data);
+
+    assert_eq!(dcln.meshes.len(), 1);
+    let mesh = dcln.meshes.iter().next().unwrap();
+    assert_eq!(mesh.version, 2);
+    assert_eq!(&mesh.obb_tree.iter().collect::<Vec<_>>()[..], &obb_tree[..]);
+    assert_eq!(mesh.indices.to_mesh().triangles.len(), 2);
+}
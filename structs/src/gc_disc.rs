@@ -4,8 +4,11 @@ use reader_writer::{CStr, Reader, Readable, RoArray, WithRead, Writable};
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
+use std::cmp;
 use std::io::{self, Write};
 use std::iter;
+use std::panic;
+use std::thread;
 
 use crate::{
     pak::Pak,
@@ -17,6 +20,10 @@ use crate::{
 
 pub const GC_DISC_LENGTH: usize = 1_459_978_240;
 
+// Limits on the PAKs `GcDisc::write` keeps serialised in memory at once
+const MAX_BATCH_PAKS: usize = 8;
+const MAX_BATCH_BYTES: usize = 256 * 1024 * 1024;
+
 pub struct GcDisc<'r>
 {
     pub header: GcDiscHeader,
@@ -349,12 +356,60 @@ impl<'r> FstEntry<'r>
             .collect();
         entries_and_zeroes.push((entries[entries.len() - 1], 0));
 
+        // Serialising the PAKs is most of the work, so the PAKs in each batch of files are
+        // serialised to buffers concurrently and then written out in order. Each batch holds at
+        // most one PAK per thread and, unless a single PAK is bigger, MAX_BATCH_BYTES of them.
+        let max_batch_paks = cmp::min(
+            thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            MAX_BATCH_PAKS,
+        );
         let zero_bytes = [0u8; 32];
-        for (e, zeroes) in entries_and_zeroes {
-            if let Some(f) = e.file {
-                notifier.notify_writing_file(&e.name, e.raw_entry.length as usize);
-                f.write_to(writer)?;
-                writer.write_all(&zero_bytes[0..zeroes as usize])?;
+        let mut remaining = &entries_and_zeroes[..];
+        while !remaining.is_empty() {
+            let mut batch_len = 0;
+            let mut batch_paks = 0;
+            let mut batch_bytes = 0;
+            for (e, _) in remaining {
+                if let Some(FstEntryFile::Pak(_)) = e.file {
+                    let bytes = e.raw_entry.length as usize;
+                    if batch_paks > 0
+                        && (batch_paks == max_batch_paks || batch_bytes + bytes > MAX_BATCH_BYTES)
+                    {
+                        break;
+                    }
+                    batch_paks += 1;
+                    batch_bytes += bytes;
+                }
+                batch_len += 1;
+            }
+            let (batch, rest) = remaining.split_at(batch_len);
+            remaining = rest;
+
+            let serialised_paks = thread::scope(|s| {
+                let handles = batch.iter()
+                    .map(|(e, _)| match e.file {
+                        Some(FstEntryFile::Pak(pak)) => Some(s.spawn(move || {
+                            let mut buf = Vec::with_capacity(pak.size());
+                            pak.write_to(&mut buf).map(|_| buf)
+                        })),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                handles.into_iter()
+                    .map(|h| h.map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e))))
+                    .collect::<Vec<_>>()
+            });
+
+            for ((e, zeroes), serialised_pak) in batch.iter().zip(serialised_paks) {
+                if let Some(f) = e.file {
+                    notifier.notify_writing_file(&e.name, e.raw_entry.length as usize);
+                    if let Some(buf) = serialised_pak {
+                        writer.write_all(&buf?)?;
+                    } else {
+                        f.write_to(writer)?;
+                    }
+                    writer.write_all(&zero_bytes[0..*zeroes as usize])?;
+                }
             }
         }
         Ok(())
@@ -4,6 +4,7 @@ mod ancs;
 mod anim;
 mod bnr;
 mod cmdl;
+mod collision;
 mod dol;
 mod evnt;
 mod font;
@@ -19,6 +20,7 @@ mod part;
 mod savw;
 mod scan;
 mod scly;
+mod scly_schema;
 mod strg;
 mod thp;
 mod txtr;
@@ -27,72 +29,180 @@ pub mod scly_props
 {
     // http://www.metroid2002.com/retromodding/wiki/User:Parax0/Sandbox
     pub mod actor;
+    pub mod actor_keyframe;
+    pub mod actor_rotate;
+    pub mod ai_jump_point;
+    pub mod area_attributes;
+    pub mod ball_trigger;
+    pub mod camera;
+    pub mod camera_blur_keyframe;
+    pub mod camera_filter_keyframe;
+    pub mod camera_hint;
+    pub mod camera_hint_trigger;
+    pub mod camera_pitch_volume;
+    pub mod camera_shaker;
+    pub mod camera_waypoint;
+    pub mod color_modulate;
+    pub mod controller_action;
+    pub mod counter;
+    pub mod cover_point;
     pub mod damageable_trigger;
+    pub mod distance_fog;
     pub mod dock;
+    pub mod dock_area_change;
     pub mod door;
     pub mod effect;
+    pub mod flaahgra;
+    pub mod fog_volume;
+    pub mod generator;
     pub mod hud_memo;
     pub mod memory_relay;
+    pub mod midi;
+    pub mod patterned_ai;
     pub mod pickup;
+    pub mod pickup_generator;
     pub mod platorm;
-    pub mod point_of_interest;
     pub mod player_actor;
     pub mod player_hint;
+    pub mod player_state_change;
+    pub mod point_of_interest;
+    pub mod radial_damage;
+    pub mod random_relay;
     pub mod relay;
+    pub mod repulsor;
+    pub mod rumble_effect;
     pub mod sound;
     pub mod spawn_point;
     pub mod special_function;
+    pub mod spider_ball_waypoint;
     pub mod streamed_audio;
+    pub mod switch;
+    pub mod targeting_point;
+    pub mod thermal_heat_fader;
     pub mod timer;
     pub mod trigger;
     pub mod water;
+    pub mod waypoint;
+    pub mod world_light_fader;
     pub mod world_transporter;
 
     pub mod structs;
 
     pub use self::actor::*;
+    pub use self::actor_keyframe::*;
+    pub use self::actor_rotate::*;
+    pub use self::ai_jump_point::*;
+    pub use self::area_attributes::*;
+    pub use self::ball_trigger::*;
+    pub use self::camera::*;
+    pub use self::camera_blur_keyframe::*;
+    pub use self::camera_filter_keyframe::*;
+    pub use self::camera_hint::*;
+    pub use self::camera_hint_trigger::*;
+    pub use self::camera_pitch_volume::*;
+    pub use self::camera_shaker::*;
+    pub use self::camera_waypoint::*;
+    pub use self::color_modulate::*;
+    pub use self::controller_action::*;
+    pub use self::counter::*;
+    pub use self::cover_point::*;
     pub use self::damageable_trigger::*;
+    pub use self::distance_fog::*;
     pub use self::dock::*;
+    pub use self::dock_area_change::*;
     pub use self::door::*;
     pub use self::effect::*;
+    pub use self::flaahgra::*;
+    pub use self::fog_volume::*;
+    pub use self::generator::*;
     pub use self::hud_memo::*;
     pub use self::memory_relay::*;
+    pub use self::midi::*;
+    pub use self::patterned_ai::*;
     pub use self::pickup::*;
+    pub use self::pickup_generator::*;
     pub use self::platorm::*;
-    pub use self::point_of_interest::*;
     pub use self::player_actor::*;
     pub use self::player_hint::*;
+    pub use self::player_state_change::*;
+    pub use self::point_of_interest::*;
+    pub use self::radial_damage::*;
+    pub use self::random_relay::*;
     pub use self::relay::*;
+    pub use self::repulsor::*;
+    pub use self::rumble_effect::*;
     pub use self::sound::*;
     pub use self::spawn_point::*;
     pub use self::special_function::*;
+    pub use self::spider_ball_waypoint::*;
     pub use self::streamed_audio::*;
+    pub use self::switch::*;
+    pub use self::targeting_point::*;
+    pub use self::thermal_heat_fader::*;
     pub use self::timer::*;
     pub use self::trigger::*;
     pub use self::water::*;
+    pub use self::waypoint::*;
+    pub use self::world_light_fader::*;
     pub use self::world_transporter::*;
 }
 
 pub use scly_props::structs as scly_structs;
 pub use scly_props::actor::*;
+pub use scly_props::actor_keyframe::*;
+pub use scly_props::actor_rotate::*;
+pub use scly_props::ai_jump_point::*;
+pub use scly_props::area_attributes::*;
+pub use scly_props::ball_trigger::*;
+pub use scly_props::camera::*;
+pub use scly_props::camera_blur_keyframe::*;
+pub use scly_props::camera_filter_keyframe::*;
+pub use scly_props::camera_hint::*;
+pub use scly_props::camera_hint_trigger::*;
+pub use scly_props::camera_pitch_volume::*;
+pub use scly_props::camera_shaker::*;
+pub use scly_props::camera_waypoint::*;
+pub use scly_props::color_modulate::*;
+pub use scly_props::controller_action::*;
+pub use scly_props::counter::*;
+pub use scly_props::cover_point::*;
 pub use scly_props::damageable_trigger::*;
+pub use scly_props::distance_fog::*;
 pub use scly_props::dock::*;
+pub use scly_props::dock_area_change::*;
 pub use scly_props::door::*;
 pub use scly_props::effect::*;
+pub use scly_props::flaahgra::*;
+pub use scly_props::fog_volume::*;
+pub use scly_props::generator::*;
 pub use scly_props::hud_memo::*;
 pub use scly_props::memory_relay::*;
+pub use scly_props::midi::*;
+pub use scly_props::patterned_ai::*;
 pub use scly_props::pickup::*;
+pub use scly_props::pickup_generator::*;
 pub use scly_props::platorm::*;
-pub use scly_props::point_of_interest::*;
 pub use scly_props::player_actor::*;
 pub use scly_props::player_hint::*;
+pub use scly_props::player_state_change::*;
+pub use scly_props::point_of_interest::*;
+pub use scly_props::radial_damage::*;
+pub use scly_props::random_relay::*;
 pub use scly_props::relay::*;
+pub use scly_props::repulsor::*;
+pub use scly_props::rumble_effect::*;
 pub use scly_props::sound::*;
 pub use scly_props::spawn_point::*;
 pub use scly_props::special_function::*;
+pub use scly_props::spider_ball_waypoint::*;
 pub use scly_props::streamed_audio::*;
+pub use scly_props::switch::*;
+pub use scly_props::targeting_point::*;
+pub use scly_props::thermal_heat_fader::*;
 pub use scly_props::timer::*;
 pub use scly_props::trigger::*;
+pub use scly_props::waypoint::*;
+pub use scly_props::world_light_fader::*;
 pub use scly_props::world_transporter::*;
 
 pub use res_id::ResId;
@@ -101,6 +211,7 @@ pub use anim::*;
 pub use ancs::*;
 pub use bnr::*;
 pub use cmdl::*;
+pub use collision::*;
 pub use dol::*;
 pub use evnt::*;
 pub use font::*;
@@ -116,6 +227,7 @@ pub use part::*;
 pub use savw::*;
 pub use scan::*;
 pub use scly::*;
+pub use scly_schema::*;
 pub use strg::*;
 pub use thp::*;
 pub use txtr::*;
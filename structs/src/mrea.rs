@@ -1,11 +1,17 @@
 
 use auto_struct_macros::auto_struct;
-use reader_writer::{LCow, IteratorArray, Readable, Reader, RoArray, RoArrayIter, Writable};
+use reader_writer::{FourCC, LCow, IteratorArray, LazyArray, Readable, Reader, RoArray, RoArrayIter,
+                    Writable, align_byte_count};
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
 use std::io;
+use std::marker::PhantomData;
+use std::ops::Range;
 
+use crate::ResId;
+use crate::collision::AreaCollision;
+use crate::res_id::*;
 use crate::scly::Scly;
 
 
@@ -43,10 +49,8 @@ pub struct Mrea<'r>
     #[auto_struct(pad_align = 32)]
     _pad: (),
 
-    // TODO: A more efficient representation might be nice
-    //       (We don't actually care about any of the sections except for scripting
-    //        section, so we could treat them as raw bytes. Similarly the indicies
-    //        for all the other sections.)
+    // Sections are left as raw bytes until one of the typed accessors below is used
+    // on them.
 
     #[auto_struct(init = section_sizes.iter())]
     pub sections: IteratorArray<'r, MreaSection<'r>, RoArrayIter<'r, u32>>,
@@ -58,75 +62,681 @@ pub struct Mrea<'r>
 
 impl<'r> Mrea<'r>
 {
+    fn section<'s>(&'s self, idx: usize) -> LCow<'s, MreaSection<'r>>
+    {
+        self.sections.iter().nth(idx).unwrap()
+    }
+
+    fn section_mut(&mut self, idx: usize) -> &mut MreaSection<'r>
+    {
+        &mut self.sections.as_mut_vec()[idx]
+    }
+
     pub fn scly_section<'s>(&'s self) -> LCow<'s, Scly<'r>>
     {
-        let section = self.sections.iter().nth(self.scly_section_idx as usize).unwrap();
-        match section {
-            LCow::Owned(MreaSection::Unknown(ref reader)) => LCow::Owned(reader.clone().read(())),
-            LCow::Borrowed(MreaSection::Unknown(ref reader)) => LCow::Owned(reader.clone().read(())),
-            LCow::Owned(MreaSection::Scly(scly)) => LCow::Owned(scly),
-            LCow::Borrowed(MreaSection::Scly(scly)) => LCow::Borrowed(scly),
-        }
+        MreaSection::scly_lcow(self.section(self.scly_section_idx as usize))
     }
 
     pub fn scly_section_mut(&mut self) -> &mut Scly<'r>
     {
-        self.sections.as_mut_vec()[self.scly_section_idx as usize].convert_to_scly()
+        self.section_mut(self.scly_section_idx as usize).convert_to_scly()
+    }
+
+    pub fn collision_section<'s>(&'s self) -> LCow<'s, AreaCollision<'r>>
+    {
+        MreaSection::collision_lcow(self.section(self.collision_section_idx as usize))
+    }
+
+    pub fn collision_section_mut(&mut self) -> &mut AreaCollision<'r>
+    {
+        self.section_mut(self.collision_section_idx as usize).convert_to_collision()
+    }
+
+    pub fn lights_section<'s>(&'s self) -> LCow<'s, AreaLights<'r>>
+    {
+        MreaSection::lights_lcow(self.section(self.lights_section_idx as usize))
+    }
+
+    pub fn lights_section_mut(&mut self) -> &mut AreaLights<'r>
+    {
+        self.section_mut(self.lights_section_idx as usize).convert_to_lights()
+    }
+
+    pub fn visibility_tree_section<'s>(&'s self) -> LCow<'s, AreaVisibilityTree<'r>>
+    {
+        MreaSection::visibility_tree_lcow(self.section(self.visibility_tree_section_idx as usize))
+    }
+
+    pub fn visibility_tree_section_mut(&mut self) -> &mut AreaVisibilityTree<'r>
+    {
+        self.section_mut(self.visibility_tree_section_idx as usize).convert_to_visibility_tree()
+    }
+
+    pub fn area_octree_section<'s>(&'s self) -> LCow<'s, AreaOctree<'r>>
+    {
+        MreaSection::area_octree_lcow(self.section(self.area_octree_section_idx as usize))
+    }
+
+    pub fn area_octree_section_mut(&mut self) -> &mut AreaOctree<'r>
+    {
+        self.section_mut(self.area_octree_section_idx as usize).convert_to_area_octree()
+    }
+
+    // The material set is always the first of the world geometry sections
+    pub fn material_set_section<'s>(&'s self) -> LCow<'s, AreaMaterialSet<'r>>
+    {
+        MreaSection::material_set_lcow(self.section(self.world_geometry_section_idx as usize))
+    }
+
+    pub fn material_set_section_mut(&mut self) -> &mut AreaMaterialSet<'r>
+    {
+        self.section_mut(self.world_geometry_section_idx as usize).convert_to_material_set()
+    }
+
+    /// Locates the sections belonging to each of the area's world models.
+    pub fn world_models(&self) -> Vec<WorldModelSections>
+    {
+        let mut idx = self.world_geometry_section_idx as usize + 1;
+        (0..self.world_model_count)
+            .map(|_| {
+                let surface_offsets = MreaSection::surface_offsets_lcow(self.section(idx + 6));
+                let surface_count = surface_offsets.surface_end_offsets.len();
+                let model = WorldModelSections {
+                    header: idx,
+                    positions: idx + 1,
+                    normals: idx + 2,
+                    colors: idx + 3,
+                    float_uvs: idx + 4,
+                    short_uvs: idx + 5,
+                    surface_offsets: idx + 6,
+                    surfaces: idx + 7..idx + 7 + surface_count,
+                };
+                idx += 7 + surface_count;
+                model
+            })
+            .collect()
+    }
+
+    pub fn world_model_header<'s>(&'s self, model: &WorldModelSections)
+        -> LCow<'s, WorldModelHeader<'r>>
+    {
+        MreaSection::world_model_header_lcow(self.section(model.header))
+    }
+
+    pub fn world_model_header_mut(&mut self, model: &WorldModelSections)
+        -> &mut WorldModelHeader<'r>
+    {
+        self.section_mut(model.header).convert_to_world_model_header()
+    }
+
+    pub fn surface<'s>(&'s self, model: &WorldModelSections, surface: usize)
+        -> LCow<'s, AreaSurface<'r>>
+    {
+        assert!(surface < model.surfaces.len());
+        MreaSection::surface_lcow(self.section(model.surfaces.start + surface))
+    }
+
+    pub fn surface_mut(&mut self, model: &WorldModelSections, surface: usize)
+        -> &mut AreaSurface<'r>
+    {
+        assert!(surface < model.surfaces.len());
+        self.section_mut(model.surfaces.start + surface).convert_to_surface()
     }
+
+    /// The union of the bounding boxes of all of the area's world models, in the same
+    /// [min_x, min_y, min_z, max_x, max_y, max_z] layout as the models' bounding boxes.
+    pub fn bounding_box(&self) -> Option<[f32; 6]>
+    {
+        self.world_models().iter()
+            .map(|model| {
+                let bb = &self.world_model_header(model).bounding_box;
+                [bb[0], bb[1], bb[2], bb[3], bb[4], bb[5]]
+            })
+            .fold(None, |acc, bb| match acc {
+                None => Some(bb),
+                Some(acc) => Some([
+                    acc[0].min(bb[0]), acc[1].min(bb[1]), acc[2].min(bb[2]),
+                    acc[3].max(bb[3]), acc[4].max(bb[4]), acc[5].max(bb[5]),
+                ]),
+            })
+    }
+
+    /// `bounding_box` with `area_transform` applied, i.e. in the same space as the
+    /// positions of the area's script objects.
+    pub fn world_bounding_box(&self) -> Option<[f32; 6]>
+    {
+        let bb = self.bounding_box()?;
+        let xf = &self.area_transform;
+        let mut res = [
+            f32::INFINITY, f32::INFINITY, f32::INFINITY,
+            f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY,
+        ];
+        for i in 0..8 {
+            let corner = [
+                if i & 1 == 0 { bb[0] } else { bb[3] },
+                if i & 2 == 0 { bb[1] } else { bb[4] },
+                if i & 4 == 0 { bb[2] } else { bb[5] },
+            ];
+            for row in 0..3 {
+                let v = xf[row * 4] * corner[0] + xf[row * 4 + 1] * corner[1]
+                    + xf[row * 4 + 2] * corner[2] + xf[row * 4 + 3];
+                res[row] = res[row].min(v);
+                res[row + 3] = res[row + 3].max(v);
+            }
+        }
+        Some(res)
+    }
+}
+
+/// The indices into `Mrea::sections` of the sections that make up a single world model.
+#[derive(Clone, Debug)]
+pub struct WorldModelSections
+{
+    pub header: usize,
+    pub positions: usize,
+    pub normals: usize,
+    pub colors: usize,
+    pub float_uvs: usize,
+    pub short_uvs: usize,
+    pub surface_offsets: usize,
+    pub surfaces: Range<usize>,
+}
+
+macro_rules! build_mrea_section {
+    (@args $reader:ident, unsized) => { () };
+    (@args $reader:ident, sized) => { $reader.len() as u32 };
+
+    ($($name:ident, $ty:ident, $sizing:ident, $lcow_fn:ident, $convert_fn:ident,)*) => {
+
+        #[derive(Debug, Clone)]
+        pub enum MreaSection<'r>
+        {
+            Unknown(Reader<'r>),
+            $($name($ty<'r>),)*
+        }
+
+        impl<'r> MreaSection<'r>
+        {
+            $(
+                fn $lcow_fn<'s>(section: LCow<'s, Self>) -> LCow<'s, $ty<'r>>
+                {
+                    match section {
+                        LCow::Owned(MreaSection::Unknown(reader)) => {
+                            let args = build_mrea_section!(@args reader, $sizing);
+                            LCow::Owned(reader.clone().read(args))
+                        },
+                        LCow::Borrowed(MreaSection::Unknown(reader)) => {
+                            let args = build_mrea_section!(@args reader, $sizing);
+                            LCow::Owned(reader.clone().read(args))
+                        },
+                        LCow::Owned(MreaSection::$name(inner)) => LCow::Owned(inner),
+                        LCow::Borrowed(MreaSection::$name(inner)) => LCow::Borrowed(inner),
+                        _ => panic!(concat!("Expected an unconverted or ", stringify!($name),
+                                            " section")),
+                    }
+                }
+
+                pub fn $convert_fn(&mut self) -> &mut $ty<'r>
+                {
+                    *self = match *self {
+                        MreaSection::Unknown(ref reader) => {
+                            let args = build_mrea_section!(@args reader, $sizing);
+                            MreaSection::$name(reader.clone().read(args))
+                        },
+                        MreaSection::$name(ref mut inner) => return inner,
+                        _ => panic!(concat!("Expected an unconverted or ", stringify!($name),
+                                            " section")),
+                    };
+                    match *self {
+                        MreaSection::$name(ref mut inner) => inner,
+                        _ => unreachable!(),
+                    }
+                }
+            )*
+        }
+
+        impl<'r> Readable<'r> for MreaSection<'r>
+        {
+            type Args = u32;
+            fn read_from(reader: &mut Reader<'r>, size: u32) -> Self
+            {
+                let res = MreaSection::Unknown(reader.truncated(size as usize));
+                reader.advance(size as usize);
+                res
+            }
+
+            fn size(&self) -> usize
+            {
+                match *self {
+                    MreaSection::Unknown(ref reader) => reader.len(),
+                    $(MreaSection::$name(ref inner) => inner.size(),)*
+                }
+            }
+        }
+
+        impl<'r> Writable for MreaSection<'r>
+        {
+            fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+            {
+                match *self {
+                    MreaSection::Unknown(ref reader) => {
+                        writer.write_all(&reader)?;
+                        Ok(reader.len() as u64)
+                    },
+                    $(MreaSection::$name(ref inner) => inner.write_to(writer),)*
+                }
+            }
+        }
+    };
 }
 
+build_mrea_section!(
+    Scly, Scly, unsized, scly_lcow, convert_to_scly,
+    Collision, AreaCollision, unsized, collision_lcow, convert_to_collision,
+    MaterialSet, AreaMaterialSet, unsized, material_set_lcow, convert_to_material_set,
+    WorldModelHeader, WorldModelHeader, unsized, world_model_header_lcow,
+        convert_to_world_model_header,
+    SurfaceOffsets, AreaSurfaceOffsets, unsized, surface_offsets_lcow, convert_to_surface_offsets,
+    Surface, AreaSurface, sized, surface_lcow, convert_to_surface,
+    Lights, AreaLights, unsized, lights_lcow, convert_to_lights,
+    VisibilityTree, AreaVisibilityTree, sized, visibility_tree_lcow,
+        convert_to_visibility_tree,
+    AreaOctree, AreaOctree, sized, area_octree_lcow, convert_to_area_octree,
+);
+
+
+// http://www.metroid2002.com/retromodding/wiki/MREA_(Metroid_Prime)
+#[auto_struct(Readable, Writable)]
 #[derive(Debug, Clone)]
-pub enum MreaSection<'r>
+pub struct AreaMaterialSet<'r>
 {
-    Unknown(Reader<'r>),
-    Scly(Scly<'r>),
+    #[auto_struct(derive = texture_ids.len() as u32)]
+    texture_count: u32,
+    #[auto_struct(init = (texture_count as usize, ()))]
+    pub texture_ids: LazyArray<'r, ResId<TXTR>>,
+
+    #[auto_struct(derive = materials.len() as u32)]
+    material_count: u32,
+    #[auto_struct(derive_from_iter = materials.iter()
+            .scan(0, &|sum: &mut u32, i: LCow<AreaMaterial>| {
+                *sum += i.size() as u32;
+                Some(*sum)
+            }))]
+    #[auto_struct(init = (material_count as usize, ()))]
+    material_end_offsets: RoArray<'r, u32>,
+
+    #[auto_struct(init = MaterialSizes::new(material_end_offsets.iter()))]
+    pub materials: IteratorArray<'r, AreaMaterial<'r>, MaterialSizes<'r>>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
 }
 
-impl<'r> MreaSection<'r>
+/// Converts the material end offsets of a material set into the size of each material.
+#[derive(Debug, Clone)]
+pub struct MaterialSizes<'r>
 {
-    // XXX A nicer/more clear name, maybe?
-    pub fn convert_to_scly(&mut self) -> &mut Scly<'r>
+    prev_end: u32,
+    end_offsets: RoArrayIter<'r, u32>,
+}
+
+impl<'r> MaterialSizes<'r>
+{
+    fn new(end_offsets: RoArrayIter<'r, u32>) -> Self
     {
-        *self = match *self {
-            MreaSection::Unknown(ref reader) => MreaSection::Scly(reader.clone().read(())),
-            MreaSection::Scly(ref mut scly) => return scly,
-        };
-        match *self {
-            MreaSection::Scly(ref mut scly) => scly,
-            _ => unreachable!(),
-        }
+        MaterialSizes { prev_end: 0, end_offsets }
     }
 }
 
-impl<'r> Readable<'r> for MreaSection<'r>
+impl<'r> Iterator for MaterialSizes<'r>
 {
-    type Args = u32;
-    fn read_from(reader: &mut Reader<'r>, size: u32) -> Self
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item>
     {
-        let res = MreaSection::Unknown(reader.truncated(size as usize));
-        reader.advance(size as usize);
-        res
+        let end = self.end_offsets.next()?;
+        let size = end - self.prev_end;
+        self.prev_end = end;
+        Some(size)
     }
 
-    fn size(&self) -> usize
+    fn size_hint(&self) -> (usize, Option<usize>)
     {
-        match *self {
-            MreaSection::Unknown(ref reader) => reader.len(),
-            MreaSection::Scly(ref scly) => scly.size()
-        }
+        self.end_offsets.size_hint()
     }
 }
 
-impl<'r> Writable for MreaSection<'r>
+impl<'r> ExactSizeIterator for MaterialSizes<'r> { }
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaMaterial<'r>
 {
-    fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+    #[auto_struct(args)]
+    size: u32,
+
+    pub flags: u32,
+
+    #[auto_struct(derive = texture_indices.len() as u32)]
+    texture_count: u32,
+    #[auto_struct(init = (texture_count as usize, ()))]
+    pub texture_indices: RoArray<'r, u32>,
+
+    pub vertex_attribute_flags: u32,
+    pub group_index: u32,
+
+    #[auto_struct(init = if flags & AREA_MATERIAL_KONST_COLORS != 0 { Some(()) } else { None })]
+    pub konst_colors: Option<KonstColors<'r>>,
+
+    // Blend modes, color channels, TEV stages, texgens and UV animations
+    #[auto_struct(init = (size as usize - 16 - texture_indices.size() - konst_colors.size(), ()))]
+    pub remainder: RoArray<'r, u8>,
+}
+
+pub const AREA_MATERIAL_KONST_COLORS: u32 = 0x8;
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct KonstColors<'r>
+{
+    #[auto_struct(derive = colors.len() as u32)]
+    color_count: u32,
+    #[auto_struct(init = (color_count as usize, ()))]
+    pub colors: LazyArray<'r, GenericArray<u8, U4>>,
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct WorldModelHeader<'r>
+{
+    pub visor_flags: u32,
+    pub transform: GenericArray<f32, U12>,
+    pub bounding_box: GenericArray<f32, U6>,
+
+    // Dummy so we can have a <'r>
+    pub _dummy: PhantomData<&'r ()>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaSurfaceOffsets<'r>
+{
+    #[auto_struct(derive = surface_end_offsets.len() as u32)]
+    surface_count: u32,
+    #[auto_struct(init = (surface_count as usize, ()))]
+    pub surface_end_offsets: RoArray<'r, u32>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaSurface<'r>
+{
+    #[auto_struct(args)]
+    size: u32,
+
+    pub center: GenericArray<f32, U3>,
+    pub material_index: u32,
+    pub mantissa: u16,
+    pub display_list_size: u16,
+    parent_model_ptr: u32,
+    next_surface_ptr: u32,
+
+    #[auto_struct(derive = extra_data.len() as u32)]
+    extra_data_size: u32,
+    pub normal: GenericArray<f32, U3>,
+    #[auto_struct(init = (extra_data_size as usize, ()))]
+    pub extra_data: RoArray<'r, u8>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+
+    #[auto_struct(init = (size as usize - align_byte_count(32, 44 + extra_data.len()), ()))]
+    pub display_list: RoArray<'r, u8>,
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaLights<'r>
+{
+    #[auto_struct(expect = 0xBABEDEAD)]
+    magic: u32,
+
+    pub layers: GenericArray<AreaLightLayer<'r>, U2>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaLightLayer<'r>
+{
+    #[auto_struct(derive = lights.len() as u32)]
+    light_count: u32,
+    #[auto_struct(init = (light_count as usize, ()))]
+    pub lights: LazyArray<'r, AreaLight>,
+}
+
+#[auto_struct(Readable, Writable, FixedSize)]
+#[derive(Debug, Clone)]
+pub struct AreaLight
+{
+    pub light_type: u32,
+    pub color: GenericArray<f32, U3>,
+    pub position: GenericArray<f32, U3>,
+    pub direction: GenericArray<f32, U3>,
+    pub brightness: f32,
+    pub spot_cutoff: f32,
+    pub unknown0: f32,
+    pub cast_shadows: u8,
+    pub unknown1: f32,
+    pub falloff_type: u32,
+    pub unknown2: f32,
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaVisibilityTree<'r>
+{
+    #[auto_struct(args)]
+    size: u32,
+
+    // Most areas have an empty visibility tree section
+    #[auto_struct(init = if size > 0 { Some(size) } else { None })]
+    pub tree: Option<VisibilityTree<'r>>,
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct VisibilityTree<'r>
+{
+    #[auto_struct(args)]
+    size: u32,
+
+    #[auto_struct(expect = FourCC::from_bytes(b"VISI"))]
+    magic: FourCC,
+    pub version: u32,
+
+    // Feature, light and entity tables, followed by the visibility octree
+    #[auto_struct(init = (size as usize - 8, ()))]
+    pub data: RoArray<'r, u8>,
+}
+
+#[auto_struct(Readable, Writable)]
+#[derive(Debug, Clone)]
+pub struct AreaOctree<'r>
+{
+    #[auto_struct(args)]
+    size: u32,
+
+    #[auto_struct(expect = FourCC::from_bytes(b"AROT"))]
+    magic: FourCC,
+    #[auto_struct(expect = 1)]
+    version: u32,
+
+    pub bitmap_count: u32,
+    pub bitmap_bit_count: u32,
+    #[auto_struct(derive = node_offsets.len() as u32)]
+    node_count: u32,
+
+    pub bounding_box: GenericArray<f32, U6>,
+
+    #[auto_struct(pad_align = 32)]
+    _pad: (),
+
+    #[auto_struct(init = ((bitmap_count * bitmap_bit_count.div_ceil(32)) as usize, ()))]
+    pub bitmaps: RoArray<'r, u32>,
+
+    #[auto_struct(init = (node_count as usize, ()))]
+    pub node_offsets: RoArray<'r, u32>,
+
+    #[auto_struct(init = (size as usize - 64 - bitmaps.size() - node_offsets.size(), ()))]
+    pub nodes: RoArray<'r, u8>,
+}
+
+#[test]
+fn test_mrea_round_trip()
+{
+    fn push_u32(buf: &mut Vec<u8>, x: u32)
+    {
+        buf.extend_from_slice(&x.to_be_bytes());
+    }
+    fn push_f32s(buf: &mut Vec<u8>, xs: &[f32])
     {
-        match *self {
-            MreaSection::Unknown(ref reader) => {
-                writer.write_all(&reader)?;
-                Ok(reader.len() as u64)
-            },
-            MreaSection::Scly(ref scly) => scly.write_to(writer),
+        for x in xs {
+            buf.extend_from_slice(&x.to_be_bytes());
         }
     }
+    fn pad(mut buf: Vec<u8>) -> Vec<u8>
+    {
+        buf.resize(align_byte_count(32, buf.len()), 0);
+        buf
+    }
+    fn raw(fill: u8) -> Vec<u8>
+    {
+        vec![fill; 32]
+    }
+
+    let mut material_set = vec![];
+    push_u32(&mut material_set, 1);
+    push_u32(&mut material_set, 0x11223344);
+    push_u32(&mut material_set, 1);
+    push_u32(&mut material_set, 32);
+    push_u32(&mut material_set, AREA_MATERIAL_KONST_COLORS);
+    push_u32(&mut material_set, 1);
+    push_u32(&mut material_set, 0);
+    push_u32(&mut material_set, 3);
+    push_u32(&mut material_set, 0);
+    push_u32(&mut material_set, 1);
+    material_set.extend_from_slice(&[1, 2, 3, 4, 0xAA, 0xBB, 0xCC, 0xDD]);
+
+    let mut header = vec![];
+    push_u32(&mut header, 0);
+    push_f32s(&mut header, &[1.0, 0.0, 0.0, 10.0, 0.0, 1.0, 0.0, 20.0, 0.0, 0.0, 1.0, 30.0]);
+    push_f32s(&mut header, &[-1.0, -2.0, -3.0, 4.0, 5.0, 6.0]);
+
+    let mut surface_offsets = vec![];
+    push_u32(&mut surface_offsets, 1);
+    push_u32(&mut surface_offsets, 96);
+
+    let mut surface = vec![];
+    push_f32s(&mut surface, &[0.5, 1.5, 2.5]);
+    push_u32(&mut surface, 0);
+    surface.extend_from_slice(&[0x12, 0x34, 0x00, 0x20]);
+    push_u32(&mut surface, 0);
+    push_u32(&mut surface, 0);
+    push_u32(&mut surface, 4);
+    push_f32s(&mut surface, &[0.0, 0.0, 1.0]);
+    surface.extend_from_slice(&[5, 6, 7, 8]);
+    let mut surface = pad(surface);
+    surface.extend_from_slice(&[0x98; 32]);
+
+    let mut scly = b"SCLY".to_vec();
+    push_u32(&mut scly, 1);
+    push_u32(&mut scly, 1);
+    push_u32(&mut scly, 32);
+    let mut layer = vec![0];
+    push_u32(&mut layer, 0);
+    scly.extend_from_slice(&pad(layer));
+
+    let mut lights = vec![];
+    push_u32(&mut lights, 0xBABEDEAD);
+    push_u32(&mut lights, 1);
+    push_u32(&mut lights, 0);
+    push_f32s(&mut lights, &[1.0, 0.5, 0.25, 3.0, 4.0, 5.0, 0.0, 0.0, -1.0, 2.0, 30.0, 1.0]);
+    lights.push(1);
+    push_f32s(&mut lights, &[1.0]);
+    push_u32(&mut lights, 2);
+    push_f32s(&mut lights, &[0.75]);
+    push_u32(&mut lights, 0);
+
+    let mut visibility_tree = b"VISI".to_vec();
+    push_u32(&mut visibility_tree, 2);
+    visibility_tree.extend_from_slice(&[0x56; 24]);
+
+    let mut octree = b"AROT".to_vec();
+    push_u32(&mut octree, 1);
+    push_u32(&mut octree, 1);
+    push_u32(&mut octree, 32);
+    push_u32(&mut octree, 1);
+    push_f32s(&mut octree, &[-1.0, -2.0, -3.0, 4.0, 5.0, 6.0]);
+    let mut octree = pad(octree);
+    push_u32(&mut octree, 0xF0F0F0F0);
+    push_u32(&mut octree, 0);
+    octree.extend_from_slice(&[0x77; 24]);
+
+    let sections = vec![
+        pad(material_set), pad(header), raw(1), raw(2), raw(3), raw(4), raw(5),
+        pad(surface_offsets), surface, scly, raw(6), raw(7), pad(lights),
+        visibility_tree, raw(8), octree,
+    ];
+
+    let mut bytes = vec![];
+    push_u32(&mut bytes, 0xDEADBEEF);
+    push_u32(&mut bytes, 0xF);
+    push_f32s(&mut bytes, &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, sections.len() as u32);
+    for idx in &[0, 9, 10, 11, 12, 13, 14, 15] {
+        push_u32(&mut bytes, *idx);
+    }
+    for section in &sections {
+        push_u32(&mut bytes, section.len() as u32);
+    }
+    let mut bytes = pad(bytes);
+    for section in &sections {
+        bytes.extend_from_slice(section);
+    }
+    let bytes = pad(bytes);
+
+    let mut mrea: Mrea = Reader::new(&bytes).read(());
+    let mut written = vec![];
+    mrea.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    // Decode every typed section, then check the re-encoded area is unchanged
+    mrea.material_set_section_mut().materials.as_mut_vec();
+    let models = mrea.world_models();
+    assert_eq!(models.len(), 1);
+    assert_eq!(mrea.world_model_header_mut(&models[0]).bounding_box[5], 6.0);
+    assert_eq!(mrea.surface_mut(&models[0], 0).extra_data.len(), 4);
+    mrea.scly_section_mut().layers.as_mut_vec();
+    mrea.lights_section_mut().layers[0].lights.as_mut_vec();
+    let tree = mrea.visibility_tree_section_mut().tree.as_ref().unwrap();
+    assert_eq!(tree.version, 2);
+    assert_eq!(tree.data.len(), 24);
+    assert_eq!(mrea.area_octree_section_mut().nodes.len(), 24);
+    assert_eq!(mrea.bounding_box(), Some([-1.0, -2.0, -3.0, 4.0, 5.0, 6.0]));
+
+    let mut written = vec![];
+    mrea.write_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+
+    let empty: AreaVisibilityTree = Reader::new(&[]).read(0);
+    assert!(empty.tree.is_none());
 }
@@ -29,7 +29,7 @@ pub struct Savw<'r>
     #[auto_struct(derive = layer_toggle_array.len() as u32)]
     layer_toggle_count: u32,
     #[auto_struct(init = (layer_toggle_count as usize, ()))]
-    pub layer_toggle_array: RoArray<'r, LayerToggle>,
+    pub layer_toggle_array: LazyArray<'r, LayerToggle>,
 
     #[auto_struct(derive = door_array.len() as u32)]
     door_count: u32,
@@ -1,12 +1,15 @@
 use auto_struct_macros::auto_struct;
 
-use reader_writer::{FourCC, LCow, RoArray, LazyArray, Readable, Reader, Writable};
+use reader_writer::{FourCC, IntoStatic, LCow, RoArray, LazyArray, Readable, Reader, Writable};
+use reader_writer::generic_array::GenericArray;
+use reader_writer::typenum::U3;
 
 use std::io;
 use std::borrow::Cow;
 use std::fmt;
 
 use crate::scly_props;
+use crate::scly_schema::{scly_schema, PropertyType, PropertyValue};
 
 
 #[auto_struct(Readable, Writable)]
@@ -79,6 +82,38 @@ pub struct SclyObject<'r>
     pub property_data: SclyProperty<'r>,
 }
 
+impl<'r> SclyObject<'r>
+{
+    /// Reads a property by its path in the SCLY property schema (see `scly_schema.txt`),
+    /// e.g. "Health.Health". Works regardless of whether this object type has a Rust struct.
+    pub fn get_property(&self, path: &str) -> Result<PropertyValue, String>
+    {
+        let data = self.property_data.to_bytes();
+        scly_schema().get_property(self.property_data.object_type(), &data, path)
+            .map_err(|e| format!("Instance 0x{:08X}: {}", self.instance_id, e))
+    }
+
+    pub fn property_type(&self, path: &str) -> Result<PropertyType, String>
+    {
+        let data = self.property_data.to_bytes();
+        scly_schema().locate(self.property_data.object_type(), &data, path)
+            .map(|(_, ty)| ty)
+            .map_err(|e| format!("Instance 0x{:08X}: {}", self.instance_id, e))
+    }
+
+    /// Overwrites a property by its path in the SCLY property schema. If the object type has
+    /// a typed struct, the object stays editable through the `_mut` accessors afterwards.
+    pub fn set_property(&mut self, path: &str, value: PropertyValue) -> Result<(), String>
+    {
+        let object_type = self.property_data.object_type();
+        let mut data = self.property_data.to_bytes().into_owned();
+        scly_schema().set_property(object_type, &mut data, path, value)
+            .map_err(|e| format!("Instance 0x{:08X}: {}", self.instance_id, e))?;
+        self.property_data = SclyProperty::from_owned_bytes(object_type, data);
+        Ok(())
+    }
+}
+
 macro_rules! build_scly_property {
     ($($name:ident, $is_check:ident, $accessor:ident, $accessor_mut:ident,)*) => {
 
@@ -89,6 +124,12 @@ macro_rules! build_scly_property {
                 object_type: u8,
                 data: Reader<'r>
             },
+            // Data that doesn't borrow from the file and that has no typed struct, e.g. after
+            // `set_property`
+            Owned {
+                object_type: u8,
+                data: Vec<u8>
+            },
 
             $($name(Box<scly_props::$name<'r >> ),)*
         }
@@ -99,6 +140,7 @@ macro_rules! build_scly_property {
             {
                 match *self {
                     SclyProperty::Unknown { object_type, .. } => object_type,
+                    SclyProperty::Owned { object_type, .. } => object_type,
                     $(SclyProperty::$name(_) =>
                       <scly_props::$name as SclyPropertyData>::OBJECT_TYPE,)*
                 }
@@ -113,35 +155,61 @@ macro_rules! build_scly_property {
                 };
                 *self = if false {
                     return
-                } $(else if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE {
+                } $(else if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE
+                    && <scly_props::$name as SclyPropertyData>::can_decode(&reader)
+                {
                     SclyProperty::$name(reader.read(()))
                 })* else {
                     return
                 };
             }
 
+            /// Decodes data that doesn't borrow from the file into its typed struct if possible
+            fn from_owned_bytes(object_type: u8, data: Vec<u8>) -> SclyProperty<'r>
+            {
+                $(if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE
+                    && <scly_props::$name as SclyPropertyData>::can_decode(&data)
+                {
+                    let prop: scly_props::$name = Reader::new(&data).read(());
+                    return SclyProperty::$name(Box::new(prop.into_static()))
+                })*
+                SclyProperty::Owned { object_type, data }
+            }
+
             $(
                 pub fn $is_check(&self) -> bool
                 {
                     match *self {
                         SclyProperty::$name(_) => true,
-                        SclyProperty::Unknown { object_type, .. } =>
+                        SclyProperty::Unknown { object_type, .. } |
+                        SclyProperty::Owned { object_type, .. } =>
                             object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE,
                         _ => false,
                     }
                 }
 
-                pub fn $accessor(&self) -> Option<Cow<scly_props::$name<'r>>>
+                pub fn $accessor(&self) -> Option<Cow<'_, scly_props::$name<'_>>>
                 {
                     match *self {
-                        SclyProperty::$name(ref inst) => Some(Cow::Borrowed(inst)),
+                        SclyProperty::$name(ref inst) => Some(Cow::Borrowed(&**inst)),
                         SclyProperty::Unknown { ref data, object_type, .. } => {
-                            if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE {
+                            if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE
+                                && <scly_props::$name as SclyPropertyData>::can_decode(data)
+                            {
                                 Some(Cow::Owned(data.clone().read(())))
                             } else {
                                 None
                             }
                         },
+                        SclyProperty::Owned { ref data, object_type, .. } => {
+                            if object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE
+                                && <scly_props::$name as SclyPropertyData>::can_decode(data)
+                            {
+                                Some(Cow::Owned(Reader::new(data).read(())))
+                            } else {
+                                None
+                            }
+                        },
                         _ => None,
                     }
                 }
@@ -154,7 +222,9 @@ macro_rules! build_scly_property {
                         SclyProperty::$name(ref mut inst) => return Some(inst),
                         _ => return None,
                     };
-                    if object_type != <scly_props::$name as SclyPropertyData>::OBJECT_TYPE {
+                    if object_type != <scly_props::$name as SclyPropertyData>::OBJECT_TYPE
+                        || !<scly_props::$name as SclyPropertyData>::can_decode(&data)
+                    {
                         return None
                     }
                     *self = SclyProperty::$name(data.read(()));
@@ -166,6 +236,23 @@ macro_rules! build_scly_property {
             )*
         }
 
+        impl<'r> SclyProperty<'r>
+        {
+            /// The serialized form of this property, without copying it if it's untyped
+            pub fn to_bytes(&self) -> Cow<'_, [u8]>
+            {
+                match *self {
+                    SclyProperty::Unknown { ref data, .. } => Cow::Borrowed(&data[..]),
+                    SclyProperty::Owned { ref data, .. } => Cow::Borrowed(&data[..]),
+                    _ => {
+                        let mut bytes = Vec::with_capacity(self.size());
+                        self.write_to(&mut bytes).unwrap();
+                        Cow::Owned(bytes)
+                    },
+                }
+            }
+        }
+
         impl<'r> Readable<'r> for SclyProperty<'r>
         {
             type Args = (u8, usize);
@@ -183,6 +270,7 @@ macro_rules! build_scly_property {
             {
                 match *self {
                     SclyProperty::Unknown { ref data, .. } => data.len(),
+                    SclyProperty::Owned { ref data, .. } => data.len(),
                     $(SclyProperty::$name(ref i) => i.size(),)*
                 }
             }
@@ -197,6 +285,10 @@ macro_rules! build_scly_property {
                         writer.write_all(&data)?;
                         Ok(data.len() as u64)
                     },
+                    SclyProperty::Owned { ref data, .. } => {
+                        writer.write_all(data)?;
+                        Ok(data.len() as u64)
+                    },
                     $(SclyProperty::$name(ref i) => i.write_to(writer),)*
                 }
             }
@@ -216,32 +308,229 @@ macro_rules! build_scly_property {
 }
 
 build_scly_property!(
-    Actor,             is_actor,              as_actor,              as_actor_mut,
-    DamageableTrigger, is_damageable_trigger, as_damageable_trigger, as_damageable_trigger_mut,
-    Dock,              is_dock,               as_dock,               as_dock_mut,
-    Door,              is_door,               as_door,               as_door_mut,
-    Effect,            is_effect,             as_effect,             as_effect_mut,
-    HudMemo,           is_hud_memo,           as_hud_memo,           as_hud_memo_mut,
-    MemoryRelay,       is_memory_relay,       as_memory_relay,       as_memory_relay_mut,
-    Pickup,            is_pickup,             as_pickup,             as_pickup_mut,
-    Platform,          is_platform,           as_platform,           as_platform_mut,
-    PlayerActor,       is_player_actor,       as_player_actor,       as_player_actor_mut,
-    PlayerHint,        is_player_hint,        as_player_hint,        as_player_hint_mut,
-    PointOfInterest,   is_point_of_interest,  as_point_of_interest,  as_point_of_interest_mut,
-    Relay,             is_relay,              as_relay,              as_relay_mut,
-    Sound,             is_sound,              as_sound,              as_sound_mut,
-    SpawnPoint,        is_spawn_point,        as_spawn_point,        as_spawn_point_mut,
-    SpecialFunction,   is_special_function,   as_special_function,   as_special_function_mut,
-    StreamedAudio,     is_streamed_audio,     as_streamed_audio,     as_streamed_audio_mut,
-    Timer,             is_timer,              as_timer,              as_timer_mut,
-    Trigger,           is_trigger,            as_trigger,            as_trigger_mut,
-    Water,             is_water,              as_water,              as_water_mut,
-    WorldTransporter,  is_world_transporter,  as_world_transporter,  as_world_transporter_mut,
+    Actor,                is_actor,                  as_actor,                  as_actor_mut,
+    ActorKeyframe,        is_actor_keyframe,         as_actor_keyframe,         as_actor_keyframe_mut,
+    ActorRotate,          is_actor_rotate,           as_actor_rotate,           as_actor_rotate_mut,
+    AiJumpPoint,          is_ai_jump_point,          as_ai_jump_point,          as_ai_jump_point_mut,
+    AreaAttributes,       is_area_attributes,        as_area_attributes,        as_area_attributes_mut,
+    AtomicAlpha,          is_atomic_alpha,           as_atomic_alpha,           as_atomic_alpha_mut,
+    AtomicBeta,           is_atomic_beta,            as_atomic_beta,            as_atomic_beta_mut,
+    Babygoth,             is_babygoth,               as_babygoth,               as_babygoth_mut,
+    BallTrigger,          is_ball_trigger,           as_ball_trigger,           as_ball_trigger_mut,
+    Beetle,               is_beetle,                 as_beetle,                 as_beetle_mut,
+    BloodFlower,          is_blood_flower,           as_blood_flower,           as_blood_flower_mut,
+    Burrower,             is_burrower,               as_burrower,               as_burrower_mut,
+    Camera,               is_camera,                 as_camera,                 as_camera_mut,
+    CameraBlurKeyframe,   is_camera_blur_keyframe,   as_camera_blur_keyframe,   as_camera_blur_keyframe_mut,
+    CameraFilterKeyframe, is_camera_filter_keyframe, as_camera_filter_keyframe, as_camera_filter_keyframe_mut,
+    CameraHint,           is_camera_hint,            as_camera_hint,            as_camera_hint_mut,
+    CameraHintTrigger,    is_camera_hint_trigger,    as_camera_hint_trigger,    as_camera_hint_trigger_mut,
+    CameraPitchVolume,    is_camera_pitch_volume,    as_camera_pitch_volume,    as_camera_pitch_volume_mut,
+    CameraShaker,         is_camera_shaker,          as_camera_shaker,          as_camera_shaker_mut,
+    CameraWaypoint,       is_camera_waypoint,        as_camera_waypoint,        as_camera_waypoint_mut,
+    ChozoGhost,           is_chozo_ghost,            as_chozo_ghost,            as_chozo_ghost_mut,
+    ColorModulate,        is_color_modulate,         as_color_modulate,         as_color_modulate_mut,
+    ControllerAction,     is_controller_action,      as_controller_action,      as_controller_action_mut,
+    Counter,              is_counter,                as_counter,                as_counter_mut,
+    CoverPoint,           is_cover_point,            as_cover_point,            as_cover_point_mut,
+    DamageableTrigger,    is_damageable_trigger,     as_damageable_trigger,     as_damageable_trigger_mut,
+    DistanceFog,          is_distance_fog,           as_distance_fog,           as_distance_fog_mut,
+    Dock,                 is_dock,                   as_dock,                   as_dock_mut,
+    DockAreaChange,       is_dock_area_change,       as_dock_area_change,       as_dock_area_change_mut,
+    Door,                 is_door,                   as_door,                   as_door_mut,
+    Drone,                is_drone,                  as_drone,                  as_drone_mut,
+    Effect,               is_effect,                 as_effect,                 as_effect_mut,
+    ElitePirate,          is_elite_pirate,           as_elite_pirate,           as_elite_pirate_mut,
+    EnergyBall,           is_energy_ball,            as_energy_ball,            as_energy_ball_mut,
+    Eyeball,              is_eyeball,                as_eyeball,                as_eyeball_mut,
+    FireFlea,             is_fire_flea,              as_fire_flea,              as_fire_flea_mut,
+    Flaahgra,             is_flaahgra,               as_flaahgra,               as_flaahgra_mut,
+    FlaahgraTentacle,     is_flaahgra_tentacle,      as_flaahgra_tentacle,      as_flaahgra_tentacle_mut,
+    FlickerBat,           is_flicker_bat,            as_flicker_bat,            as_flicker_bat_mut,
+    FlyingPirate,         is_flying_pirate,          as_flying_pirate,          as_flying_pirate_mut,
+    FogVolume,            is_fog_volume,             as_fog_volume,             as_fog_volume_mut,
+    Geemer,               is_geemer,                 as_geemer,                 as_geemer_mut,
+    Generator,            is_generator,              as_generator,              as_generator_mut,
+    HudMemo,              is_hud_memo,               as_hud_memo,               as_hud_memo_mut,
+    IceSheegoth,          is_ice_sheegoth,           as_ice_sheegoth,           as_ice_sheegoth_mut,
+    IceZoomer,            is_ice_zoomer,             as_ice_zoomer,             as_ice_zoomer_mut,
+    Magdolite,            is_magdolite,              as_magdolite,              as_magdolite_mut,
+    MemoryRelay,          is_memory_relay,           as_memory_relay,           as_memory_relay_mut,
+    MetareeAlpha,         is_metaree_alpha,          as_metaree_alpha,          as_metaree_alpha_mut,
+    Metroid,              is_metroid,                as_metroid,                as_metroid_mut,
+    MetroidBeta,          is_metroid_beta,           as_metroid_beta,           as_metroid_beta_mut,
+    Midi,                 is_midi,                   as_midi,                   as_midi_mut,
+    NewIntroBoss,         is_new_intro_boss,         as_new_intro_boss,         as_new_intro_boss_mut,
+    Oculus,               is_oculus,                 as_oculus,                 as_oculus_mut,
+    OmegaPirate,          is_omega_pirate,           as_omega_pirate,           as_omega_pirate_mut,
+    Parasite,             is_parasite,               as_parasite,               as_parasite_mut,
+    PhazonHealingNodule,  is_phazon_healing_nodule,  as_phazon_healing_nodule,  as_phazon_healing_nodule_mut,
+    Pickup,               is_pickup,                 as_pickup,                 as_pickup_mut,
+    PickupGenerator,      is_pickup_generator,       as_pickup_generator,       as_pickup_generator_mut,
+    Platform,             is_platform,               as_platform,               as_platform_mut,
+    PlayerActor,          is_player_actor,           as_player_actor,           as_player_actor_mut,
+    PlayerHint,           is_player_hint,            as_player_hint,            as_player_hint_mut,
+    PlayerStateChange,    is_player_state_change,    as_player_state_change,    as_player_state_change_mut,
+    PointOfInterest,      is_point_of_interest,      as_point_of_interest,      as_point_of_interest_mut,
+    PuddleSpore,          is_puddle_spore,           as_puddle_spore,           as_puddle_spore_mut,
+    PuddleToadGamma,      is_puddle_toad_gamma,      as_puddle_toad_gamma,      as_puddle_toad_gamma_mut,
+    Puffer,               is_puffer,                 as_puffer,                 as_puffer_mut,
+    RadialDamage,         is_radial_damage,          as_radial_damage,          as_radial_damage_mut,
+    RandomRelay,          is_random_relay,           as_random_relay,           as_random_relay_mut,
+    Relay,                is_relay,                  as_relay,                  as_relay_mut,
+    Repulsor,             is_repulsor,               as_repulsor,               as_repulsor_mut,
+    Ridley,               is_ridley,                 as_ridley,                 as_ridley_mut,
+    Ripper,               is_ripper,                 as_ripper,                 as_ripper_mut,
+    RumbleEffect,         is_rumble_effect,          as_rumble_effect,          as_rumble_effect_mut,
+    Seedling,             is_seedling,               as_seedling,               as_seedling_mut,
+    Sound,                is_sound,                  as_sound,                  as_sound_mut,
+    SpacePirate,          is_space_pirate,           as_space_pirate,           as_space_pirate_mut,
+    SpankWeed,            is_spank_weed,             as_spank_weed,             as_spank_weed_mut,
+    SpawnPoint,           is_spawn_point,            as_spawn_point,            as_spawn_point_mut,
+    SpecialFunction,      is_special_function,       as_special_function,       as_special_function_mut,
+    SpiderBallWaypoint,   is_spider_ball_waypoint,   as_spider_ball_waypoint,   as_spider_ball_waypoint_mut,
+    StreamedAudio,        is_streamed_audio,         as_streamed_audio,         as_streamed_audio_mut,
+    Switch,               is_switch,                 as_switch,                 as_switch_mut,
+    TargetingPoint,       is_targeting_point,        as_targeting_point,        as_targeting_point_mut,
+    Thardus,              is_thardus,                as_thardus,                as_thardus_mut,
+    ThardusRollingBomb,   is_thardus_rolling_bomb,   as_thardus_rolling_bomb,   as_thardus_rolling_bomb_mut,
+    ThermalHeatFader,     is_thermal_heat_fader,     as_thermal_heat_fader,     as_thermal_heat_fader_mut,
+    Timer,                is_timer,                  as_timer,                  as_timer_mut,
+    Trigger,              is_trigger,                as_trigger,                as_trigger_mut,
+    Tryclops,             is_tryclops,               as_tryclops,               as_tryclops_mut,
+    Warwasp,              is_warwasp,                as_warwasp,                as_warwasp_mut,
+    Water,                is_water,                  as_water,                  as_water_mut,
+    Waypoint,             is_waypoint,               as_waypoint,               as_waypoint_mut,
+    WorldLightFader,      is_world_light_fader,      as_world_light_fader,      as_world_light_fader_mut,
+    WorldTransporter,     is_world_transporter,      as_world_transporter,      as_world_transporter_mut,
+);
+
+macro_rules! build_patterned_info_accessors {
+    ($($name:ident, $is_check:ident,)*) => {
+        impl<'r> SclyProperty<'r>
+        {
+            /// Whether this is any of the enemy object types that have a PatternedInfo
+            pub fn is_patterned_ai(&self) -> bool
+            {
+                false $(|| self.$is_check())*
+            }
+
+            pub fn is_patterned_ai_type(object_type: u8) -> bool
+            {
+                false $(|| object_type == <scly_props::$name as SclyPropertyData>::OBJECT_TYPE)*
+            }
+
+            pub fn patterned_info(&self) -> Option<Cow<'_, scly_props::structs::PatternedInfo>>
+            {
+                if !self.is_patterned_ai() {
+                    return None
+                }
+                match *self {
+                    $(SclyProperty::$name(ref inst) => Some(Cow::Borrowed(&inst.patterned_info)),)*
+                    _ => {
+                        let data = self.to_bytes();
+                        let mut prop = SclyProperty::Unknown {
+                            object_type: self.object_type(),
+                            data: Reader::new(&data),
+                        };
+                        prop.guess_kind();
+                        // Stays untyped if the data can't be decoded, e.g. an unexpected
+                        // property count, so don't recurse on it
+                        match prop {
+                            $(SclyProperty::$name(inst) =>
+                                Some(Cow::Owned(inst.patterned_info)),)*
+                            _ => None,
+                        }
+                    },
+                }
+            }
+
+            pub fn patterned_info_mut(&mut self) -> Option<&mut scly_props::structs::PatternedInfo>
+            {
+                if !self.is_patterned_ai() {
+                    return None
+                }
+                self.guess_kind();
+                match *self {
+                    $(SclyProperty::$name(ref mut inst) => Some(&mut inst.patterned_info),)*
+                    _ => None,
+                }
+            }
+
+            pub fn patterned_scale_mut(&mut self) -> Option<&mut GenericArray<f32, U3>>
+            {
+                if !self.is_patterned_ai() {
+                    return None
+                }
+                self.guess_kind();
+                match *self {
+                    $(SclyProperty::$name(ref mut inst) => Some(&mut inst.scale),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+build_patterned_info_accessors!(
+    AtomicAlpha,         is_atomic_alpha,
+    AtomicBeta,          is_atomic_beta,
+    Babygoth,            is_babygoth,
+    Beetle,              is_beetle,
+    BloodFlower,         is_blood_flower,
+    Burrower,            is_burrower,
+    ChozoGhost,          is_chozo_ghost,
+    Drone,               is_drone,
+    ElitePirate,         is_elite_pirate,
+    EnergyBall,          is_energy_ball,
+    Eyeball,             is_eyeball,
+    FireFlea,            is_fire_flea,
+    Flaahgra,            is_flaahgra,
+    FlaahgraTentacle,    is_flaahgra_tentacle,
+    FlickerBat,          is_flicker_bat,
+    FlyingPirate,        is_flying_pirate,
+    Geemer,              is_geemer,
+    IceSheegoth,         is_ice_sheegoth,
+    IceZoomer,           is_ice_zoomer,
+    Magdolite,           is_magdolite,
+    MetareeAlpha,        is_metaree_alpha,
+    Metroid,             is_metroid,
+    MetroidBeta,         is_metroid_beta,
+    NewIntroBoss,        is_new_intro_boss,
+    Oculus,              is_oculus,
+    OmegaPirate,         is_omega_pirate,
+    Parasite,            is_parasite,
+    PhazonHealingNodule, is_phazon_healing_nodule,
+    PuddleSpore,         is_puddle_spore,
+    PuddleToadGamma,     is_puddle_toad_gamma,
+    Puffer,              is_puffer,
+    Ridley,              is_ridley,
+    Ripper,              is_ripper,
+    Seedling,            is_seedling,
+    SpacePirate,         is_space_pirate,
+    SpankWeed,           is_spank_weed,
+    Thardus,             is_thardus,
+    ThardusRollingBomb,  is_thardus_rolling_bomb,
+    Tryclops,            is_tryclops,
+    Warwasp,             is_warwasp,
 );
 
 pub trait SclyPropertyData
 {
     const OBJECT_TYPE: u8;
+    /// The property counts this type can be decoded from. Data with any other count is left
+    /// untyped rather than failing to decode. `None` means the count isn't checked first.
+    const PROP_COUNTS: Option<&'static [u32]> = None;
+
+    fn can_decode(data: &[u8]) -> bool
+    {
+        match (Self::PROP_COUNTS, data) {
+            (None, _) => true,
+            (Some(counts), [a, b, c, d, ..]) =>
+                counts.contains(&u32::from_be_bytes([*a, *b, *c, *d])),
+            (Some(_), _) => false,
+        }
+    }
 }
 
 
@@ -259,6 +548,16 @@ macro_rules! build_scly_conn_field {
         impl $struct_name
         {
             $(pub const $field: $struct_name = $struct_name($value);)+
+
+            pub fn from_name(name: &str) -> Option<$struct_name>
+            {
+                $(
+                if name.eq_ignore_ascii_case(stringify!($field)) {
+                    return Some($struct_name::$field)
+                }
+                )+
+                None
+            }
         }
 
         impl fmt::Debug for $struct_name
@@ -352,3 +651,84 @@ build_scly_conn_field!(ConnectionMsg {
     PLAY = 0x14,
     ALERT = 0x15,
 });
+
+#[test]
+fn test_unexpected_prop_count_stays_untyped()
+{
+    use crate::scly_props::Counter;
+    use reader_writer::CStrConversionExtension;
+
+    let counter = Counter {
+        name: b"Counter\0".as_cstr(),
+        start_value: 0,
+        max_value: 3,
+        auto_reset: 0,
+        active: 1,
+    };
+    let mut data = vec![];
+    counter.write_to(&mut data).unwrap();
+    let property = SclyProperty::Unknown {
+        object_type: Counter::OBJECT_TYPE,
+        data: Reader::new(&data),
+    };
+    assert_eq!(property.as_counter().unwrap().max_value, 3);
+
+    // A counter with an extra property, which the typed struct doesn't know about
+    data[3] += 1;
+    data.push(0);
+    let mut property = SclyProperty::Unknown {
+        object_type: Counter::OBJECT_TYPE,
+        data: Reader::new(&data),
+    };
+    assert!(property.is_counter());
+    assert!(property.as_counter().is_none());
+    assert!(property.as_counter_mut().is_none());
+    property.guess_kind();
+    assert!(matches!(property, SclyProperty::Unknown { .. }));
+}
+
+#[test]
+fn test_patterned_info_of_undecodable_enemy()
+{
+    use crate::scly_props::Warwasp;
+
+    // A Warwasp with one property more than the typed struct expects
+    let mut data = 14u32.to_be_bytes().to_vec();
+    data.extend_from_slice(b"Warwasp\0");
+    let mut property = SclyProperty::Unknown {
+        object_type: Warwasp::OBJECT_TYPE,
+        data: Reader::new(&data),
+    };
+    assert!(property.is_patterned_ai());
+    assert!(property.patterned_info().is_none());
+    assert!(property.patterned_info_mut().is_none());
+    assert!(property.patterned_scale_mut().is_none());
+}
+
+#[test]
+fn test_set_property_keeps_typed_access()
+{
+    use crate::scly_props::Counter;
+    use reader_writer::CStrConversionExtension;
+
+    let mut obj = SclyObject {
+        instance_id: 0,
+        connections: vec![].into(),
+        property_data: Counter {
+            name: b"Counter\0".as_cstr(),
+            start_value: 0,
+            max_value: 3,
+            auto_reset: 0,
+            active: 1,
+        }.into(),
+    };
+    obj.set_property("Name", PropertyValue::String("Edited counter".to_owned())).unwrap();
+    obj.set_property("MaxValue", PropertyValue::U32(5)).unwrap();
+
+    let counter = obj.property_data.as_counter_mut().unwrap();
+    assert_eq!(counter.name.to_str().unwrap(), "Edited counter");
+    assert_eq!(counter.max_value, 5);
+    counter.start_value = 2;
+    assert_eq!(obj.get_property("StartValue"), Ok(PropertyValue::U32(2)));
+    assert_eq!(obj.get_property("MaxValue"), Ok(PropertyValue::U32(5)));
+}
@@ -0,0 +1,34 @@
+use auto_struct_macros::auto_struct;
+
+use crate::{ResId, SclyPropertyData};
+use crate::res_id::*;
+
+use std::marker::PhantomData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct AreaAttributes<'r>
+{
+    #[auto_struct(expect = 9)]
+    prop_count: u32,
+
+    // Unlike most objects, AreaAttributes don't have a name
+    pub load: u32,
+    pub show_skybox: u8,
+    pub env_fx: u32,
+    pub env_fx_density: f32,
+    pub thermal_heat: f32,
+    pub xray_fog_distance: f32,
+    pub world_lighting_level: f32,
+    pub skybox_cmdl: ResId<CMDL>,
+    pub phazon_type: u32,
+
+    // Dummy so we can have a <'r>
+    pub _dummy: PhantomData<&'r ()>,
+}
+
+impl<'r> SclyPropertyData for AreaAttributes<'r>
+{
+    const OBJECT_TYPE: u8 = 0x4E;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[9]);
+}
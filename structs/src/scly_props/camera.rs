@@ -0,0 +1,40 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct Camera<'r>
+{
+    #[auto_struct(derive = 14 + disable_out_of_into.is_some() as u32)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub rotation: GenericArray<f32, U3>,
+
+    pub active: u8,
+    pub shot_duration: f32,
+    pub look_at_player: u8,
+    pub out_of_player_eye: u8,
+    pub into_player_eye: u8,
+    pub draw_player: u8,
+    pub disable_input: u8,
+    pub unknown: u8,
+    pub finish_cine_skip: u8,
+    pub fov: f32,
+    pub check_failsafe: u8,
+
+    #[auto_struct(init = if prop_count == 15 { Some(()) } else { None })]
+    pub disable_out_of_into: Option<u8>,
+}
+
+impl<'r> SclyPropertyData for Camera<'r>
+{
+    const OBJECT_TYPE: u8 = 0x0C;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[14, 15]);
+}
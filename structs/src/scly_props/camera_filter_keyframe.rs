@@ -0,0 +1,33 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::{ResId, SclyPropertyData};
+use crate::res_id::*;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct CameraFilterKeyframe<'r>
+{
+    #[auto_struct(expect = 10)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub active: u8,
+    pub filter_type: u32,
+    pub filter_shape: u32,
+    pub filter_index: u32,
+    pub unknown: u32,
+    pub color: GenericArray<f32, U4>, // RGBA
+    pub fade_in_time: f32,
+    pub fade_out_time: f32,
+    pub overlay_txtr: ResId<TXTR>,
+}
+
+impl<'r> SclyPropertyData for CameraFilterKeyframe<'r>
+{
+    const OBJECT_TYPE: u8 = 0x18;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[10]);
+}
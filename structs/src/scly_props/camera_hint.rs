@@ -0,0 +1,33 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+use crate::scly_props::structs::UndecodedProperties;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct CameraHint<'r>
+{
+    #[auto_struct(expect = 25)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub rotation: GenericArray<f32, U3>,
+
+    pub active: u8,
+    pub priority: u32,
+    pub behaviour: u32,
+
+    // The override flags and the camera parameters they control
+    pub remainder: UndecodedProperties<'r>,
+}
+
+impl<'r> SclyPropertyData for CameraHint<'r>
+{
+    const OBJECT_TYPE: u8 = 0x10;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[25]);
+}
@@ -0,0 +1,29 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct CameraShaker<'r>
+{
+    #[auto_struct(expect = 9)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub x_magnitude: f32,
+    pub x_b: f32,
+    pub y_magnitude: f32,
+    pub y_b: f32,
+    pub z_magnitude: f32,
+    pub z_b: f32,
+    pub duration: f32,
+    pub active: u8,
+}
+
+impl<'r> SclyPropertyData for CameraShaker<'r>
+{
+    const OBJECT_TYPE: u8 = 0x1C;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[9]);
+}
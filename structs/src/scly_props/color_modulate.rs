@@ -0,0 +1,34 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct ColorModulate<'r>
+{
+    #[auto_struct(expect = 13)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub color_a: GenericArray<f32, U4>, // RGBA
+    pub color_b: GenericArray<f32, U4>, // RGBA
+    pub blend_mode: u32,
+    pub time_a_to_b: f32,
+    pub time_b_to_a: f32,
+    pub do_reverse: u8,
+    pub reset_target_when_done: u8,
+    pub depth_compare: u8,
+    pub depth_update: u8,
+    pub depth_backwards: u8,
+    pub active: u8,
+}
+
+impl<'r> SclyPropertyData for ColorModulate<'r>
+{
+    const OBJECT_TYPE: u8 = 0x5E;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[13]);
+}
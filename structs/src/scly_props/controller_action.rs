@@ -0,0 +1,24 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct ControllerAction<'r>
+{
+    #[auto_struct(expect = 4)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub active: u8,
+    pub command: u32,
+    pub one_shot: u8,
+}
+
+impl<'r> SclyPropertyData for ControllerAction<'r>
+{
+    const OBJECT_TYPE: u8 = 0x55;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[4]);
+}
@@ -8,7 +8,7 @@ use crate::res_id::*;
 use crate::scly_props::structs::{DamageVulnerability, HealthInfo, VisorParameters};
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct DamageableTrigger<'r>
 {
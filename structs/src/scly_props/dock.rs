@@ -5,7 +5,7 @@ use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 use crate::SclyPropertyData;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct Dock<'r>
 {
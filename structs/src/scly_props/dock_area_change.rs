@@ -0,0 +1,23 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct DockAreaChange<'r>
+{
+    #[auto_struct(expect = 3)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub dock_index: u32,
+    pub active: u8,
+}
+
+impl<'r> SclyPropertyData for DockAreaChange<'r>
+{
+    const OBJECT_TYPE: u8 = 0x38;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[3]);
+}
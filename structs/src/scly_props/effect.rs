@@ -9,7 +9,7 @@ use crate::scly_props::structs::LightParameters;
 use crate::res_id::*;
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct Effect<'r>
 {
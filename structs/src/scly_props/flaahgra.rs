@@ -0,0 +1,53 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::{ResId, SclyPropertyData};
+use crate::res_id::*;
+use crate::scly_props::structs::{
+    ActorParameters, AncsProp, DamageInfo, DamageVulnerability, PatternedInfo,
+};
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct Flaahgra<'r>
+{
+    #[auto_struct(expect = 23)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub rotation: GenericArray<f32, U3>,
+    pub scale: GenericArray<f32, U3>,
+
+    pub patterned_info: PatternedInfo,
+    pub actor_params: ActorParameters,
+
+    pub unknown0: f32,
+    pub unknown1: f32,
+    pub unknown2: f32,
+    pub faint_duration: f32,
+    pub unknown_vulnerability: DamageVulnerability,
+
+    pub projectile0: u32,
+    pub projectile0_damage: DamageInfo,
+    pub projectile1: u32,
+    pub projectile1_damage: DamageInfo,
+    pub plants_particle: ResId<PART>,
+    pub plants_damage: DamageInfo,
+
+    pub unknown_actor_params: ActorParameters,
+    pub unknown3: f32,
+    pub unknown4: f32,
+    pub unknown5: f32,
+    pub unknown_animation_parameters: AncsProp,
+    pub dependency_group: u32,
+}
+
+impl<'r> SclyPropertyData for Flaahgra<'r>
+{
+    const OBJECT_TYPE: u8 = 0x4D;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[23]);
+}
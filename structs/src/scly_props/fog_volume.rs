@@ -0,0 +1,29 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct FogVolume<'r>
+{
+    #[auto_struct(expect = 7)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub scale: GenericArray<f32, U3>,
+    pub flicker_speed: f32,
+    pub unknown: f32,
+    pub color: GenericArray<f32, U4>, // RGBA
+    pub active: u8,
+}
+
+impl<'r> SclyPropertyData for FogVolume<'r>
+{
+    const OBJECT_TYPE: u8 = 0x65;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[7]);
+}
@@ -4,7 +4,7 @@ use reader_writer::CStr;
 use crate::{ResId, SclyPropertyData};
 use crate::res_id::*;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct HudMemo<'r>
 {
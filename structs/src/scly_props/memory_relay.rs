@@ -4,7 +4,7 @@ use crate::SclyPropertyData;
 use reader_writer::CStr;
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct MemoryRelay<'r>
 {
@@ -0,0 +1,26 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct Midi<'r>
+{
+    #[auto_struct(expect = 6)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub active: u8,
+    pub csng: u32,
+    pub fade_in_time: f32,
+    pub fade_out_time: f32,
+    pub volume: u32,
+}
+
+impl<'r> SclyPropertyData for Midi<'r>
+{
+    const OBJECT_TYPE: u8 = 0x60;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[6]);
+}
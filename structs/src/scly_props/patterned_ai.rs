@@ -0,0 +1,84 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+use crate::scly_props::structs::{ActorParameters, PatternedInfo, UndecodedProperties};
+
+// Every PatternedAI object begins with the same set of properties: its name, transform,
+// PatternedInfo and ActorParameters. Only that prefix is typed here. The properties that are
+// unique to each kind of enemy stay raw bytes in `remainder`, so edit those through
+// `SclyObject::set_property` instead.
+macro_rules! decl_patterned_ai {
+    ($($name:ident, $object_type:literal, $prop_count:literal,)*) => {
+        $(
+            #[auto_struct(Readable, Writable, IntoStatic)]
+            #[derive(Debug, Clone)]
+            pub struct $name<'r>
+            {
+                #[auto_struct(expect = $prop_count)]
+                prop_count: u32,
+
+                pub name: CStr<'r>,
+
+                pub position: GenericArray<f32, U3>,
+                pub rotation: GenericArray<f32, U3>,
+                pub scale: GenericArray<f32, U3>,
+
+                pub patterned_info: PatternedInfo,
+                pub actor_params: ActorParameters,
+
+                pub remainder: UndecodedProperties<'r>,
+            }
+
+            impl<'r> SclyPropertyData for $name<'r>
+            {
+                const OBJECT_TYPE: u8 = $object_type;
+                const PROP_COUNTS: Option<&'static [u32]> = Some(&[$prop_count]);
+            }
+        )*
+    };
+}
+
+decl_patterned_ai!(
+    NewIntroBoss,        0x0E, 13,
+    Beetle,              0x16, 16,
+    Warwasp,             0x21, 13,
+    SpacePirate,         0x24, 34,
+    FlyingPirate,        0x25, 36,
+    ElitePirate,         0x26, 41,
+    MetroidBeta,         0x27, 23,
+    ChozoGhost,          0x28, 32,
+    BloodFlower,         0x2D, 18,
+    FlickerBat,          0x2E, 10,
+    PuddleSpore,         0x31, 16,
+    PuddleToadGamma,     0x34, 17,
+    FireFlea,            0x36, 9,
+    MetareeAlpha,        0x37, 12,
+    SpankWeed,           0x3B, 11,
+    Parasite,            0x3D, 25,
+    Ripper,              0x3F, 8,
+    Drone,               0x43, 45,
+    Metroid,             0x44, 20,
+    IceSheegoth,         0x4B, 35,
+    Thardus,             0x58, 44,
+    FlaahgraTentacle,    0x5C, 6,
+    ThardusRollingBomb,  0x5F, 6,
+    Babygoth,            0x66, 33,
+    Eyeball,             0x67, 21,
+    Magdolite,           0x6B, 22,
+    Oculus,              0x6F, 15,
+    Geemer,              0x70, 16,
+    AtomicAlpha,         0x72, 14,
+    AtomicBeta,          0x77, 21,
+    IceZoomer,           0x78, 16,
+    Puffer,              0x79, 16,
+    Tryclops,            0x7A, 10,
+    Ridley,              0x7B, 48,
+    Seedling,            0x7C, 14,
+    Burrower,            0x7F, 13,
+    OmegaPirate,         0x86, 51,
+    PhazonHealingNodule, 0x88, 9,
+    EnergyBall,          0x8B, 16,
+);
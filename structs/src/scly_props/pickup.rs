@@ -8,7 +8,7 @@ use crate::scly_props::structs::{ActorParameters, AncsProp};
 use crate::res_id::*;
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct Pickup<'r>
 {
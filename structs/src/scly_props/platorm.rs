@@ -8,7 +8,7 @@ use crate::res_id::*;
 use crate::scly_props::structs::{ActorParameters, AncsProp, DamageVulnerability, HealthInfo};
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct Platform<'r>
 {
@@ -10,7 +10,7 @@ use crate::scly_props::structs::{
 };
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct PlayerActor<'r>
 {
@@ -16,7 +16,7 @@ pub struct PlayerHintStruct
     pub unknowns: GenericArray<u8, U15>,
 }
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct PlayerHint<'r>
 {
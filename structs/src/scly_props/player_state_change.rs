@@ -0,0 +1,27 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct PlayerStateChange<'r>
+{
+    #[auto_struct(expect = 7)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub active: u8,
+    pub item: u32,
+    pub amount: u32,
+    pub capacity: u32,
+    pub control: u32,
+    pub control_command: u32,
+}
+
+impl<'r> SclyPropertyData for PlayerStateChange<'r>
+{
+    const OBJECT_TYPE: u8 = 0x57;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[7]);
+}
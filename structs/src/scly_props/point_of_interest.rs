@@ -6,7 +6,7 @@ use reader_writer::generic_array::GenericArray;
 use crate::scly_props::structs::ScannableParameters;
 use crate::SclyPropertyData;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct PointOfInterest<'r>
 {
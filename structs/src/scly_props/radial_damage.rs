@@ -0,0 +1,28 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+use crate::scly_props::structs::DamageInfo;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct RadialDamage<'r>
+{
+    #[auto_struct(expect = 5)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub active: u8,
+    pub damage_info: DamageInfo,
+    pub radius: f32,
+}
+
+impl<'r> SclyPropertyData for RadialDamage<'r>
+{
+    const OBJECT_TYPE: u8 = 0x68;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[5]);
+}
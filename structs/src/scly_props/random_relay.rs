@@ -0,0 +1,25 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct RandomRelay<'r>
+{
+    #[auto_struct(expect = 5)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub send_set_size: u32,
+    pub send_set_variance: u32,
+    pub percent_size: u8,
+    pub active: u8,
+}
+
+impl<'r> SclyPropertyData for RandomRelay<'r>
+{
+    const OBJECT_TYPE: u8 = 0x14;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[5]);
+}
@@ -3,7 +3,7 @@ use auto_struct_macros::auto_struct;
 use reader_writer::CStr;
 use crate::SclyPropertyData;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct Relay<'r>
 {
@@ -0,0 +1,26 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct Repulsor<'r>
+{
+    #[auto_struct(expect = 4)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub active: u8,
+    pub radius: f32,
+}
+
+impl<'r> SclyPropertyData for Repulsor<'r>
+{
+    const OBJECT_TYPE: u8 = 0x63;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[4]);
+}
@@ -0,0 +1,28 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use reader_writer::typenum::*;
+use reader_writer::generic_array::GenericArray;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct RumbleEffect<'r>
+{
+    #[auto_struct(expect = 6)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub position: GenericArray<f32, U3>,
+    pub active: u8,
+    pub intensity: f32,
+    pub effect: u32,
+    pub flags: u32,
+}
+
+impl<'r> SclyPropertyData for RumbleEffect<'r>
+{
+    const OBJECT_TYPE: u8 = 0x74;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[6]);
+}
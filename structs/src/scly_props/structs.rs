@@ -1,11 +1,15 @@
 use auto_struct_macros::auto_struct;
 
+use reader_writer::{IntoStatic, Readable, Reader, Writable};
 use reader_writer::typenum::*;
 use reader_writer::generic_array::GenericArray;
 
 use crate::ResId;
 use crate::res_id:: *;
 
+use std::borrow::Cow;
+use std::io;
+
 #[auto_struct(Readable, Writable, FixedSize)]
 #[derive(Debug, Clone)]
 pub struct ActorParameters
@@ -231,3 +235,42 @@ pub struct PatternedInfo
     pub particle1: ResId<PART>,
     pub ice_shatter_sfx: u32,
 }
+
+/// The trailing properties of an object that haven't been given a typed representation
+/// yet. When read, consumes the rest of the object's property data.
+#[derive(Debug, Clone)]
+pub struct UndecodedProperties<'r>(pub Cow<'r, [u8]>);
+
+impl<'r> Readable<'r> for UndecodedProperties<'r>
+{
+    type Args = ();
+    fn read_from(reader: &mut Reader<'r>, (): ()) -> Self
+    {
+        let res = reader.clone();
+        reader.advance(res.len());
+        UndecodedProperties(Cow::Borrowed(*res))
+    }
+
+    fn size(&self) -> usize
+    {
+        self.0.len()
+    }
+}
+
+impl<'r> Writable for UndecodedProperties<'r>
+{
+    fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<u64>
+    {
+        writer.write_all(&self.0)?;
+        Ok(self.0.len() as u64)
+    }
+}
+
+impl<'r> IntoStatic for UndecodedProperties<'r>
+{
+    type Static = UndecodedProperties<'static>;
+    fn into_static(self) -> Self::Static
+    {
+        UndecodedProperties(Cow::Owned(self.0.into_owned()))
+    }
+}
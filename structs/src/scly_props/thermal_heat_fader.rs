@@ -0,0 +1,24 @@
+use auto_struct_macros::auto_struct;
+
+use reader_writer::CStr;
+use crate::SclyPropertyData;
+
+#[auto_struct(Readable, Writable, IntoStatic)]
+#[derive(Debug, Clone)]
+pub struct ThermalHeatFader<'r>
+{
+    #[auto_struct(expect = 4)]
+    prop_count: u32,
+
+    pub name: CStr<'r>,
+
+    pub active: u8,
+    pub faded_level: f32,
+    pub initial_level: f32,
+}
+
+impl<'r> SclyPropertyData for ThermalHeatFader<'r>
+{
+    const OBJECT_TYPE: u8 = 0x7D;
+    const PROP_COUNTS: Option<&'static [u32]> = Some(&[4]);
+}
@@ -7,7 +7,7 @@ use reader_writer::generic_array::GenericArray;
 use crate::scly_props::structs::DamageInfo;
 use crate::SclyPropertyData;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct Trigger<'r>
 {
@@ -7,7 +7,7 @@ use crate::{ResId, SclyPropertyData};
 use crate::res_id::*;
 use crate::scly_props::structs::AncsProp;
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct WorldTransporter<'r>
 {
@@ -42,7 +42,7 @@ pub struct WorldTransporter<'r>
 }
 
 
-#[auto_struct(Readable, Writable)]
+#[auto_struct(Readable, Writable, IntoStatic)]
 #[derive(Debug, Clone)]
 pub struct WorldTransporterPalAdditions<'r>
 {
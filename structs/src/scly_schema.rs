@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// The type of a single (non-struct) property in the SCLY property schema
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropertyType
+{
+    Bool,
+    U32,
+    F32,
+    Vec2,
+    Vec3,
+    Color,
+    String,
+    Asset,
+}
+
+impl fmt::Display for PropertyType
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str(match *self {
+            PropertyType::Bool => "bool",
+            PropertyType::U32 => "u32",
+            PropertyType::F32 => "f32",
+            PropertyType::Vec2 => "vec2",
+            PropertyType::Vec3 => "vec3",
+            PropertyType::Color => "color",
+            PropertyType::String => "string",
+            PropertyType::Asset => "asset",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue
+{
+    Bool(bool),
+    U32(u32),
+    F32(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Color([f32; 4]),
+    String(String),
+    Asset(u32),
+}
+
+impl PropertyValue
+{
+    pub fn property_type(&self) -> PropertyType
+    {
+        match *self {
+            PropertyValue::Bool(_) => PropertyType::Bool,
+            PropertyValue::U32(_) => PropertyType::U32,
+            PropertyValue::F32(_) => PropertyType::F32,
+            PropertyValue::Vec2(_) => PropertyType::Vec2,
+            PropertyValue::Vec3(_) => PropertyType::Vec3,
+            PropertyValue::Color(_) => PropertyType::Color,
+            PropertyValue::String(_) => PropertyType::String,
+            PropertyValue::Asset(_) => PropertyType::Asset,
+        }
+    }
+
+    fn read(ty: PropertyType, bytes: &[u8]) -> PropertyValue
+    {
+        let f32_at = |i: usize| f32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let u32_at = |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        match ty {
+            PropertyType::Bool => PropertyValue::Bool(bytes[0] != 0),
+            PropertyType::U32 => PropertyValue::U32(u32_at(0)),
+            PropertyType::F32 => PropertyValue::F32(f32_at(0)),
+            PropertyType::Vec2 => PropertyValue::Vec2([f32_at(0), f32_at(4)]),
+            PropertyType::Vec3 => PropertyValue::Vec3([f32_at(0), f32_at(4), f32_at(8)]),
+            PropertyType::Color => PropertyValue::Color([f32_at(0), f32_at(4), f32_at(8), f32_at(12)]),
+            PropertyType::String =>
+                PropertyValue::String(String::from_utf8_lossy(&bytes[..bytes.len() - 1]).into_owned()),
+            PropertyType::Asset => PropertyValue::Asset(u32_at(0)),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8>
+    {
+        match *self {
+            PropertyValue::Bool(b) => vec![b as u8],
+            PropertyValue::U32(i) | PropertyValue::Asset(i) => i.to_be_bytes().to_vec(),
+            PropertyValue::F32(f) => f.to_be_bytes().to_vec(),
+            PropertyValue::Vec2(ref v) => v.iter().flat_map(|f| f.to_be_bytes()).collect(),
+            PropertyValue::Vec3(ref v) => v.iter().flat_map(|f| f.to_be_bytes()).collect(),
+            PropertyValue::Color(ref v) => v.iter().flat_map(|f| f.to_be_bytes()).collect(),
+            PropertyValue::String(ref s) => s.bytes().chain(Some(0)).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum FieldKind
+{
+    Value(PropertyType),
+    Struct(usize),
+}
+
+#[derive(Debug)]
+struct SchemaField
+{
+    name: String,
+    kind: FieldKind,
+}
+
+#[derive(Debug)]
+struct SchemaStruct
+{
+    name: String,
+    counted: bool,
+    fields: Vec<SchemaField>,
+}
+
+/// Field layouts for SCLY objects that don't have a dedicated Rust struct (or where it's
+/// more convenient to address a property by name). Loaded from `scly_schema.txt`.
+#[derive(Debug)]
+pub struct SclySchema
+{
+    structs: Vec<SchemaStruct>,
+    objects: HashMap<u8, SchemaStruct>,
+}
+
+pub fn scly_schema() -> &'static SclySchema
+{
+    static SCHEMA: OnceLock<SclySchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        SclySchema::parse(include_str!("scly_schema.txt"))
+            .unwrap_or_else(|e| panic!("Invalid SCLY property schema: {}", e))
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String>
+{
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Property data ends unexpectedly".to_owned())
+}
+
+impl SclySchema
+{
+    pub fn parse(src: &str) -> Result<SclySchema, String>
+    {
+        // Struct names are resolved once every struct has been seen, so that a struct can
+        // be used before it's declared.
+        let mut raw_structs: Vec<(Option<u8>, SchemaStruct, Vec<&str>)> = vec![];
+        for (line_num, line) in src.lines().enumerate() {
+            let line = line.split('#').next().unwrap();
+            if line.trim().is_empty() {
+                continue
+            }
+            let words: Vec<_> = line.split_whitespace().collect();
+            let err = || format!("Line {}: malformed declaration \"{}\"", line_num + 1, line.trim());
+            if line.starts_with(char::is_whitespace) {
+                let (_, ref mut s, ref mut type_names) = raw_structs.last_mut().ok_or_else(err)?;
+                if words.len() != 2 {
+                    return Err(err())
+                }
+                s.fields.push(SchemaField {
+                    name: words[0].to_owned(),
+                    kind: FieldKind::Struct(0),
+                });
+                type_names.push(words[1]);
+            } else if words[0] == "struct" && (words.len() == 2 || words.len() == 3) {
+                if words.len() == 3 && words[2] != "counted" {
+                    return Err(err())
+                }
+                let s = SchemaStruct {
+                    name: words[1].to_owned(),
+                    counted: words.len() == 3,
+                    fields: vec![],
+                };
+                raw_structs.push((None, s, vec![]));
+            } else if words[0] == "object" && words.len() == 3 {
+                let object_type = words[1].strip_prefix("0x")
+                    .and_then(|s| u8::from_str_radix(s, 16).ok())
+                    .ok_or_else(err)?;
+                let s = SchemaStruct {
+                    name: words[2].to_owned(),
+                    counted: true,
+                    fields: vec![],
+                };
+                raw_structs.push((Some(object_type), s, vec![]));
+            } else {
+                return Err(err())
+            }
+        }
+
+        let struct_indices: HashMap<String, usize> = raw_structs.iter()
+            .filter(|(object_type, _, _)| object_type.is_none())
+            .enumerate()
+            .map(|(i, (_, s, _))| (s.name.clone(), i))
+            .collect();
+
+        let mut schema = SclySchema {
+            structs: vec![],
+            objects: HashMap::new(),
+        };
+        for (object_type, mut s, type_names) in raw_structs {
+            let struct_name = s.name.clone();
+            for (field, type_name) in s.fields.iter_mut().zip(type_names) {
+                field.kind = match type_name {
+                    "bool" => FieldKind::Value(PropertyType::Bool),
+                    "u32" => FieldKind::Value(PropertyType::U32),
+                    "f32" => FieldKind::Value(PropertyType::F32),
+                    "vec2" => FieldKind::Value(PropertyType::Vec2),
+                    "vec3" => FieldKind::Value(PropertyType::Vec3),
+                    "color" => FieldKind::Value(PropertyType::Color),
+                    "string" => FieldKind::Value(PropertyType::String),
+                    "asset" => FieldKind::Value(PropertyType::Asset),
+                    name => FieldKind::Struct(*struct_indices.get(name).ok_or_else(|| {
+                        format!("{}.{} has unknown type \"{}\"", struct_name, field.name, name)
+                    })?),
+                };
+            }
+            if let Some(object_type) = object_type {
+                if schema.objects.insert(object_type, s).is_some() {
+                    return Err(format!("Object type 0x{:02X} is declared twice", object_type))
+                }
+            } else {
+                schema.structs.push(s);
+            }
+        }
+        Ok(schema)
+    }
+
+    pub fn object_name(&self, object_type: u8) -> Option<&str>
+    {
+        self.objects.get(&object_type).map(|s| &s.name[..])
+    }
+
+    pub fn object_type(&self, name: &str) -> Option<u8>
+    {
+        self.objects.iter()
+            .find(|(_, s)| s.name == name)
+            .map(|(object_type, _)| *object_type)
+    }
+
+    /// Finds the byte range and type of the property named by `path` (a '.' separated list
+    /// of field names, e.g. "PatternedInfo.Health.Health") within an object's property data.
+    pub fn locate(&self, object_type: u8, data: &[u8], path: &str)
+        -> Result<(Range<usize>, PropertyType), String>
+    {
+        let s = self.objects.get(&object_type)
+            .ok_or_else(|| format!("No property schema for object type 0x{:02X}", object_type))?;
+        let path: Vec<_> = path.split('.').collect();
+        self.locate_in_struct(s, data, 0, &path)
+    }
+
+    pub fn get_property(&self, object_type: u8, data: &[u8], path: &str)
+        -> Result<PropertyValue, String>
+    {
+        let (range, ty) = self.locate(object_type, data, path)?;
+        Ok(PropertyValue::read(ty, &data[range]))
+    }
+
+    pub fn set_property(&self, object_type: u8, data: &mut Vec<u8>, path: &str, value: PropertyValue)
+        -> Result<(), String>
+    {
+        let (range, ty) = self.locate(object_type, data, path)?;
+        if value.property_type() != ty {
+            return Err(format!("Property {} is a {}, not a {}", path, ty, value.property_type()))
+        }
+        data.splice(range, value.to_bytes());
+        Ok(())
+    }
+
+    fn locate_in_struct(&self, s: &SchemaStruct, data: &[u8], offset: usize, path: &[&str])
+        -> Result<(Range<usize>, PropertyType), String>
+    {
+        let (mut offset, count) = self.struct_header(s, data, offset)?;
+        for field in s.fields.iter().take(count) {
+            if field.name.eq_ignore_ascii_case(path[0]) {
+                return match (field.kind, path.len()) {
+                    (FieldKind::Value(ty), 1) => {
+                        let size = self.field_size(field.kind, data, offset)?;
+                        Ok((offset..offset + size, ty))
+                    },
+                    (FieldKind::Struct(idx), len) if len > 1 =>
+                        self.locate_in_struct(&self.structs[idx], data, offset, &path[1..]),
+                    (FieldKind::Struct(_), _) =>
+                        Err(format!("{}.{} is a struct, not a property", s.name, field.name)),
+                    (FieldKind::Value(_), _) =>
+                        Err(format!("{}.{} is not a struct", s.name, field.name)),
+                };
+            }
+            offset += self.field_size(field.kind, data, offset)?;
+        }
+        Err(format!("{} has no property named {}", s.name, path[0]))
+    }
+
+    /// Returns the offset of the struct's first field and the number of fields present
+    fn struct_header(&self, s: &SchemaStruct, data: &[u8], offset: usize)
+        -> Result<(usize, usize), String>
+    {
+        if s.counted {
+            Ok((offset + 4, read_u32(data, offset)? as usize))
+        } else {
+            Ok((offset, s.fields.len()))
+        }
+    }
+
+    fn field_size(&self, kind: FieldKind, data: &[u8], offset: usize) -> Result<usize, String>
+    {
+        let size = match kind {
+            FieldKind::Value(PropertyType::Bool) => 1,
+            FieldKind::Value(PropertyType::U32) => 4,
+            FieldKind::Value(PropertyType::F32) => 4,
+            FieldKind::Value(PropertyType::Asset) => 4,
+            FieldKind::Value(PropertyType::Vec2) => 8,
+            FieldKind::Value(PropertyType::Vec3) => 12,
+            FieldKind::Value(PropertyType::Color) => 16,
+            FieldKind::Value(PropertyType::String) => {
+                data.get(offset..)
+                    .and_then(|rest| rest.iter().position(|b| *b == 0))
+                    .ok_or_else(|| "Unterminated string in property data".to_owned())? + 1
+            },
+            FieldKind::Struct(idx) => {
+                let s = &self.structs[idx];
+                let (fields_offset, count) = self.struct_header(s, data, offset)?;
+                if count > s.fields.len() {
+                    return Err(format!("The property schema for {} is incomplete", s.name))
+                }
+                let mut end = fields_offset;
+                for field in &s.fields[..count] {
+                    end += self.field_size(field.kind, data, end)?;
+                }
+                end - offset
+            },
+        };
+        if offset + size > data.len() {
+            return Err("Property data ends unexpectedly".to_owned())
+        }
+        Ok(size)
+    }
+}
+
+#[test]
+fn test_scly_schema_property_access()
+{
+    use crate::{SclyObject, scly_props::Timer};
+    use reader_writer::CStrConversionExtension;
+
+    assert!(scly_schema().object_name(0x4D).is_some());
+
+    let mut obj = SclyObject {
+        instance_id: 0,
+        connections: vec![].into(),
+        property_data: Timer {
+            name: b"Timer\0".as_cstr(),
+            start_time: 3.0,
+            max_random_add: 0.0,
+            reset_to_zero: 0,
+            start_immediately: 1,
+            active: 1,
+        }.into(),
+    };
+
+    assert_eq!(obj.get_property("StartTime"), Ok(PropertyValue::F32(3.0)));
+    obj.set_property("Name", PropertyValue::String("A longer timer name".to_owned())).unwrap();
+    obj.set_property("active", PropertyValue::Bool(false)).unwrap();
+    assert!(obj.set_property("StartTime", PropertyValue::U32(1)).is_err());
+    assert!(obj.get_property("Missing").is_err());
+
+    let timer = obj.property_data.as_timer().unwrap();
+    assert_eq!(timer.name.to_str().unwrap(), "A longer timer name");
+    assert_eq!(timer.start_time, 3.0);
+    assert_eq!(timer.start_immediately, 1);
+    assert_eq!(timer.active, 0);
+}